@@ -0,0 +1,85 @@
+//! Measures how many samples per second the oscillators and envelopes
+//! render at, across block sizes from a single sample up to 1024.
+//!
+//! Run with `cargo bench -p catalina-engine --features std`. The
+//! throughput should stay roughly flat as the blocks get smaller.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use catalina_engine::{
+    audio::{
+        AudioSource,
+        envelope::adsr::Envelope,
+        oscillator::{
+            Oscillator, OscillatorType, RuntimeOscillator, ToneSource,
+            variable::VariableShapeOscillator,
+        },
+    },
+    core::Hertz,
+};
+
+const SAMPLE_RATE: usize = 48_000;
+
+const BLOCK_SIZES: [usize; 5] = [1, 16, 64, 256, 1024];
+
+/// The amount of samples rendered per iteration, in blocks of the size being measured.
+const SAMPLES: usize = 4096;
+
+fn bench_oscillators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("oscillators");
+    group.throughput(Throughput::Elements(SAMPLES as u64));
+
+    for block_size in BLOCK_SIZES {
+        let mut buffer = vec![0.0_f32; block_size];
+
+        for osc_type in [OscillatorType::Sine, OscillatorType::Saw] {
+            let mut osc = RuntimeOscillator::new(osc_type, SAMPLE_RATE, Hertz(440.0));
+            let id = BenchmarkId::new(format!("runtime_{osc_type:?}"), block_size);
+            group.bench_function(id, |b| {
+                b.iter(|| {
+                    for _ in 0..SAMPLES / block_size {
+                        Oscillator::render(&mut osc, black_box(&mut buffer));
+                    }
+                })
+            });
+        }
+
+        let mut osc = VariableShapeOscillator::new(SAMPLE_RATE);
+        ToneSource::set_frequency(&mut osc, Hertz(440.0));
+        osc.set_waveshape(0.5);
+        group.bench_function(BenchmarkId::new("variable_shape", block_size), |b| {
+            b.iter(|| {
+                for _ in 0..SAMPLES / block_size {
+                    AudioSource::render(&mut osc, black_box(&mut buffer));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_envelope(c: &mut Criterion) {
+    let mut group = c.benchmark_group("envelope");
+    group.throughput(Throughput::Elements(SAMPLES as u64));
+
+    for block_size in BLOCK_SIZES {
+        let mut buffer = vec![0.0_f32; block_size];
+        let mut envelope = Envelope::new(SAMPLE_RATE);
+        envelope.set_sustain_hold(true);
+
+        group.bench_function(BenchmarkId::new("adsr", block_size), |b| {
+            b.iter(|| {
+                for _ in 0..SAMPLES / block_size {
+                    envelope.process_block(true, black_box(&mut buffer));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_oscillators, bench_envelope);
+criterion_main!(benches);
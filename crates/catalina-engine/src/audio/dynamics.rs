@@ -0,0 +1,303 @@
+//! Dynamics processors for controlling the level of a signal.
+
+use crate::core::{db::db_to_linear, param::one_pole_coefficient, ring_buffer};
+
+/// How many time constants the gain reduction takes to
+/// attack across the lookahead window (within ~0.7%).
+const ATTACK_TIME_CONSTANTS: f32 = 5.0;
+
+/// Returns the gain needed to bring a sample down to the ceiling.
+#[inline]
+fn required_gain(sample: f32, ceiling: f32) -> f32 {
+    let level = libm::fabsf(sample);
+    if level > ceiling {
+        ceiling / level
+    } else {
+        1.0
+    }
+}
+
+/// A brickwall limiter that guarantees it's output never exceeds the ceiling.
+///
+/// The input is delayed by `LOOKAHEAD` samples so that the gain can be
+/// reduced before a peak arrives rather than clipping it, which lets
+/// the output run much hotter than scaling it down by a fixed amount.
+pub struct BrickwallLimiter<const LOOKAHEAD: usize> {
+    /// The maximum absolute level of the output.
+    ceiling: f32,
+
+    /// One-pole coefficient for reducing the gain ahead of a peak.
+    attack: f32,
+    /// One-pole coefficient for recovering the gain after a peak.
+    release: f32,
+
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// Delays the input, along with the gain each sample needs.
+    delay: ring_buffer::Fixed<[(f32, f32); LOOKAHEAD]>,
+
+    /// The gain applied to the last output sample.
+    gain: f32,
+}
+
+impl<const LOOKAHEAD: usize> BrickwallLimiter<LOOKAHEAD> {
+    /// Constructs a new limiter with a linear ceiling and a release time in seconds.
+    ///
+    /// Panics if `LOOKAHEAD` is zero.
+    pub fn new(sample_rate: usize, ceiling: f32, release: f32) -> Self {
+        assert!(
+            LOOKAHEAD > 0,
+            "The limiter needs at least one sample of lookahead."
+        );
+
+        let lookahead = LOOKAHEAD as f32 / sample_rate as f32;
+
+        Self {
+            ceiling,
+            attack: one_pole_coefficient(lookahead / ATTACK_TIME_CONSTANTS, sample_rate),
+            release: one_pole_coefficient(release, sample_rate),
+            sample_rate,
+            delay: ring_buffer::Fixed::from([(0.0, 1.0); LOOKAHEAD]),
+            gain: 1.0,
+        }
+    }
+
+    /// Sets the maximum absolute level of the output.
+    pub fn set_ceiling(&mut self, ceiling: f32) {
+        self.ceiling = ceiling;
+    }
+
+    /// Sets the maximum level of the output in decibels, e.g. -0.3 dBFS.
+    pub fn set_ceiling_decibels(&mut self, decibels: f32) {
+        self.ceiling = db_to_linear(decibels);
+    }
+
+    /// Sets the time in seconds it takes the gain to recover after a peak.
+    pub fn set_release_time(&mut self, seconds: f32) {
+        self.release = one_pole_coefficient(seconds, self.sample_rate);
+    }
+
+    /// Returns the delay the limiter adds to the signal in samples.
+    pub const fn latency(&self) -> usize {
+        LOOKAHEAD
+    }
+
+    /// Returns the gain currently being applied, 1.0 is no reduction.
+    pub const fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Limits a single sample, returning the delayed limited output.
+    pub fn process(&mut self, input: f32) -> f32 {
+        let (delayed, delayed_gain) = self.delay.push((input, required_gain(input, self.ceiling)));
+
+        // The lowest gain needed by anything in the lookahead window.
+        let target = self
+            .delay
+            .iter()
+            .fold(delayed_gain, |target, (_, gain)| target.min(*gain));
+
+        let coefficient = if target < self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain += (target - self.gain) * coefficient;
+
+        // The attack might not have fully caught up to the peak, so never
+        // let the delayed sample through with more gain than it allows.
+        self.gain = self.gain.min(delayed_gain);
+
+        // Guard against rounding pushing the output a hair over the ceiling.
+        (delayed * self.gain).clamp(-self.ceiling, self.ceiling)
+    }
+
+    /// Limits a buffer of samples in place.
+    pub fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
+/// Ducks a signal whenever a separate sidechain signal is loud, i.e.
+/// for the pumping of a synth pad that gets out of the way of a kick.
+///
+/// The sidechain can be any audio or control signal, such as the kick
+/// itself, it's envelope, or a pulse on the beat of the transport. It's
+/// level is followed with an instant attack and the release time, and
+/// while it's over the threshold the input is turned down by the depth.
+pub struct Ducker {
+    /// The sidechain level the ducking starts at.
+    threshold: f32,
+    /// How far the input is turned down, from 0.0 (not at all) to 1.0 (silent).
+    depth: f32,
+
+    /// One-pole coefficient for turning the gain down.
+    attack: f32,
+    /// One-pole coefficient for recovering the gain, and letting go of the sidechain level.
+    release: f32,
+
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// The followed level of the sidechain.
+    level: f32,
+    /// The gain applied to the last output sample.
+    gain: f32,
+}
+
+impl Ducker {
+    /// Constructs a new ducker that turns the input down by half
+    /// when the sidechain goes over a linear threshold of 0.5.
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            threshold: 0.5,
+            depth: 0.5,
+            attack: one_pole_coefficient(0.005, sample_rate),
+            release: one_pole_coefficient(0.15, sample_rate),
+            sample_rate,
+            level: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Sets the linear sidechain level the ducking starts at.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Sets how far the input is turned down, from 0.0 (not at all) to 1.0 (silent).
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Sets how far the input is turned down in decibels, e.g. -12 dB.
+    pub fn set_depth_decibels(&mut self, decibels: f32) {
+        self.set_depth(1.0 - db_to_linear(decibels));
+    }
+
+    /// Sets the time in seconds it takes the gain to duck once the sidechain is loud.
+    pub fn set_attack_time(&mut self, seconds: f32) {
+        self.attack = one_pole_coefficient(seconds, self.sample_rate);
+    }
+
+    /// Sets the time in seconds it takes the gain to recover once the sidechain is quiet.
+    pub fn set_release_time(&mut self, seconds: f32) {
+        self.release = one_pole_coefficient(seconds, self.sample_rate);
+    }
+
+    /// Returns the gain applied to the last output sample, from 0.0 to 1.0.
+    pub const fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Processes a single sample of the input, ducked by the sidechain.
+    pub fn process(&mut self, input: f32, sidechain: f32) -> f32 {
+        let level = libm::fabsf(sidechain);
+        if level > self.level {
+            self.level = level;
+        } else {
+            self.level += (level - self.level) * self.release;
+        }
+
+        let target = if self.level > self.threshold {
+            1.0 - self.depth
+        } else {
+            1.0
+        };
+        let coefficient = if target < self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain += (target - self.gain) * coefficient;
+
+        input * self.gain
+    }
+
+    /// Processes a buffer of the input in place, ducked by a sidechain buffer.
+    ///
+    /// Only as many samples as both buffers hold are processed.
+    pub fn process_buffer(&mut self, buffer: &mut [f32], sidechain: &[f32]) {
+        for (sample, sidechain) in buffer.iter_mut().zip(sidechain) {
+            *sample = self.process(*sample, *sidechain);
+        }
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::oscillator::sample_sine;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_never_exceeds_ceiling() {
+        let mut limiter = BrickwallLimiter::<48>::new(48_000, 0.5, 0.05);
+
+        let mut buffer = [0.0; 4_800];
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            // Quiet to start, then a sudden jump well above the ceiling.
+            let amplitude = if i < 1_000 { 0.25 } else { 2.0 };
+            *sample = sample_sine::<f32>(i, 48_000, 440.0.into()) * amplitude;
+        }
+
+        limiter.process_buffer(&mut buffer);
+
+        let peak = buffer.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        assert!(peak <= 0.5, "output peaked at {peak}");
+        // The limiter should still be running close to the ceiling.
+        assert!(peak > 0.45, "output peaked at {peak}");
+    }
+
+    #[test]
+    fn test_passes_quiet_signals() {
+        let mut limiter = BrickwallLimiter::<4>::new(48_000, 1.0, 0.05);
+
+        let input = [0.1, -0.2, 0.3, -0.4, 0.5, -0.6, 0.7, -0.8];
+        let output = input.map(|sample| limiter.process(sample));
+
+        // Quiet signals are only delayed by the lookahead.
+        self::assert_eq!(output, [0.0, 0.0, 0.0, 0.0, 0.1, -0.2, 0.3, -0.4]);
+        self::assert_eq!(limiter.gain(), 1.0);
+    }
+
+    #[test]
+    fn test_ducks_with_sidechain_pulses() {
+        let mut ducker = Ducker::new(48_000);
+        ducker.set_depth(0.75);
+        ducker.set_attack_time(0.001);
+        ducker.set_release_time(0.02);
+
+        // A 10ms pulse on every beat at 120bpm.
+        let pulse = |i: usize| if i % 24_000 < 480 { 1.0 } else { 0.0 };
+
+        let mut output = [0.0; 96_000];
+        for (i, sample) in output.iter_mut().enumerate() {
+            *sample = ducker.process(0.8, pulse(i));
+        }
+
+        for beat in 0..4 {
+            let start = beat * 24_000;
+            // Ducked down to a quarter by the end of the pulse.
+            let ducked = output[start + 470];
+            assert!(
+                (ducked - 0.2).abs() < 0.01,
+                "beat {beat} ducked to {ducked}"
+            );
+            // Recovered well before the next beat.
+            let recovered = output[start + 23_000];
+            assert!(
+                (recovered - 0.8).abs() < 0.01,
+                "beat {beat} recovered to {recovered}"
+            );
+        }
+
+        // Nothing is ducked before the first pulse arrives.
+        self::assert_eq!(Ducker::new(48_000).process(0.8, 0.0), 0.8);
+    }
+}
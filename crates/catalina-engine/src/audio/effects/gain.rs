@@ -0,0 +1,60 @@
+//! A fixed gain stage.
+
+use super::Effect;
+use crate::core::db::{db_to_linear, linear_to_db};
+
+/// Scales a signal by a linear gain.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Gain {
+    gain: f32,
+}
+
+impl Gain {
+    /// Constructs a new gain stage with a linear gain.
+    pub const fn new(gain: f32) -> Self {
+        Self { gain }
+    }
+
+    /// Constructs a new gain stage from a gain in decibels.
+    pub fn from_decibels(decibels: f32) -> Self {
+        Self::new(db_to_linear(decibels))
+    }
+
+    /// Returns the linear gain.
+    pub const fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the linear gain.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// Returns the gain in decibels.
+    pub fn decibels(&self) -> f32 {
+        linear_to_db(self.gain)
+    }
+
+    /// Sets the gain in decibels.
+    pub fn set_decibels(&mut self, decibels: f32) {
+        self.gain = db_to_linear(decibels);
+    }
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Effect for Gain {
+    fn process(&mut self, input: f32) -> f32 {
+        input * self.gain
+    }
+
+    fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample *= self.gain;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Effects that process an audio signal sample by sample.
+
+pub mod phaser;
+pub use phaser::Phaser;
+
+pub mod ping_pong;
+pub use ping_pong::PingPongDelay;
+
+pub mod gain;
+pub use gain::Gain;
+
+pub mod tremolo;
+pub use tremolo::Tremolo;
+
+pub mod oversample;
+pub use oversample::{Oversampler, Oversampling};
+
+#[cfg(feature = "alloc")]
+pub mod chain;
+#[cfg(feature = "alloc")]
+pub use chain::Chain;
+
+use crate::audio::{
+    dynamics::BrickwallLimiter,
+    filter::{CombResonator, Lowpass},
+};
+
+/// A mono processor that turns one input sample into one output sample.
+///
+/// Implemented by the effects, filters and dynamics processors so
+/// they can be swapped or strung together, see [`Chain`].
+pub trait Effect {
+    /// Processes a single sample through the effect.
+    fn process(&mut self, input: f32) -> f32;
+
+    /// Processes a buffer of samples in place.
+    fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
+impl<const STAGES: usize> Effect for Phaser<STAGES> {
+    fn process(&mut self, input: f32) -> f32 {
+        Phaser::process(self, input)
+    }
+}
+
+impl<const LOOKAHEAD: usize> Effect for BrickwallLimiter<LOOKAHEAD> {
+    fn process(&mut self, input: f32) -> f32 {
+        BrickwallLimiter::process(self, input)
+    }
+}
+
+impl<const MAX_DELAY: usize> Effect for CombResonator<MAX_DELAY> {
+    fn process(&mut self, input: f32) -> f32 {
+        CombResonator::process(self, input)
+    }
+}
+
+impl Effect for Lowpass {
+    fn process(&mut self, input: f32) -> f32 {
+        Lowpass::process(self, input)
+    }
+}
@@ -0,0 +1,212 @@
+//! Runs an effect at a multiple of the sample rate to reduce aliasing.
+
+use crate::{
+    audio::{
+        effects::Effect,
+        filter::Lowpass,
+        interpolate::{Interpolator, linear::Linear},
+    },
+    core::Hertz,
+};
+
+/// How many times the sample rate an [`Oversampler`] runs it's effect at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Oversampling {
+    X2,
+    X4,
+}
+
+impl Oversampling {
+    /// Returns the multiple of the sample rate.
+    pub const fn factor(self) -> usize {
+        match self {
+            Oversampling::X2 => 2,
+            Oversampling::X4 => 4,
+        }
+    }
+}
+
+/// The resonance that makes each lowpass stage close to a Butterworth response.
+const BUTTERWORTH_RESONANCE: f32 = 0.3;
+
+/// Runs an effect at 2x or 4x the sample rate.
+///
+/// Nonlinear effects like waveshapers add harmonics above the input,
+/// any that land past Nyquist fold back down as inharmonic aliasing.
+/// Running the effect at a higher rate gives the harmonics room, they
+/// are then filtered out before the output is brought back down.
+///
+/// The input is upsampled with the [`Linear`] interpolator, and the output
+/// is lowpassed just below the original Nyquist and decimated. The effect
+/// sees the higher rate, so construct it with the oversampled rate
+/// (see [`Oversampler::effect_sample_rate`]) if it depends on it.
+///
+/// Upsampling adds a sample of latency.
+pub struct Oversampler<E: Effect> {
+    effect: E,
+    oversampling: Oversampling,
+    /// The sample rate of the signal going in and out of the oversampler.
+    sample_rate: usize,
+
+    upsampler: Linear<f32>,
+    /// Two stages for a steeper slope than a single lowpass.
+    downsampler: [Lowpass; 2],
+}
+
+impl<E: Effect> Oversampler<E> {
+    /// Wraps an effect to run at a multiple of the provided sample rate.
+    pub fn new(effect: E, sample_rate: usize, oversampling: Oversampling) -> Self {
+        let rate = sample_rate * oversampling.factor();
+        let cutoff = Hertz(sample_rate as f32 * 0.45);
+
+        let downsampler = core::array::from_fn(|_| {
+            let mut filter = Lowpass::new(rate);
+            filter.set_cutoff(cutoff);
+            filter.set_resonance(BUTTERWORTH_RESONANCE);
+            filter
+        });
+
+        Self {
+            effect,
+            oversampling,
+            sample_rate,
+            upsampler: Linear::new(0.0, 0.0),
+            downsampler,
+        }
+    }
+
+    /// Returns the sample rate the wrapped effect is run at.
+    pub fn effect_sample_rate(&self) -> usize {
+        self.sample_rate * self.oversampling.factor()
+    }
+
+    /// Returns the wrapped effect.
+    pub fn effect(&self) -> &E {
+        &self.effect
+    }
+
+    /// Returns the wrapped effect mutably, i.e. to change its settings.
+    pub fn effect_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+
+    /// Unwraps the effect.
+    pub fn into_effect(self) -> E {
+        self.effect
+    }
+
+    /// Clears the resampling state, i.e. when the input jumps.
+    pub fn reset(&mut self) {
+        self.upsampler.reset();
+        for filter in self.downsampler.iter_mut() {
+            filter.reset();
+        }
+    }
+
+    /// Processes a single sample through the effect at the oversampled rate.
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.upsampler.next_source_frame(input);
+
+        let factor = self.oversampling.factor();
+        let mut output = 0.0;
+        for index in 0..factor {
+            let upsampled = self.upsampler.interpolate(index as f64 / factor as f64);
+
+            let mut sample = self.effect.process(upsampled);
+            for filter in self.downsampler.iter_mut() {
+                sample = filter.process(sample);
+            }
+
+            // Only the last sample is kept, the rest just run the filters.
+            output = sample;
+        }
+
+        output
+    }
+}
+
+impl<E: Effect> Effect for Oversampler<E> {
+    fn process(&mut self, input: f32) -> f32 {
+        Oversampler::process(self, input)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::oscillator::{OscillatorType, RuntimeOscillator};
+    use crate::audio::signal::Signal;
+    use pretty_assertions::assert_eq;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    /// Drives the signal hard into a clipper, adding lots of odd harmonics.
+    struct HardClip;
+
+    impl Effect for HardClip {
+        fn process(&mut self, input: f32) -> f32 {
+            (input * 4.0).clamp(-1.0, 1.0)
+        }
+    }
+
+    /// The power of `buffer` at `frequency`, using the Goertzel algorithm.
+    fn power_at(buffer: &[f32], frequency: f32) -> f32 {
+        let w = 2.0 * core::f32::consts::PI * frequency / SAMPLE_RATE as f32;
+        let coeff = 2.0 * libm::cosf(w);
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in buffer {
+            let s0 = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coeff * s1 * s2
+    }
+
+    /// Clips a 5kHz sine and returns the power where the harmonics
+    /// past Nyquist fold back to, none of which are harmonics of 5kHz.
+    fn aliasing(effect: &mut impl Effect) -> f32 {
+        let mut sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(5_000.0));
+        let output: [f32; SAMPLE_RATE / 10] = core::array::from_fn(|_| effect.process(sine.next()));
+
+        // The 7th, 9th, 11th and 13th harmonics.
+        [13_000.0, 3_000.0, 7_000.0, 17_000.0]
+            .into_iter()
+            .map(|alias| power_at(&output, alias))
+            .sum()
+    }
+
+    #[test]
+    fn test_reduces_aliasing() {
+        let base = aliasing(&mut HardClip);
+        let x2 = aliasing(&mut Oversampler::new(
+            HardClip,
+            SAMPLE_RATE,
+            Oversampling::X2,
+        ));
+        let x4 = aliasing(&mut Oversampler::new(
+            HardClip,
+            SAMPLE_RATE,
+            Oversampling::X4,
+        ));
+
+        // Both cut the aliasing by well over 10dB.
+        assert!(x2 < base / 10.0, "2x aliasing {x2} vs {base}");
+        assert!(x4 < base / 10.0, "4x aliasing {x4} vs {base}");
+    }
+
+    #[test]
+    fn test_passes_fundamental() {
+        let mut oversampler = Oversampler::new(HardClip, SAMPLE_RATE, Oversampling::X4);
+        self::assert_eq!(oversampler.effect_sample_rate(), SAMPLE_RATE * 4);
+
+        // A quiet, low sine isn't clipped and comes through at the same level.
+        let mut sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(100.0));
+        let peak: f32 = (0..SAMPLE_RATE / 2)
+            .map(|_| oversampler.process(sine.next() * 0.1))
+            .skip(SAMPLE_RATE / 4)
+            .fold(0.0, |peak, sample: f32| peak.max(sample.abs()));
+        assert!((peak - 0.4).abs() < 0.01, "peaked at {peak}");
+    }
+}
@@ -0,0 +1,150 @@
+//! A phaser effect built from a chain of swept all-pass filters.
+
+use crate::{
+    audio::{filter::allpass::AllPass, lfo::Lfo, oscillator::OscillatorType, signal::Signal},
+    core::Hertz,
+};
+
+/// The amount of octaves the notches sweep either side of the center at full depth.
+const SWEEP_OCTAVES: f32 = 2.0;
+
+/// A phaser that mixes a signal with a phase-shifted copy of itself.
+///
+/// The phase shift comes from a chain of `STAGES` all-pass filters, every
+/// two stages add a notch to the spectrum where the copy cancels out the
+/// dry signal. An LFO sweeps the notches up and down for the classic
+/// whooshing sound, and feedback sharpens the notches.
+#[derive(Debug, Clone)]
+pub struct Phaser<const STAGES: usize> {
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// Sweeps the break frequency of the all-pass filters.
+    lfo: Lfo,
+    /// How far the sweep reaches from 0.0 (none) to 1.0 (two octaves either way).
+    depth: f32,
+    /// The frequency the sweep is centered around.
+    center: Hertz,
+    /// How much of the phase-shifted signal is fed back into the chain.
+    feedback: f32,
+
+    stages: [AllPass; STAGES],
+    /// The last output of the all-pass chain, for feedback.
+    last: f32,
+}
+
+impl<const STAGES: usize> Phaser<STAGES> {
+    /// Constructs a new phaser sweeping around 1kHz at 0.5Hz.
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            sample_rate,
+            lfo: Lfo::new(OscillatorType::Sine, sample_rate, Hertz(0.5)),
+            depth: 0.5,
+            center: Hertz(1_000.0),
+            feedback: 0.0,
+            stages: [AllPass::default(); STAGES],
+            last: 0.0,
+        }
+    }
+
+    /// Sets the rate of the sweep.
+    pub fn set_rate(&mut self, rate: Hertz) {
+        self.lfo.set_frequency(rate);
+    }
+
+    /// Sets how far the sweep reaches, from 0.0 (none) to 1.0 (two octaves either way).
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Sets the frequency the sweep is centered around.
+    pub fn set_center_frequency(&mut self, center: Hertz) {
+        self.center = center;
+    }
+
+    /// Sets how much of the phase-shifted signal is fed back, clamped to ±0.95.
+    ///
+    /// Negative feedback moves the notches, positive feedback sharpens them.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(-0.95, 0.95);
+    }
+
+    /// Processes a single sample through the phaser.
+    pub fn process(&mut self, input: f32) -> f32 {
+        let octaves = self.lfo.next() * self.depth * SWEEP_OCTAVES;
+
+        // Keep the break frequency safely below nyquist.
+        let nyquist = self.sample_rate as f32 / 2.0;
+        let frequency = (self.center.hertz() * libm::exp2f(octaves)).min(nyquist * 0.95);
+
+        // Bilinear transform of the break frequency into the all-pass coefficient.
+        let t = libm::tanf(core::f32::consts::PI * frequency / self.sample_rate as f32);
+        let coefficient = (t - 1.0) / (t + 1.0);
+
+        let mut wet = input + self.last * self.feedback;
+        for stage in self.stages.iter_mut() {
+            wet = stage.process(wet, coefficient);
+        }
+        self.last = wet;
+
+        (input + wet) * 0.5
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::oscillator::sample_sine;
+
+    /// Returns the range of the RMS level of the phaser output
+    /// across 10ms windows, which grows with the sweep.
+    fn level_range(depth: f32) -> f32 {
+        let mut phaser = Phaser::<4>::new(48_000);
+        phaser.set_depth(depth);
+
+        let (mut min, mut max) = (f32::MAX, f32::MIN);
+        for window in 0..200 {
+            let mut sum = 0.0;
+            for i in 0..480 {
+                let index = window * 480 + i;
+                let output = phaser.process(sample_sine(index, 48_000, Hertz(1_000.0)));
+                sum += output * output;
+            }
+
+            // Skip the first window while the filters settle.
+            if window > 0 {
+                let rms = libm::sqrtf(sum / 480.0);
+                min = min.min(rms);
+                max = max.max(rms);
+            }
+        }
+
+        max - min
+    }
+
+    #[test]
+    fn test_differs_from_dry() {
+        let mut phaser = Phaser::<4>::new(48_000);
+
+        let mut difference = 0.0;
+        for i in 0..4_800 {
+            let dry: f32 = sample_sine(i, 48_000, Hertz(1_000.0));
+            difference += libm::fabsf(phaser.process(dry) - dry);
+        }
+
+        assert!(difference > 100.0, "difference was {difference}");
+    }
+
+    #[test]
+    fn test_depth_widens_sweep() {
+        let none = level_range(0.0);
+        let shallow = level_range(0.25);
+        let deep = level_range(1.0);
+
+        assert!(none < 0.01, "no depth still swept by {none}");
+        assert!(shallow > none, "{shallow} <= {none}");
+        assert!(deep > shallow, "{deep} <= {shallow}");
+    }
+}
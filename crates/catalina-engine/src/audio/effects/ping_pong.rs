@@ -0,0 +1,135 @@
+//! A stereo delay that bounces echoes between the left and right channels.
+
+use crate::audio::frame::Stereo;
+
+/// A stereo delay whose echoes alternate between channels.
+///
+/// Each channel has it's own delay line, but the input and the feedback
+/// of each line are crossed over into the other, so a sound on the left
+/// echoes first on the right, then on the left, and so on.
+///
+/// `MAX_DELAY` is the length of each delay line in samples, bounding the
+/// longest delay time to `MAX_DELAY` divided by the sample rate.
+#[derive(Debug, Clone)]
+pub struct PingPongDelay<const MAX_DELAY: usize> {
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// The time between echoes in samples.
+    delay: usize,
+    /// How much of each echo is fed into the next, from 0.0 to just under 1.0.
+    feedback: f32,
+    /// The balance of dry to delayed signal, from 0.0 (dry) to 1.0 (wet).
+    mix: f32,
+
+    left: [f32; MAX_DELAY],
+    right: [f32; MAX_DELAY],
+    /// The index of the next sample to be written in both lines.
+    write: usize,
+}
+
+impl<const MAX_DELAY: usize> PingPongDelay<MAX_DELAY> {
+    /// Constructs a new delay echoing every 250ms at half level, half wet.
+    pub fn new(sample_rate: usize) -> Self {
+        let mut delay = Self {
+            sample_rate,
+            delay: 1,
+            feedback: 0.5,
+            mix: 0.5,
+            left: [0.0; MAX_DELAY],
+            right: [0.0; MAX_DELAY],
+            write: 0,
+        };
+
+        delay.set_time(0.25);
+
+        delay
+    }
+
+    /// Sets the time between echoes in seconds.
+    ///
+    /// Clamped between one sample and the length of the delay lines.
+    pub fn set_time(&mut self, seconds: f32) {
+        let samples = libm::roundf(seconds * self.sample_rate as f32).max(1.0) as usize;
+        self.delay = samples.min(MAX_DELAY.max(1));
+    }
+
+    /// Sets how much of each echo is fed into the next, clamped to 0.0..0.99.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(0.0, 0.99);
+    }
+
+    /// Sets the balance of dry to delayed signal, from 0.0 (dry) to 1.0 (wet).
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Silences any echoes still in the delay lines.
+    pub fn reset(&mut self) {
+        self.left = [0.0; MAX_DELAY];
+        self.right = [0.0; MAX_DELAY];
+    }
+
+    /// Processes a single stereo frame through the delay.
+    pub fn process(&mut self, input: Stereo<f32>) -> Stereo<f32> {
+        if MAX_DELAY == 0 {
+            return input;
+        }
+
+        let read = (self.write + MAX_DELAY - self.delay) % MAX_DELAY;
+        let (left, right) = (self.left[read], self.right[read]);
+
+        // Cross the input and feedback over so echoes alternate sides.
+        self.left[self.write] = input[1] + self.feedback * right;
+        self.right[self.write] = input[0] + self.feedback * left;
+        self.write = (self.write + 1) % MAX_DELAY;
+
+        let dry = 1.0 - self.mix;
+        [
+            input[0] * dry + left * self.mix,
+            input[1] * dry + right * self.mix,
+        ]
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_echoes_alternate_sides() {
+        let mut delay = PingPongDelay::<16>::new(1_000);
+        delay.set_time(0.01);
+        delay.set_feedback(0.5);
+        delay.set_mix(1.0);
+
+        let mut output = [[0.0f32; 2]; 45];
+        for (i, frame) in output.iter_mut().enumerate() {
+            let input = if i == 0 { [1.0, 0.0] } else { [0.0, 0.0] };
+            *frame = delay.process(input);
+        }
+
+        // Right first, then left, then right, halving each time.
+        self::assert_eq!(output[10], [0.0, 1.0]);
+        self::assert_eq!(output[20], [0.5, 0.0]);
+        self::assert_eq!(output[30], [0.0, 0.25]);
+        self::assert_eq!(output[40], [0.125, 0.0]);
+
+        // Silent between the echoes.
+        for (i, frame) in output.iter().enumerate() {
+            if i % 10 != 0 || i == 0 {
+                self::assert_eq!(*frame, [0.0, 0.0], "frame {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dry_mix_passes_input() {
+        let mut delay = PingPongDelay::<16>::new(1_000);
+        delay.set_mix(0.0);
+        self::assert_eq!(delay.process([0.3, -0.6]), [0.3, -0.6]);
+    }
+}
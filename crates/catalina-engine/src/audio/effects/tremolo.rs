@@ -0,0 +1,103 @@
+//! A tremolo effect that modulates the level of a signal with an LFO.
+
+use crate::{
+    audio::{effects::Effect, lfo::Lfo, oscillator::OscillatorType, signal::Signal},
+    core::Hertz,
+};
+
+/// Pulses the level of a signal by multiplying it with a unipolar LFO.
+///
+/// The amplitude counterpart to vibrato, at full depth the level
+/// swings all the way down to silence once per cycle of the LFO.
+#[derive(Debug, Clone)]
+pub struct Tremolo {
+    lfo: Lfo,
+    /// How far the level dips from 0.0 (none) to 1.0 (down to silence).
+    depth: f32,
+}
+
+impl Tremolo {
+    /// Constructs a new sine tremolo pulsing at 5Hz at half depth.
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            lfo: Lfo::new(OscillatorType::Sine, sample_rate, Hertz(5.0)),
+            depth: 0.5,
+        }
+    }
+
+    /// Sets the rate the level pulses at.
+    pub fn set_rate(&mut self, rate: Hertz) {
+        self.lfo.set_frequency(rate);
+    }
+
+    /// Sets how far the level dips, from 0.0 (none) to 1.0 (down to silence).
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Sets the shape of the LFO, i.e. a square for a choppy tremolo.
+    pub fn set_waveform(&mut self, shape: OscillatorType) {
+        self.lfo.set_shape(shape);
+    }
+
+    /// Processes a single sample through the tremolo.
+    pub fn process(&mut self, input: f32) -> f32 {
+        // Move the LFO from -1.0..1.0 to 0.0..1.0 so the level only ever dips.
+        let unipolar = (self.lfo.next() + 1.0) * 0.5;
+        input * (1.0 - self.depth * (1.0 - unipolar))
+    }
+}
+
+impl Effect for Tremolo {
+    fn process(&mut self, input: f32) -> f32 {
+        Tremolo::process(self, input)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_level_pulses_at_rate() {
+        let mut tremolo = Tremolo::new(48_000);
+        tremolo.set_rate(Hertz(4.0));
+        tremolo.set_depth(1.0);
+
+        // A constant input makes the output the level envelope itself.
+        let envelope: heapless::Vec<f32, 48_000> =
+            (0..48_000).map(|_| tremolo.process(1.0)).collect();
+
+        let low = envelope.iter().fold(1.0_f32, |a, b| a.min(*b));
+        let high = envelope.iter().fold(0.0_f32, |a, b| a.max(*b));
+        assert!(
+            low < 0.01 && high > 0.99,
+            "level swung from {low} to {high}"
+        );
+
+        // Crossing the middle on the way up once per cycle of the LFO,
+        // the first cycle starts on the crossing so isn't counted.
+        let crossings: heapless::Vec<usize, 8> = (1..envelope.len())
+            .filter(|&i| envelope[i - 1] < 0.5 && envelope[i] >= 0.5)
+            .collect();
+        self::assert_eq!(crossings.len(), 3);
+        for pair in crossings.windows(2) {
+            let period = pair[1] - pair[0];
+            assert!(period.abs_diff(12_000) <= 1, "period of {period}");
+        }
+    }
+
+    #[test]
+    fn test_zero_depth_is_transparent() {
+        let mut tremolo = Tremolo::new(48_000);
+        tremolo.set_depth(0.0);
+
+        for i in 0..1_000 {
+            let input = libm::sinf(i as f32 * 0.1);
+            self::assert_eq!(tremolo.process(input), input);
+        }
+    }
+}
@@ -1,10 +1,12 @@
 //! Implements the common attack, decay, sustain and release
 //! (ADSR) envelope used by most audio synthesis.
 
-/// Derrived from the C++ constant.
-const M_E: f32 = 2.71828182845904523536;
+use crate::core::param::one_pole_coefficient;
 
-#[derive(PartialEq, Eq)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EnvelopeStage {
     Init,
     Attack,
@@ -12,6 +14,39 @@ pub enum EnvelopeStage {
     Release,
 }
 
+/// The settings of an [`Envelope`], without any of it's running state.
+///
+/// Kept separate from the envelope's coefficients, which depend on
+/// the sample rate, so the settings can be saved with a patch and
+/// loaded back at any rate with [`Envelope::from_params`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EnvelopeParams {
+    /// The attack time in seconds.
+    pub attack: f32,
+    /// The curve of the attack, 0.0 for the default exponential rise.
+    pub attack_shape: f32,
+    /// The decay time in seconds.
+    pub decay: f32,
+    /// The sustain level from 0.0 to 1.0.
+    pub sustain: f32,
+    /// The release time in seconds.
+    pub release: f32,
+}
+
+impl Default for EnvelopeParams {
+    /// The same settings as a new [`Envelope`].
+    fn default() -> Self {
+        Self {
+            attack: 0.1,
+            attack_shape: 0.0,
+            decay: 0.1,
+            sustain: 0.0,
+            release: 0.1,
+        }
+    }
+}
+
 /// Implements the common attack, decay, sustain and release
 /// (ADSR) envelope used by most audio synthesis.
 ///
@@ -27,6 +62,7 @@ pub enum EnvelopeStage {
 /// In May of 2020 it was then ported from Soundpipe by Ben Sergentanis for DaisyDSP,
 /// and eventually remade by Steffan DIedrichsen in May of 2021. It was then adapted
 /// to Rust on December 2025 with several improvements by Kat Mitchell for Catalina.
+#[derive(Clone)]
 pub struct Envelope {
     /// The sample rate the audio engine is being ran at.
     sample_rate: usize,
@@ -61,6 +97,10 @@ pub struct Envelope {
     /// When this changes to true, it triggers the envelope's attack
     /// stage. When changed to false, it transitions to the release.
     gate: bool,
+    /// The gate signal as it was last passed in, before any sustain hold.
+    pressed: bool,
+    /// Keeps the gate held open after it's released, i.e. while a sustain pedal is down.
+    sustain_hold: bool,
     x: f32,
 }
 
@@ -82,6 +122,8 @@ impl Envelope {
             release_d0: 0.0,
             stage: EnvelopeStage::Init,
             gate: false,
+            pressed: false,
+            sustain_hold: false,
             x: 0.0,
         };
 
@@ -92,6 +134,52 @@ impl Envelope {
         adsr
     }
 
+    /// Constructs a new envelope from saved settings.
+    pub fn from_params(sample_rate: usize, params: &EnvelopeParams) -> Self {
+        let mut adsr = Self::new(sample_rate);
+        adsr.set_params(params);
+        adsr
+    }
+
+    /// Returns the envelope's settings, i.e. to save them with a patch.
+    pub fn to_params(&self) -> EnvelopeParams {
+        EnvelopeParams {
+            attack: self.attack_time,
+            attack_shape: self.attack_shape,
+            decay: self.decay_time,
+            // A sustain of zero is stored just below zero so the decay finishes.
+            sustain: self.sustain_level.max(0.0),
+            release: self.release_time,
+        }
+    }
+
+    /// Applies saved settings, keeping the current stage and level.
+    pub fn set_params(&mut self, params: &EnvelopeParams) {
+        self.set_attack_time(params.attack, params.attack_shape);
+        self.set_decay_time(params.decay);
+        self.set_sustain_level(params.sustain);
+        self.set_release_time(params.release);
+    }
+
+    /// Changes the sample rate, keeping the same stage times.
+    ///
+    /// The current level and stage are kept so a
+    /// sounding envelope continues without a jump.
+    pub fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.sample_rate = sample_rate;
+
+        // Clear the cached times so the coefficients are recalculated.
+        let (attack, shape) = (self.attack_time, self.attack_shape);
+        let (decay, release) = (self.decay_time, self.release_time);
+        self.attack_time = -1.0;
+        self.decay_time = -1.0;
+        self.release_time = -1.0;
+
+        self.set_attack_time(attack, shape);
+        self.set_decay_time(decay);
+        self.set_release_time(release);
+    }
+
     /// Configures the attack time ramp for the ADSR envelope.
     pub fn set_attack_time(&mut self, seconds: f32, shape: f32) {
         if (seconds != self.attack_time) || (shape != self.attack_shape) {
@@ -115,13 +203,7 @@ impl Envelope {
     pub fn set_decay_time(&mut self, seconds: f32) {
         if seconds != self.decay_time {
             self.decay_time = seconds;
-            if self.decay_time > 0.0 {
-                let target: f32 = libm::logf(1. / M_E);
-                self.decay_d0 =
-                    1.0 - libm::expf(target / (self.decay_time * self.sample_rate as f32));
-            } else {
-                self.decay_d0 = 1.0; // instant change
-            }
+            self.decay_d0 = one_pole_coefficient(self.decay_time, self.sample_rate);
         }
     }
 
@@ -131,13 +213,7 @@ impl Envelope {
     pub fn set_release_time(&mut self, seconds: f32) {
         if seconds != self.release_time {
             self.release_time = seconds;
-            if self.release_time > 0.0 {
-                let target: f32 = libm::logf(1. / M_E);
-                self.release_d0 =
-                    1.0 - libm::expf(target / (self.release_time * self.sample_rate as f32));
-            } else {
-                self.release_d0 = 1.0; // instant change
-            }
+            self.release_d0 = one_pole_coefficient(self.release_time, self.sample_rate);
         }
     }
 
@@ -153,24 +229,66 @@ impl Envelope {
         }
     }
 
-    /// Processes a single sample from the envelope.
+    /// Holds the envelope at it's sustain level after the gate is released,
+    /// like a sustain pedal. Turning the hold off releases the envelope if
+    /// the gate has been released in the meantime.
     ///
-    /// The returned float is a percentage of the current level of the envelope.
-    /// Multiply this by a sound source to apply the envelope to it.
+    /// Pressing the gate again while held retriggers the attack.
+    pub fn set_sustain_hold(&mut self, hold: bool) {
+        self.sustain_hold = hold;
+    }
+
+    /// Returns true if the envelope is holding it's sustain after gate-off.
+    pub const fn sustain_hold(&self) -> bool {
+        self.sustain_hold
+    }
+
+    /// Returns true if the envelope is silent and waiting for the gate.
+    pub fn is_idle(&self) -> bool {
+        self.stage == EnvelopeStage::Init
+    }
+
+    /// Silences the envelope immediately, skipping any release.
     ///
-    /// Gate triggers the envelope when true, and starts the decay/release
-    /// when false. This is typically tied to a note press/release
-    pub fn process(&mut self, gate: bool) -> f32 {
+    /// The gate is treated as released, so the next
+    /// gate-on starts a fresh attack from silence.
+    pub fn reset(&mut self) {
+        self.stage = EnvelopeStage::Init;
+        self.gate = false;
+        self.pressed = false;
+        self.x = 0.0;
+    }
+
+    /// Moves to the attack or release stage on the edges of the gate signal.
+    fn update_gate(&mut self, gate: bool) {
+        let retrigger = gate && !self.pressed;
+        self.pressed = gate;
+
+        // While held, a released gate keeps the envelope open.
+        let gate = gate || (self.sustain_hold && self.gate);
+
         // When the incoming gate signal is true and the local one
         // is false, that means we're seeing a rising edge and the
         // attack stage should be triggered.
-        if gate && !self.gate {
+        if retrigger || (gate && !self.gate) {
             self.stage = EnvelopeStage::Attack;
         } else if !gate && self.gate {
             // We're seeing a falling gate signal, and
             // should trigger the release stage.
             self.stage = EnvelopeStage::Release;
         }
+        self.gate = gate;
+    }
+
+    /// Processes a single sample from the envelope.
+    ///
+    /// The returned float is a percentage of the current level of the envelope.
+    /// Multiply this by a sound source to apply the envelope to it.
+    ///
+    /// Gate triggers the envelope when true, and starts the decay/release
+    /// when false. This is typically tied to a note press/release
+    pub fn process(&mut self, gate: bool) -> f32 {
+        self.update_gate(gate);
 
         // Determine which coefficiant to use depending
         // on the current stage of the envelope.
@@ -217,4 +335,192 @@ impl Envelope {
             }
         }
     }
+
+    /// Processes a block of samples from the envelope with the gate held
+    /// at the same state for the whole block, writing the levels to `out`.
+    ///
+    /// Produces the same levels as calling [Envelope::process] once per
+    /// sample, but only selects the stage and coefficient again when the
+    /// envelope crosses into a new stage part way through the block.
+    pub fn process_block(&mut self, gate: bool, out: &mut [f32]) {
+        self.update_gate(gate);
+
+        let mut i = 0;
+        while i < out.len() {
+            match self.stage {
+                EnvelopeStage::Init => {
+                    out[i..].fill(0.0);
+                    return;
+                }
+                EnvelopeStage::Attack => {
+                    let d0 = self.attack_d0;
+                    let target = self.attack_level;
+                    while i < out.len() {
+                        self.x += d0 * (target - self.x);
+                        if self.x > 1.0 {
+                            self.x = 1.0;
+                            out[i] = 1.0;
+                            i += 1;
+                            self.stage = EnvelopeStage::Decay;
+                            break;
+                        }
+                        out[i] = self.x;
+                        i += 1;
+                    }
+                }
+                EnvelopeStage::Decay | EnvelopeStage::Release => {
+                    let (d0, target) = if self.stage == EnvelopeStage::Decay {
+                        (self.decay_d0, self.sustain_level)
+                    } else {
+                        (self.release_d0, -0.01)
+                    };
+                    while i < out.len() {
+                        self.x += d0 * (target - self.x);
+                        if self.x < 0.0 {
+                            self.x = 0.0;
+                            out[i] = 0.0;
+                            i += 1;
+                            self.stage = EnvelopeStage::Init;
+                            break;
+                        }
+                        out[i] = self.x;
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_held_gate_settles_at_sustain() {
+        let mut envelope = Envelope::new(1_000);
+        envelope.set_attack_time(0.0, 0.0);
+        envelope.set_decay_time(0.01);
+        envelope.set_sustain_level(0.5);
+
+        self::assert_eq!(envelope.process(true), 1.0);
+        for _ in 0..500 {
+            envelope.process(true);
+        }
+        assert!((envelope.process(true) - 0.5).abs() < 0.01);
+
+        // Releasing the gate decays back to silence.
+        for _ in 0..1_000 {
+            envelope.process(false);
+        }
+        self::assert_eq!(envelope.process(false), 0.0);
+    }
+
+    #[test]
+    fn test_block_matches_per_sample() {
+        let mut per_sample = Envelope::new(1_000);
+        per_sample.set_attack_time(0.02, 0.0);
+        per_sample.set_decay_time(0.05);
+        per_sample.set_sustain_level(0.4);
+        per_sample.set_release_time(0.05);
+        let mut block = per_sample.clone();
+
+        // Held through attack and decay, then released, in uneven
+        // block sizes so stage changes land part way through a block.
+        let blocks: [(bool, usize); 6] = [
+            (true, 7),
+            (true, 64),
+            (true, 200),
+            (false, 13),
+            (false, 100),
+            (false, 400),
+        ];
+
+        let mut out = [0.0f32; 400];
+        for (gate, len) in blocks {
+            block.process_block(gate, &mut out[..len]);
+            for (i, level) in out[..len].iter().enumerate() {
+                let expected = per_sample.process(gate);
+                assert!(
+                    (level - expected).abs() < 1e-6,
+                    "sample {i}: block {level} != per-sample {expected}"
+                );
+            }
+            self::assert_eq!(block.stage, per_sample.stage);
+        }
+        self::assert_eq!(block.stage, EnvelopeStage::Init);
+    }
+
+    #[test]
+    fn test_sustain_hold() {
+        let mut envelope = Envelope::new(1_000);
+        envelope.set_attack_time(0.0, 0.0);
+        envelope.set_decay_time(0.01);
+        envelope.set_sustain_level(0.5);
+        envelope.set_sustain_hold(true);
+
+        for _ in 0..500 {
+            envelope.process(true);
+        }
+
+        // Releasing the gate keeps sustaining while held.
+        for _ in 0..1_000 {
+            envelope.process(false);
+        }
+        assert!((envelope.process(false) - 0.5).abs() < 0.01);
+        self::assert_eq!(envelope.stage, EnvelopeStage::Decay);
+
+        // Lifting the hold releases it.
+        envelope.set_sustain_hold(false);
+        let first = envelope.process(false);
+        self::assert_eq!(envelope.stage, EnvelopeStage::Release);
+        assert!(first < 0.5);
+        for _ in 0..1_000 {
+            envelope.process(false);
+        }
+        self::assert_eq!(envelope.process(false), 0.0);
+    }
+
+    #[test]
+    fn test_sustain_hold_retriggers() {
+        let mut envelope = Envelope::new(1_000);
+        envelope.set_attack_time(0.0, 0.0);
+        envelope.set_sustain_level(0.5);
+        envelope.set_sustain_hold(true);
+
+        envelope.process(true);
+        for _ in 0..500 {
+            envelope.process(false);
+        }
+
+        // Pressing again while held goes back to the peak.
+        self::assert_eq!(envelope.process(true), 1.0);
+    }
+
+    #[test]
+    fn test_params_round_trip() {
+        let mut original = Envelope::new(48_000);
+        original.set_attack_time(0.01, 0.3);
+        original.set_decay_time(0.2);
+        original.set_sustain_level(0.6);
+        original.set_release_time(0.5);
+
+        let params = original.to_params();
+        self::assert_eq!(Envelope::new(48_000).to_params(), EnvelopeParams::default());
+
+        #[cfg(feature = "serde")]
+        let params: EnvelopeParams =
+            serde_json::from_str(&serde_json::to_string(&params).unwrap()).unwrap();
+
+        let mut restored = Envelope::from_params(48_000, &params);
+        self::assert_eq!(restored.to_params(), original.to_params());
+
+        for i in 0..48_000 {
+            let gate = i < 24_000;
+            self::assert_eq!(restored.process(gate), original.process(gate));
+        }
+    }
 }
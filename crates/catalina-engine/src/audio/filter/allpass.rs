@@ -0,0 +1,21 @@
+//! A first-order all-pass filter shared by the phase-shifting effects.
+
+/// A first-order all-pass filter.
+///
+/// Passes all frequencies at the same level while shifting their phase,
+/// from 0° at DC to 180° at nyquist, with 90° at the break frequency.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub(crate) struct AllPass {
+    x1: f32,
+    y1: f32,
+}
+
+impl AllPass {
+    #[inline]
+    pub(crate) fn process(&mut self, input: f32, coefficient: f32) -> f32 {
+        let output = coefficient * input + self.x1 - coefficient * self.y1;
+        self.x1 = input;
+        self.y1 = output;
+        output
+    }
+}
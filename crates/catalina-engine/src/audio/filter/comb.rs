@@ -0,0 +1,183 @@
+//! A feedback comb filter tuned to a pitch, for resonator effects.
+
+use crate::{core::Hertz, music::note::Note};
+
+/// The highest feedback allowed, keeps the resonator from ringing forever.
+const MAX_FEEDBACK: f32 = 0.999;
+
+/// A feedback comb filter whose delay is tuned to a frequency.
+///
+/// Feeding the delayed output back into the input reinforces the tuned
+/// frequency and its harmonics, turning noise bursts or transients into
+/// pitched tones like a plucked string (Karplus-Strong) or a struck tube.
+///
+/// A one-pole lowpass in the feedback loop damps the higher harmonics
+/// faster than the fundamental, making the tone darker as it rings out.
+///
+/// `MAX_DELAY` is the length of the delay line in samples, so the lowest
+/// frequency it can be tuned to is the sample rate divided by `MAX_DELAY`.
+///
+/// Note that the output isn't normalized, at the tuned frequency the
+/// gain is `1 / (1 - feedback)`, scale the output down as needed.
+#[derive(Debug, Clone)]
+pub struct CombResonator<const MAX_DELAY: usize> {
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// The frequency the resonator is tuned to.
+    frequency: Hertz,
+    /// The length of the delay in samples, fractional for precise tuning.
+    delay: f32,
+    /// How much of the delayed output is fed back, from 0.0 to just under 1.0.
+    feedback: f32,
+    /// How much the feedback is lowpassed, from 0.0 (bright) to 1.0 (dull).
+    damping: f32,
+
+    buffer: [f32; MAX_DELAY],
+    /// The index of the next sample to be written.
+    write: usize,
+    /// The state of the damping lowpass.
+    filter: f32,
+}
+
+impl<const MAX_DELAY: usize> CombResonator<MAX_DELAY> {
+    /// Constructs a resonator tuned to A4 with a long, lightly damped ring.
+    pub fn new(sample_rate: usize) -> Self {
+        let mut resonator = Self {
+            sample_rate,
+            frequency: Hertz(440.0),
+            delay: 1.0,
+            feedback: 0.95,
+            damping: 0.2,
+            buffer: [0.0; MAX_DELAY],
+            write: 0,
+            filter: 0.0,
+        };
+
+        resonator.set_frequency(Hertz(440.0));
+
+        resonator
+    }
+
+    pub fn frequency(&self) -> Hertz {
+        self.frequency
+    }
+
+    /// Tunes the resonator to the given frequency.
+    ///
+    /// Frequencies too low for the delay line are clamped
+    /// to the lowest frequency the resonator can reach.
+    pub fn set_frequency(&mut self, frequency: Hertz) {
+        let max = MAX_DELAY.saturating_sub(2).max(1) as f32;
+        self.delay = if frequency.0 > 0.0 {
+            (self.sample_rate as f32 / frequency.0).clamp(1.0, max)
+        } else {
+            max
+        };
+        self.frequency = Hertz(self.sample_rate as f32 / self.delay);
+    }
+
+    /// Tunes the resonator to the frequency of the given note.
+    pub fn set_note(&mut self, note: Note) {
+        self.set_frequency(note.frequency());
+    }
+
+    /// Sets how much of the delayed output is fed back, longer rings at higher values.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(0.0, MAX_FEEDBACK);
+    }
+
+    /// Sets how much the feedback is lowpassed, from 0.0 (bright) to 1.0 (dull).
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+    }
+
+    /// Silences the resonator by clearing the delay line.
+    pub fn reset(&mut self) {
+        self.buffer = [0.0; MAX_DELAY];
+        self.filter = 0.0;
+    }
+
+    /// Processes a single sample through the resonator.
+    pub fn process(&mut self, input: f32) -> f32 {
+        if MAX_DELAY < 2 {
+            return input;
+        }
+
+        // Read the delay line between the two samples
+        // either side of the fractional delay.
+        let whole = self.delay as usize;
+        let fraction = self.delay - whole as f32;
+        let newer = self.buffer[(self.write + MAX_DELAY - whole) % MAX_DELAY];
+        let older = self.buffer[(self.write + MAX_DELAY - whole - 1) % MAX_DELAY];
+        let delayed = newer + fraction * (older - newer);
+
+        self.filter = delayed + self.damping * (self.filter - delayed);
+        let output = input + self.feedback * self.filter;
+
+        self.buffer[self.write] = output;
+        self.write = (self.write + 1) % MAX_DELAY;
+
+        output
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::signal::{ColoredNoise, NoiseColor, Signal};
+    use crate::music::note::AFour;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    /// The power of `buffer` at `frequency`, using the Goertzel algorithm.
+    fn power_at(buffer: &[f32], frequency: f32) -> f32 {
+        let w = 2.0 * core::f32::consts::PI * frequency / SAMPLE_RATE as f32;
+        let coeff = 2.0 * libm::cosf(w);
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in buffer {
+            let s0 = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coeff * s1 * s2
+    }
+
+    #[test]
+    fn test_noise_resonates_at_a4() {
+        let mut comb = CombResonator::<512>::new(SAMPLE_RATE);
+        comb.set_note(AFour);
+        comb.set_feedback(0.98);
+        comb.set_damping(0.1);
+
+        let mut noise = ColoredNoise::new(3, NoiseColor::White);
+        let mut output = [0.0f32; SAMPLE_RATE / 2];
+        for sample in output.iter_mut() {
+            *sample = comb.process(noise.next());
+        }
+
+        // The strongest frequency in the octave around A4 is close to 440Hz.
+        let (peak, _) = (330_i32..660)
+            .step_by(2)
+            .map(|hz| (hz, power_at(&output, hz as f32)))
+            .fold(
+                (0, 0.0),
+                |best, next| if next.1 > best.1 { next } else { best },
+            );
+        assert!((peak - 440).abs() <= 6, "peak at {peak}Hz");
+
+        // And stands well above the troughs half way between the harmonics.
+        let tone = power_at(&output, 440.0);
+        assert!(tone > 20.0 * power_at(&output, 220.0));
+        assert!(tone > 20.0 * power_at(&output, 660.0));
+    }
+
+    #[test]
+    fn test_low_frequencies_clamp_to_delay_line() {
+        let mut comb = CombResonator::<100>::new(SAMPLE_RATE);
+        comb.set_frequency(Hertz(20.0));
+        assert!((comb.frequency().0 - SAMPLE_RATE as f32 / 98.0).abs() < 0.01);
+    }
+}
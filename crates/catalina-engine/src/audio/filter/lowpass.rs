@@ -0,0 +1,134 @@
+//! A resonant lowpass filter for subtractive voices.
+
+use crate::core::Hertz;
+
+/// The lowest damping allowed, keeps the filter from self-oscillating.
+const MIN_DAMPING: f32 = 0.05;
+
+/// A resonant two-pole lowpass, built as a state variable
+/// filter using the topology-preserving transform.
+///
+/// The cutoff can be swept every sample without the filter blowing up,
+/// so it can be driven directly from an envelope or LFO.
+#[derive(Debug, Clone)]
+pub struct Lowpass {
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// The frequency above which the signal is attenuated.
+    cutoff: Hertz,
+    /// How much the cutoff frequency is emphasised, from 0.0 to 1.0.
+    resonance: f32,
+
+    /// The cutoff coefficient, prewarped from the cutoff frequency.
+    g: f32,
+    /// The damping coefficient, derived from the resonance.
+    k: f32,
+
+    /// The state of the two integrators.
+    ic1: f32,
+    ic2: f32,
+}
+
+impl Lowpass {
+    /// Constructs a lowpass with a fully open cutoff and no resonance.
+    pub fn new(sample_rate: usize) -> Self {
+        let mut filter = Self {
+            sample_rate,
+            cutoff: Hertz(20_000.0),
+            resonance: 0.0,
+            g: 0.0,
+            k: 2.0,
+            ic1: 0.0,
+            ic2: 0.0,
+        };
+
+        filter.set_cutoff(filter.cutoff);
+
+        filter
+    }
+
+    pub fn cutoff(&self) -> Hertz {
+        self.cutoff
+    }
+
+    /// Sets the cutoff frequency.
+    ///
+    /// Frequencies are clamped to between 10Hz and just under Nyquist.
+    pub fn set_cutoff(&mut self, cutoff: Hertz) {
+        let nyquist = self.sample_rate as f32 / 2.0;
+        self.cutoff = Hertz(cutoff.0.clamp(10.0, nyquist * 0.99));
+        self.g = libm::tanf(core::f32::consts::PI * self.cutoff.0 / self.sample_rate as f32);
+    }
+
+    /// Sets how much the cutoff frequency is emphasised, from 0.0 to 1.0.
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.k = 2.0 - (2.0 - MIN_DAMPING) * self.resonance;
+    }
+
+    /// Clears the filter state, i.e. when a voice is retriggered.
+    pub fn reset(&mut self) {
+        self.ic1 = 0.0;
+        self.ic2 = 0.0;
+    }
+
+    /// Processes a single sample through the filter.
+    pub fn process(&mut self, input: f32) -> f32 {
+        let a1 = 1.0 / (1.0 + self.g * (self.g + self.k));
+        let a2 = self.g * a1;
+        let a3 = self.g * a2;
+
+        let v3 = input - self.ic2;
+        let v1 = a1 * self.ic1 + a2 * v3;
+        let v2 = self.ic2 + a2 * self.ic1 + a3 * v3;
+
+        self.ic1 = 2.0 * v1 - self.ic1;
+        self.ic2 = 2.0 * v2 - self.ic2;
+
+        v2
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::oscillator::{OscillatorType, RuntimeOscillator};
+    use crate::audio::signal::Signal;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    /// Filters a second of a sine at `frequency` and returns the settled peak.
+    fn peak(filter: &mut Lowpass, frequency: f32) -> f32 {
+        let mut sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(frequency));
+        filter.reset();
+        (0..SAMPLE_RATE)
+            .map(|_| filter.process(sine.next()))
+            .skip(SAMPLE_RATE / 2)
+            .fold(0.0, |peak, sample: f32| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_attenuates_above_cutoff() {
+        let mut filter = Lowpass::new(SAMPLE_RATE);
+        filter.set_cutoff(Hertz(500.0));
+
+        let low = peak(&mut filter, 100.0);
+        let high = peak(&mut filter, 5_000.0);
+        assert!(low > 0.95, "100Hz peaked at {low}");
+        assert!(high < 0.02, "5kHz peaked at {high}");
+    }
+
+    #[test]
+    fn test_resonance_emphasises_cutoff() {
+        let mut filter = Lowpass::new(SAMPLE_RATE);
+        filter.set_cutoff(Hertz(1_000.0));
+
+        let flat = peak(&mut filter, 1_000.0);
+        filter.set_resonance(0.9);
+        let resonant = peak(&mut filter, 1_000.0);
+        assert!(resonant > 3.0 * flat, "{resonant} vs {flat}");
+    }
+}
@@ -0,0 +1,9 @@
+//! Filters that shape the spectrum of an audio signal sample by sample.
+
+pub(crate) mod allpass;
+
+pub mod comb;
+pub use comb::CombResonator;
+
+pub mod lowpass;
+pub use lowpass::Lowpass;
@@ -9,7 +9,7 @@
 
 use core::{iter::DoubleEndedIterator, mem::MaybeUninit};
 
-use crate::audio::sample::Sample;
+use crate::audio::sample::{FromSample, Sample};
 
 /// Represents one sample from each channel at a single discrete instance in time within a
 /// PCM signal.
@@ -283,6 +283,38 @@ pub trait NumChannels {}
 pub type Mono<S> = [S; 1];
 pub type Stereo<S> = [S; 2];
 
+/// Mixes two frames together by summing each of their channels.
+///
+/// # Examples
+///
+/// ```
+/// use catalina_engine::audio::frame;
+///
+/// assert_eq!(frame::mix([0.25, -0.5], [0.5, 0.25]), [0.75, -0.25]);
+/// ```
+#[inline]
+pub fn mix<F: Frame>(a: F, b: F) -> F {
+    a.add_amp(b.to_signed_frame())
+}
+
+/// Scales the amplitude of every channel in a frame.
+///
+/// # Examples
+///
+/// ```
+/// use catalina_engine::audio::frame;
+///
+/// assert_eq!(frame::scale([0.5, -1.0], 0.5), [0.25, -0.5]);
+/// ```
+#[inline]
+pub fn scale<F>(frame: F, amp: f32) -> F
+where
+    F: Frame,
+    <F::Sample as Sample>::Float: FromSample<f32>,
+{
+    frame.scale_amp(<F::Sample as Sample>::Float::from_sample_(amp))
+}
+
 /// An iterator that yields the sample for each channel in the frame by value.
 #[derive(Clone)]
 pub struct Channels<F> {
@@ -672,3 +704,32 @@ fn array_from_iter<T, const N: usize>(mut iter: impl Iterator<Item = T>) -> Opti
         Some(result.map(|v| v.assume_init()))
     }
 }
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_mix() {
+        let a: Stereo<f32> = [0.25, -0.5];
+        let b: Stereo<f32> = [0.5, 0.25];
+        self::assert_eq!(mix(a, b), [0.75, -0.25]);
+
+        let a: Mono<f32> = [0.25];
+        self::assert_eq!(mix(a, [0.5]), [0.75]);
+
+        // Unsigned samples are mixed around their equilibrium.
+        self::assert_eq!(mix([128_u8, 138], [138_u8, 128]), [138, 138]);
+    }
+
+    #[test]
+    fn test_scale() {
+        let frame: Stereo<f32> = [0.5, -1.0];
+        self::assert_eq!(scale(frame, 0.5), [0.25, -0.5]);
+        self::assert_eq!(scale(frame, 0.0), [0.0, 0.0]);
+        self::assert_eq!(scale([1.0_f64], 0.25), [0.25]);
+    }
+}
@@ -0,0 +1,67 @@
+//! A low frequency oscillator for use as a modulation source.
+
+use crate::{
+    audio::{
+        oscillator::{DutyCycle, OscillatorType},
+        signal::Signal,
+    },
+    core::Hertz,
+};
+
+/// A low frequency oscillator that outputs a bipolar
+/// waveform between -1.0 and 1.0 for modulating parameters.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Lfo {
+    /// The shape of the waveform.
+    shape: OscillatorType,
+
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+    /// The rate of the LFO.
+    frequency: Hertz,
+
+    /// The phase of the waveform, between 0.0 and 1.0.
+    phase: f32,
+}
+
+impl Lfo {
+    /// Constructs a new LFO at the start of it's cycle.
+    pub fn new(shape: OscillatorType, sample_rate: usize, frequency: Hertz) -> Self {
+        Self {
+            shape,
+            sample_rate,
+            frequency,
+            phase: 0.0,
+        }
+    }
+
+    /// Sets the shape of the waveform.
+    pub fn set_shape(&mut self, shape: OscillatorType) {
+        self.shape = shape;
+    }
+
+    /// Sets the rate of the LFO.
+    pub fn set_frequency(&mut self, frequency: Hertz) {
+        self.frequency = frequency;
+    }
+
+    /// Restarts the LFO from the start of it's cycle, i.e. on a key press.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+}
+
+impl Signal for Lfo {
+    type Frame = f32;
+
+    fn next(&mut self) -> Self::Frame {
+        let sample = self.shape.sample(self.phase, DutyCycle::Half);
+
+        self.phase += self.frequency.hertz() / self.sample_rate as f32;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+
+        sample
+    }
+}
@@ -0,0 +1,128 @@
+//! Meters for inspecting a signal as it passes through.
+
+use crate::{
+    audio::frame::Stereo,
+    core::{db::linear_to_db, param::one_pole_coefficient},
+};
+
+/// Measures how alike the left and right channels of a stereo signal are.
+///
+/// Reads +1 when the channels are identical (mono), 0 when they're
+/// unrelated and -1 when one is the inverse of the other, which
+/// cancels out when the signal is summed to mono.
+///
+/// The products of the channels are averaged over a window with
+/// one-pole smoothing, so the reading follows the recent signal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CorrelationMeter {
+    /// One-pole coefficient for the averaging window.
+    coefficient: f32,
+
+    /// Average of left multiplied by right.
+    left_right: f32,
+    /// Average of left squared.
+    left_left: f32,
+    /// Average of right squared.
+    right_right: f32,
+}
+
+impl CorrelationMeter {
+    /// Constructs a new meter averaging over roughly `window` seconds.
+    pub fn new(sample_rate: usize, window: f32) -> Self {
+        Self {
+            coefficient: one_pole_coefficient(window, sample_rate),
+            left_right: 0.0,
+            left_left: 0.0,
+            right_right: 0.0,
+        }
+    }
+
+    /// Measures a frame, returning it unchanged so the meter can sit in a chain.
+    pub fn process(&mut self, frame: Stereo<f32>) -> Stereo<f32> {
+        let [left, right] = frame;
+
+        self.left_right += (left * right - self.left_right) * self.coefficient;
+        self.left_left += (left * left - self.left_left) * self.coefficient;
+        self.right_right += (right * right - self.right_right) * self.coefficient;
+
+        frame
+    }
+
+    /// Returns the correlation of the channels from -1.0 to 1.0.
+    ///
+    /// Silence, or a signal on only one channel, reads as 0.0.
+    pub fn correlation(&self) -> f32 {
+        let power = libm::sqrtf(self.left_left * self.right_right);
+        if power <= f32::EPSILON {
+            return 0.0;
+        }
+
+        (self.left_right / power).clamp(-1.0, 1.0)
+    }
+
+    /// Returns the RMS level of both channels over the window in decibels.
+    ///
+    /// Silence reads as [`SILENCE_DB`](crate::core::db::SILENCE_DB).
+    pub fn level(&self) -> f32 {
+        linear_to_db(libm::sqrtf((self.left_left + self.right_right) * 0.5))
+    }
+
+    /// Clears the measurement.
+    pub fn reset(&mut self) {
+        self.left_right = 0.0;
+        self.left_left = 0.0;
+        self.right_right = 0.0;
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::signal::{ColoredNoise, NoiseColor};
+    use pretty_assertions::assert_eq;
+
+    /// Runs 100ms of frames through a 50ms meter and returns the reading.
+    fn measure(mut frame: impl FnMut(usize) -> Stereo<f32>) -> f32 {
+        let mut meter = CorrelationMeter::new(48_000, 0.05);
+        for i in 0..4_800 {
+            let input = frame(i);
+            self::assert_eq!(meter.process(input), input);
+        }
+        meter.correlation()
+    }
+
+    #[test]
+    fn test_correlation() {
+        let sine = |i: usize| libm::sinf(i as f32 * 0.05);
+
+        let identical = measure(|i| [sine(i), sine(i)]);
+        assert!((identical - 1.0).abs() < 1e-3, "identical read {identical}");
+
+        let inverted = measure(|i| [sine(i), -sine(i)]);
+        assert!((inverted + 1.0).abs() < 1e-3, "inverted read {inverted}");
+
+        let mut left = ColoredNoise::new(1, NoiseColor::White);
+        let mut right = ColoredNoise::new(2, NoiseColor::White);
+        let unrelated = measure(|_| [left.next_sample(), right.next_sample()]);
+        assert!(unrelated.abs() < 0.1, "unrelated read {unrelated}");
+
+        self::assert_eq!(measure(|_| [0.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_level() {
+        let mut meter = CorrelationMeter::new(48_000, 0.05);
+        self::assert_eq!(meter.level(), crate::core::db::SILENCE_DB);
+
+        // A full scale sine has an RMS level of about -3 dB,
+        // run for a full second so the average settles.
+        for i in 0..48_000 {
+            let sample = libm::sinf(i as f32 * 0.05);
+            meter.process([sample, sample]);
+        }
+        let level = meter.level();
+        assert!((level + 3.01).abs() < 0.1, "sine read {level} dB");
+    }
+}
@@ -41,9 +41,571 @@ pub mod oscillator;
 
 pub mod envelope;
 
+// Dynamics processors such as limiters.
+pub mod dynamics;
+
+// Effects for processing audio signals.
+pub mod effects;
+#[cfg(feature = "alloc")]
+pub use effects::Chain;
+use effects::Effect;
+
+// Filters for shaping the spectrum of audio signals.
+pub mod filter;
+use filter::allpass::AllPass;
+
+// Meters for inspecting signals, such as stereo correlation.
+pub mod meter;
+
+// Low frequency oscillators for modulating parameters.
+pub mod lfo;
+
+// Routing of modulation sources to parameters.
+pub mod modulation;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{vec, vec::Vec};
+
+/// The amount of frames [`AudioSource::render_add`] and [`AudioSource::prime`]
+/// render at once by default, bounding the size of their stack buffer.
+const RENDER_ADD_CHUNK: usize = 64;
+
+/// The amount of frames [`Samples`] renders into it's buffer at once.
+#[cfg(feature = "alloc")]
+const SAMPLES_BLOCK: usize = 256;
+
 pub trait AudioSource {
     type Frame: Frame;
 
     /// Render a buffered block of audio from the audio source.
     fn render(&mut self, buffer: &'_ mut [Self::Frame]);
+
+    /// Render only the first `frames` frames of the buffer, leaving the rest silent.
+    ///
+    /// Useful when playback stops part way through a block, i.e.
+    /// while a stream is being torn down. Frame counts larger
+    /// than the buffer fill the entire buffer.
+    fn render_until(&mut self, buffer: &'_ mut [Self::Frame], frames: usize) {
+        let (head, tail) = buffer.split_at_mut(frames.min(buffer.len()));
+        self.render(head);
+        slice::equilibrium(tail);
+    }
+
+    /// Render a buffered block of audio from the audio source,
+    /// adding it to the existing contents of the buffer.
+    ///
+    /// This lets several sources be mixed into a single buffer in place.
+    /// The default implementation renders in chunks through a small stack
+    /// buffer, sources that can sum directly should override it.
+    fn render_add(&mut self, buffer: &'_ mut [Self::Frame]) {
+        let mut scratch = [Self::Frame::EQUILIBRIUM; RENDER_ADD_CHUNK];
+
+        for chunk in buffer.chunks_mut(RENDER_ADD_CHUNK) {
+            let scratch = &mut scratch[..chunk.len()];
+            self.render(scratch);
+
+            for (out, frame) in chunk.iter_mut().zip(scratch.iter()) {
+                *out = out.add_amp(frame.to_signed_frame());
+            }
+        }
+    }
+
+    /// Renders and discards `frames` frames, i.e. so filters and delay
+    /// lines settle before an offline render is captured.
+    ///
+    /// The default implementation renders in chunks through a small stack
+    /// buffer, sources that can reach their steady state more cheaply,
+    /// i.e. by pre-filling a delay line, should override it.
+    fn prime(&mut self, frames: usize) {
+        let mut scratch = [Self::Frame::EQUILIBRIUM; RENDER_ADD_CHUNK];
+
+        let mut remaining = frames;
+        while remaining > 0 {
+            let chunk = remaining.min(RENDER_ADD_CHUNK);
+            self.render(&mut scratch[..chunk]);
+            remaining -= chunk;
+        }
+    }
+
+    /// Returns true if the source would only render silence until it's
+    /// played again, i.e. once every note has released and decayed.
+    ///
+    /// Hosts can skip rendering silent sources to save CPU. The default
+    /// implementation returns false, so sources are always rendered.
+    fn is_silent(&self) -> bool {
+        false
+    }
+
+    /// Runs the rendered audio through an effect, e.g. `synth.through(chain)`.
+    ///
+    /// The returned source renders each block from this source
+    /// then processes the whole block through the effect.
+    fn through<E: Effect>(self, effect: E) -> Through<Self, E>
+    where
+        Self: AudioSource<Frame = f32> + Sized,
+    {
+        Through {
+            source: self,
+            effect,
+        }
+    }
+
+    /// Spreads a mono source across a stereo output, e.g. `synth.widen(0.5)`.
+    ///
+    /// See [`Widen`] for how the width is created.
+    fn widen(self, width: f32) -> Widen<Self>
+    where
+        Self: AudioSource<Frame = f32> + Sized,
+    {
+        let mut widen = Widen {
+            source: self,
+            width: 0.0,
+            stages: [AllPass::default(); WIDEN_COEFFICIENTS.len()],
+        };
+        widen.set_width(width);
+        widen
+    }
+
+    /// Renders the source once per block for several consumers, e.g. a dry and wet chain.
+    ///
+    /// See [`Splitter`] for how the block is shared.
+    fn split<const BLOCK: usize>(self) -> Splitter<Self, BLOCK>
+    where
+        Self: Sized,
+    {
+        Splitter {
+            source: self,
+            block: [Self::Frame::EQUILIBRIUM; BLOCK],
+            len: 0,
+        }
+    }
+
+    /// Pulls the rendered frames one at a time as an iterator,
+    /// e.g. `source.samples().take(n).map(...)`.
+    ///
+    /// See [`Samples`] for how the frames are rendered.
+    #[cfg(feature = "alloc")]
+    fn samples(&mut self) -> Samples<'_, Self>
+    where
+        Self: Sized,
+    {
+        Samples {
+            source: self,
+            buffer: vec![Self::Frame::EQUILIBRIUM; SAMPLES_BLOCK],
+            position: SAMPLES_BLOCK,
+        }
+    }
+}
+
+/// An [`AudioSource`] that runs another source through an [`Effect`].
+///
+/// Created by [`AudioSource::through`].
+pub struct Through<S, E> {
+    source: S,
+    effect: E,
+}
+
+impl<S, E> Through<S, E> {
+    /// Returns a reference to the source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns a mutable reference to the source, i.e. for playing notes on an instrument.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns a mutable reference to the effect.
+    pub fn effect_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+
+    /// Splits the adapter back into the source and effect.
+    pub fn into_inner(self) -> (S, E) {
+        (self.source, self.effect)
+    }
+}
+
+impl<S: AudioSource<Frame = f32>, E: Effect> AudioSource for Through<S, E> {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        self.source.render(buffer);
+        self.effect.process_buffer(buffer);
+    }
+}
+
+/// The coefficients of the all-pass chain that decorrelates the right
+/// channel of a [`Widen`], spreading the phase shift across the spectrum.
+const WIDEN_COEFFICIENTS: [f32; 4] = [-0.7, -0.3, 0.2, 0.6];
+
+/// An [`AudioSource`] that spreads a mono source across a stereo output.
+///
+/// The left channel is the source as is, and the right channel blends
+/// towards a copy run through a chain of all-pass filters. The copy has
+/// the same spectrum but a shifted phase, so the channels decorrelate and
+/// the sound widens without changing it's tone. At a width of 0.0 both
+/// channels are identical.
+///
+/// Created by [`AudioSource::widen`].
+pub struct Widen<S> {
+    source: S,
+    /// How far the right channel is blended towards the phase-shifted copy.
+    width: f32,
+    stages: [AllPass; WIDEN_COEFFICIENTS.len()],
+}
+
+impl<S> Widen<S> {
+    /// Returns the width from 0.0 (dual mono) to 1.0.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Sets the width, clamped from 0.0 (dual mono) to 1.0.
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width.clamp(0.0, 1.0);
+    }
+
+    /// Returns a mutable reference to the source, i.e. for playing notes on an instrument.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the source, dropping the widening.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: AudioSource<Frame = f32>> AudioSource for Widen<S> {
+    type Frame = Stereo<f32>;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        let mut mono = [0.0; RENDER_ADD_CHUNK];
+
+        for chunk in buffer.chunks_mut(RENDER_ADD_CHUNK) {
+            let mono = &mut mono[..chunk.len()];
+            self.source.render(mono);
+
+            for (frame, sample) in chunk.iter_mut().zip(mono.iter()) {
+                // The chain keeps running at zero width so widening later doesn't click.
+                let mut shifted = *sample;
+                for (stage, coefficient) in self.stages.iter_mut().zip(WIDEN_COEFFICIENTS) {
+                    shifted = stage.process(shifted, coefficient);
+                }
+
+                *frame = [*sample, *sample + (shifted - *sample) * self.width];
+            }
+        }
+    }
+}
+
+/// Renders a source once per block and shares the block between several consumers.
+///
+/// Rendering a stateful source, such as an oscillator, for each consumer
+/// would advance it once per consumer, so instead [`Splitter::render`]
+/// renders the next block into an internal buffer of up to `BLOCK` frames
+/// and each consumer reads or copies the same block from there.
+///
+/// Created by [`AudioSource::split`].
+pub struct Splitter<S: AudioSource, const BLOCK: usize> {
+    source: S,
+    block: [S::Frame; BLOCK],
+    /// How many frames of the block were rendered last.
+    len: usize,
+}
+
+impl<S: AudioSource, const BLOCK: usize> Splitter<S, BLOCK> {
+    /// Renders the next block of `frames` from the source and returns it.
+    ///
+    /// Frame counts larger than `BLOCK` are limited to it.
+    pub fn render(&mut self, frames: usize) -> &[S::Frame] {
+        self.len = frames.min(BLOCK);
+        self.source.render(&mut self.block[..self.len]);
+        &self.block[..self.len]
+    }
+
+    /// Returns the last rendered block.
+    pub fn block(&self) -> &[S::Frame] {
+        &self.block[..self.len]
+    }
+
+    /// Copies the last rendered block into a consumer's buffer.
+    ///
+    /// Copies as much of the block as fits, buffers
+    /// longer than the block are left silent past it.
+    pub fn copy_to(&self, buffer: &'_ mut [S::Frame]) {
+        let (head, tail) = buffer.split_at_mut(self.len.min(buffer.len()));
+        head.copy_from_slice(&self.block[..head.len()]);
+        slice::equilibrium(tail);
+    }
+
+    /// Returns a mutable reference to the source, i.e. for playing notes on an instrument.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the source, dropping the shared block.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+/// An endless iterator over the frames rendered from an [`AudioSource`].
+///
+/// Frames are rendered lazily in blocks into an internal buffer, so the
+/// source is rendered the same way as it would be by a host. Dropping the
+/// iterator drops any frames left in the buffer, the source has already
+/// moved past them.
+///
+/// Created by [`AudioSource::samples`].
+#[cfg(feature = "alloc")]
+pub struct Samples<'a, S: AudioSource> {
+    source: &'a mut S,
+    buffer: Vec<S::Frame>,
+    /// The index of the next frame in the buffer.
+    position: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, S: AudioSource> Iterator for Samples<'a, S> {
+    type Item = S::Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.buffer.len() {
+            self.source.render(&mut self.buffer);
+            self.position = 0;
+        }
+
+        let frame = self.buffer[self.position];
+        self.position += 1;
+        Some(frame)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::{filter::Lowpass, signal::ConstantSource},
+        core::Hertz,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// A source that outputs a ramp, continuing across renders.
+    struct Ramp {
+        value: f32,
+        step: f32,
+    }
+
+    impl AudioSource for Ramp {
+        type Frame = f32;
+
+        fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+            for frame in buffer.iter_mut() {
+                *frame = self.value;
+                self.value += self.step;
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_add() {
+        // Long enough to span several chunks plus a partial one.
+        let mut a = [0.0; 150];
+        let mut b = [0.0; 150];
+        Ramp {
+            value: 0.0,
+            step: 1.0,
+        }
+        .render(&mut a);
+        Ramp {
+            value: 0.5,
+            step: -0.25,
+        }
+        .render(&mut b);
+
+        let mut mixed = [0.0; 150];
+        Ramp {
+            value: 0.0,
+            step: 1.0,
+        }
+        .render_add(&mut mixed);
+        Ramp {
+            value: 0.5,
+            step: -0.25,
+        }
+        .render_add(&mut mixed);
+
+        for i in 0..mixed.len() {
+            self::assert_eq!(mixed[i], a[i] + b[i]);
+        }
+    }
+
+    /// A source that outputs a sine wave, continuing across renders.
+    struct Sine {
+        phase: f32,
+    }
+
+    impl AudioSource for Sine {
+        type Frame = f32;
+
+        fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+            for frame in buffer.iter_mut() {
+                *frame = libm::sinf(self.phase);
+                self.phase += 0.1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_through_effect() {
+        let mut dry = [0.0; 100];
+        Sine { phase: 0.0 }.render(&mut dry);
+
+        let mut wet = [0.0; 100];
+        let mut source = Sine { phase: 0.0 }.through(effects::Gain::new(0.5));
+        source.render(&mut wet[..40]);
+        source.render(&mut wet[40..]);
+
+        for i in 0..wet.len() {
+            self::assert_eq!(wet[i], dry[i] * 0.5);
+        }
+    }
+
+    #[test]
+    fn test_prime_skips_transient() {
+        let source = || {
+            let mut filter = Lowpass::new(48_000);
+            filter.set_cutoff(Hertz(200.0));
+            ConstantSource::new(1.0).through(filter)
+        };
+
+        // Unprimed, the filter starts from silence and rises towards the input.
+        let mut cold = [0.0; 64];
+        source().render(&mut cold);
+        assert!(cold[0] < 0.1, "cold start at {}", cold[0]);
+
+        // Primed, the capture starts at the settled level.
+        let mut primed = source();
+        primed.prime(48_000 / 10);
+        let mut warm = [0.0; 64];
+        primed.render(&mut warm);
+        for sample in warm {
+            assert!((sample - 1.0).abs() < 0.01, "primed output of {sample}");
+        }
+    }
+
+    #[test]
+    fn test_render_until() {
+        let mut buffer = [9.0; 8];
+        Ramp {
+            value: 1.0,
+            step: 1.0,
+        }
+        .render_until(&mut buffer, 3);
+        self::assert_eq!(buffer, [1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        // Asking for more frames than the buffer holds fills it.
+        Ramp {
+            value: 1.0,
+            step: 1.0,
+        }
+        .render_until(&mut buffer[..2], 10);
+        self::assert_eq!(buffer[..2], [1.0, 2.0]);
+    }
+
+    /// A source of seeded white noise.
+    struct WhiteNoise(signal::noise::ColoredNoise);
+
+    impl AudioSource for WhiteNoise {
+        type Frame = f32;
+
+        fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+            for frame in buffer.iter_mut() {
+                *frame = self.0.next_sample();
+            }
+        }
+    }
+
+    fn widened_correlation(width: f32) -> f32 {
+        let noise = signal::noise::ColoredNoise::new(1, signal::noise::NoiseColor::White);
+        let mut source = WhiteNoise(noise).widen(width);
+        let mut meter = meter::CorrelationMeter::new(48_000, 0.05);
+
+        let mut buffer = [[0.0; 2]; 4_800];
+        source.render(&mut buffer);
+        for frame in buffer {
+            meter.process(frame);
+        }
+
+        meter.correlation()
+    }
+
+    #[test]
+    fn test_widen() {
+        let noise = signal::noise::ColoredNoise::new(1, signal::noise::NoiseColor::White);
+        let mut source = WhiteNoise(noise).widen(0.0);
+        let mut buffer = [[0.0; 2]; 100];
+        source.render(&mut buffer);
+        assert!(buffer.iter().all(|[left, right]| left == right));
+
+        let correlation = widened_correlation(1.0);
+        assert!(correlation < 0.5, "correlation of {correlation}");
+        assert!(widened_correlation(0.5) > correlation);
+    }
+
+    #[test]
+    fn test_split() {
+        let mut splitter = Ramp {
+            value: 0.0,
+            step: 1.0,
+        }
+        .split::<64>();
+
+        let block: [f32; 48] = core::array::from_fn(|i| i as f32);
+        self::assert_eq!(splitter.render(48), &block);
+
+        // Both consumers get the same block.
+        let mut dry = [0.0; 48];
+        let mut wet = [0.0; 48];
+        splitter.copy_to(&mut dry);
+        splitter.copy_to(&mut wet);
+        self::assert_eq!(dry, block);
+        self::assert_eq!(wet, block);
+
+        // The source only advanced by a single block.
+        self::assert_eq!(splitter.render(2), &[48.0, 49.0]);
+        self::assert_eq!(splitter.into_inner().value, 50.0);
+    }
+
+    #[test]
+    fn test_samples() {
+        let mut ramp = Ramp {
+            value: 0.0,
+            step: 0.5,
+        };
+
+        // Spans a few of the iterator's internal blocks.
+        let collected: std::vec::Vec<f32> = ramp.samples().take(1000).collect();
+
+        let mut rendered = [0.0; 1000];
+        Ramp {
+            value: 0.0,
+            step: 0.5,
+        }
+        .render(&mut rendered);
+        self::assert_eq!(collected, rendered);
+
+        // Combinators work on the frames as usual.
+        let doubled: std::vec::Vec<f32> = ramp.samples().map(|x| x * 2.0).take(2).collect();
+        self::assert_eq!(doubled, [1024.0, 1025.0]);
+    }
 }
@@ -0,0 +1,166 @@
+//! A modulation matrix for routing modulation sources to parameters.
+//!
+//! Sources are anything producing a value each sample, such as an
+//! [`super::lfo::Lfo`], an envelope, or the velocity of a note. The
+//! owner of the matrix samples it's sources into a slice and the
+//! matrix sums the routed values for each destination parameter.
+
+use heapless::Vec;
+
+use crate::core::param::ParamId;
+
+/// An error returned when adding a route to a [`ModMatrix`].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub enum ModMatrixError {
+    RoutesFull,
+}
+
+/// A route from a modulation source to a parameter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ModRoute {
+    /// The index of the source in the slice of source values.
+    pub source: usize,
+    /// The parameter the source modulates.
+    pub destination: ParamId,
+    /// The amount the source value is scaled by, can be negative.
+    pub depth: f32,
+}
+
+/// A fixed-size set of routes between modulation sources and parameters.
+pub struct ModMatrix<const ROUTES: usize> {
+    routes: Vec<ModRoute, ROUTES>,
+}
+
+impl<const ROUTES: usize> ModMatrix<ROUTES> {
+    pub const fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Routes a source to a parameter with the provided depth.
+    pub fn add_route(
+        &mut self,
+        source: usize,
+        destination: ParamId,
+        depth: f32,
+    ) -> Result<(), ModMatrixError> {
+        self.routes
+            .push(ModRoute {
+                source,
+                destination,
+                depth,
+            })
+            .map_err(|_| ModMatrixError::RoutesFull)
+    }
+
+    /// Removes all the routes to the provided parameter.
+    pub fn remove_routes(&mut self, destination: ParamId) {
+        self.routes.retain(|route| route.destination != destination);
+    }
+
+    /// Removes all the routes.
+    pub fn clear(&mut self) {
+        self.routes.clear();
+    }
+
+    /// Returns the routes in the matrix.
+    pub fn routes(&self) -> &[ModRoute] {
+        &self.routes
+    }
+
+    /// Returns the summed modulation for a parameter from the current source values.
+    ///
+    /// Routes from sources outside of the slice are ignored.
+    pub fn modulation(&self, sources: &[f32], destination: ParamId) -> f32 {
+        self.routes
+            .iter()
+            .filter(|route| route.destination == destination)
+            .filter_map(|route| sources.get(route.source).map(|value| value * route.depth))
+            .sum()
+    }
+
+    /// Computes the summed modulation for every parameter at once.
+    ///
+    /// The output slice is indexed by [`ParamId`], it's cleared before
+    /// summing and routes to parameters outside of it are ignored.
+    pub fn process(&self, sources: &[f32], modulation: &mut [f32]) {
+        modulation.fill(0.0);
+
+        for route in self.routes.iter() {
+            let Some(value) = sources.get(route.source) else {
+                continue;
+            };
+
+            if let Some(param) = modulation.get_mut(route.destination.0 as usize) {
+                *param += value * route.depth;
+            }
+        }
+    }
+}
+
+impl<const ROUTES: usize> Default for ModMatrix<ROUTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{lfo::Lfo, oscillator::OscillatorType, signal::Signal};
+    use pretty_assertions::assert_eq;
+
+    const PITCH: ParamId = ParamId(0);
+    const CUTOFF: ParamId = ParamId(1);
+
+    #[test]
+    fn test_lfo_to_pitch() {
+        let mut lfo = Lfo::new(OscillatorType::Sine, 1_000, 4.0.into());
+
+        let mut matrix = ModMatrix::<4>::new();
+        matrix.add_route(0, PITCH, 2.0).unwrap();
+
+        let mut modulation = [0.0; 2];
+        let (mut min, mut max) = (f32::MAX, f32::MIN);
+        let mut sign_changes = 0;
+        let mut previous = 0.0;
+
+        for _ in 0..1_000 {
+            matrix.process(&[lfo.next()], &mut modulation);
+
+            let pitch = modulation[PITCH.0 as usize];
+            if (pitch < 0.0) != (previous < 0.0) {
+                sign_changes += 1;
+            }
+            previous = pitch;
+            min = min.min(pitch);
+            max = max.max(pitch);
+
+            // Nothing is routed to the cutoff.
+            self::assert_eq!(modulation[CUTOFF.0 as usize], 0.0);
+        }
+
+        // A 4Hz LFO crosses zero twice a cycle, minus
+        // the crossing at the very start of the first cycle.
+        self::assert_eq!(sign_changes, 7);
+        assert!((max - 2.0).abs() < 0.01);
+        assert!((min + 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_sums_routes() {
+        let mut matrix = ModMatrix::<4>::new();
+        matrix.add_route(0, CUTOFF, 0.5).unwrap();
+        matrix.add_route(1, CUTOFF, -1.0).unwrap();
+        matrix.add_route(1, PITCH, 1.0).unwrap();
+
+        self::assert_eq!(matrix.modulation(&[1.0, 0.25], CUTOFF), 0.25);
+        self::assert_eq!(matrix.modulation(&[1.0, 0.25], PITCH), 0.25);
+
+        matrix.remove_routes(CUTOFF);
+        self::assert_eq!(matrix.modulation(&[1.0, 0.25], CUTOFF), 0.0);
+        self::assert_eq!(matrix.routes().len(), 1);
+    }
+}
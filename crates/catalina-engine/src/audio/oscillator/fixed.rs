@@ -0,0 +1,130 @@
+//! An oscillator that keeps it's phase in integer arithmetic, for
+//! MCUs without an FPU where float phase math is slow.
+
+use crate::{
+    audio::{
+        oscillator::{DutyCycle, Oscillator, OscillatorType},
+        sample::{FromSample, Sample},
+    },
+    core::fixed::FixedHertz,
+};
+
+/// The phase accumulator value of a whole cycle, as a float.
+const CYCLE: f32 = 4_294_967_296.0;
+
+/// An oscillator driven by a 32-bit phase accumulator and a [`FixedHertz`] frequency.
+///
+/// The accumulator wraps at the end of each cycle on it's own, so advancing
+/// the phase is a single integer add. Only the waveform shaping is done
+/// in float, the same as [`RuntimeOscillator`](super::RuntimeOscillator).
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedOscillator {
+    osc_type: OscillatorType,
+
+    sample_rate: u32,
+    frequency: FixedHertz,
+
+    /// Fractional duty cycle for square waves.
+    duty_cycle: DutyCycle,
+
+    /// How far the phase advances each sample.
+    increment: u32,
+    /// The position in the cycle, where the full range of the `u32` is one cycle.
+    phase: u32,
+}
+
+impl FixedOscillator {
+    /// Construct a new fixed-point oscillator.
+    pub const fn new(osc_type: OscillatorType, sample_rate: u32, frequency: FixedHertz) -> Self {
+        Self {
+            osc_type,
+            sample_rate,
+            frequency,
+            duty_cycle: DutyCycle::Half,
+            increment: frequency.phase_increment(sample_rate),
+            phase: 0,
+        }
+    }
+
+    /// Returns the frequency of the oscillator.
+    #[inline]
+    pub const fn frequency(&self) -> FixedHertz {
+        self.frequency
+    }
+
+    /// Sets the frequency of the oscillator, keeping the phase.
+    pub fn set_frequency(&mut self, frequency: FixedHertz) {
+        self.frequency = frequency;
+        self.increment = frequency.phase_increment(self.sample_rate);
+    }
+
+    /// Sets the duty cycle used for square waves.
+    pub fn set_duty_cycle(&mut self, duty_cycle: DutyCycle) {
+        self.duty_cycle = duty_cycle;
+    }
+
+    /// Returns the raw phase accumulator, where the full range of the `u32` is one cycle.
+    #[inline]
+    pub const fn phase(&self) -> u32 {
+        self.phase
+    }
+
+    /// Restarts the waveform from the beginning of it's cycle.
+    pub fn reset(&mut self) {
+        self.phase = 0;
+    }
+}
+
+impl<S: Sample + FromSample<f32>> Oscillator<S> for FixedOscillator {
+    fn sample(&mut self) -> S {
+        let sample = self
+            .osc_type
+            .sample(self.phase as f32 / CYCLE, self.duty_cycle);
+        self.phase = self.phase.wrapping_add(self.increment);
+        sample
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::oscillator::RuntimeOscillator, core::Hertz};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_matches_float_oscillator() {
+        for (osc_type, hertz) in [
+            (OscillatorType::Sine, 440.0),
+            (OscillatorType::Triangle, 261.6256),
+            (OscillatorType::Sine, 4186.009),
+        ] {
+            let mut fixed = FixedOscillator::new(osc_type, 48_000, Hertz(hertz).into());
+            let mut float = RuntimeOscillator::new(osc_type, 48_000, Hertz(hertz));
+
+            // A quarter second, long enough for any phase error to build up.
+            for index in 0..12_000 {
+                let expected: f32 = float.sample();
+                let actual: f32 = fixed.sample();
+                assert!(
+                    (expected - actual).abs() < 0.01,
+                    "{osc_type:?} at {hertz}Hz sample {index}: {actual} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_phase_wraps() {
+        let mut osc =
+            FixedOscillator::new(OscillatorType::Saw, 48_000, FixedHertz::from_int(12_000));
+        for _ in 0..4 {
+            let _: f32 = osc.sample();
+        }
+
+        // Four quarter cycles lands back at the start.
+        self::assert_eq!(osc.phase(), 0);
+    }
+}
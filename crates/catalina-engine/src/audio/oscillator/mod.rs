@@ -13,11 +13,13 @@
 //  https://github.com/RustAudio/cpal/blob/da923a2d5a01dd7f841f648ec26aeb6c1eabfa3e/examples/synth_tones.rs#L59
 
 use core::array;
+use core::cell::Ref;
+use core::str::FromStr;
 
 use heapless::index_map::FnvIndexMap;
 
 use crate::audio::{
-    Frame, Mono,
+    AudioSource, Frame, Mono,
     sample::{FromSample, Sample},
     signal::Signal,
 };
@@ -25,8 +27,15 @@ use crate::audio::{
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{core::Hertz, prelude::*};
+use crate::{
+    core::{Hertz, rng::Rng},
+    prelude::*,
+};
 
+#[cfg(feature = "fixed-point")]
+pub mod fixed;
+pub mod precise;
+pub mod stereo;
 pub mod variable;
 
 const PI2: f32 = PI * 2.0;
@@ -112,11 +121,28 @@ pub fn sample_triangle<S: Sample + FromSample<f32>>(
 ///
 /// Phase can be calculated as (sample_index % sample_rate) / sample_rate.
 pub fn square<S: Sample + FromSample<f32>>(phase: f32, duty_cycle: DutyCycle) -> S {
+    pulse(phase, duty_cycle.to_fractional(), false)
+}
+
+/// Generates a sample of a phase-inverted square wave, like [`square`].
+///
+/// Summing it with a non-inverted square of the
+/// same parameters cancels out to silence.
+pub fn square_inverted<S: Sample + FromSample<f32>>(phase: f32, duty_cycle: DutyCycle) -> S {
+    pulse(phase, duty_cycle.to_fractional(), true)
+}
+
+/// Generates a sample of a pulse wave at the provided phase, high
+/// for the fractional `width` (0.0 to 1.0) of each cycle.
+///
+/// Like [`square`] but with a continuous width, for pulse width modulation.
+pub fn pulse<S: Sample + FromSample<f32>>(phase: f32, width: f32, invert: bool) -> S {
     // Note that to_sample() handles the convertion of
     // the float-based waveform into other bit depth
     // domains - for f32 it's a no-op.
 
-    if phase % 1.0 < duty_cycle.to_fractional() {
+    let high = phase % 1.0 < width;
+    if high != invert {
         (1.0).to_sample()
     } else {
         (-1.0).to_sample()
@@ -134,9 +160,28 @@ pub fn sample_square<S: Sample + FromSample<f32>>(
     square(index as f32 / sample_rate as f32 * frequency.0, duty_cycle)
 }
 
-/// Temporary solution to specifying an Eq compatile duty cycle.
+/// Generates a sample of any oscillator type given the provided
+/// time index, sample rate, frequency, amplitude and duty cycle.
+///
+/// The amplitude is applied to the float waveform before it's converted
+/// to `S`, so integer outputs keep their full resolution instead of
+/// losing the low bits to converting at full scale and then scaling.
+pub fn sample_with<S: Sample + FromSample<f32>>(
+    osc_type: OscillatorType,
+    index: usize,
+    sample_rate: usize,
+    frequency: Hertz,
+    amplitude: f32,
+    duty_cycle: DutyCycle,
+) -> S {
+    let sample: f32 = osc_type.sample_index(index, sample_rate, frequency, duty_cycle);
+    (sample * amplitude).to_sample()
+}
+
+/// Specifies an Eq compatible duty cycle for square waves.
 ///
-/// Needs future work to allow a larger range of square wave cycles.
+/// The common cycles have named variants, any other whole
+/// percentage can be specified with [`DutyCycle::Percent`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub enum DutyCycle {
@@ -148,6 +193,8 @@ pub enum DutyCycle {
     Third,
     /// A duty cycle of 50%.
     Half,
+    /// A duty cycle in whole percent, values above 100 are clamped to 100%.
+    Percent(u8),
 }
 
 impl DutyCycle {
@@ -159,10 +206,52 @@ impl DutyCycle {
             DutyCycle::Quarter => 0.25,
             DutyCycle::Third => 0.33,
             DutyCycle::Half => 0.5,
+            DutyCycle::Percent(percent) => percent.min(100) as f32 / 100.0,
         }
     }
 }
 
+/// Parses a duty cycle from a percentage like "25%" or a fraction like "0.25".
+///
+/// The common cycles parse to their named variants, any other
+/// whole percentage parses to [`DutyCycle::Percent`].
+impl FromStr for DutyCycle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let percent = match s.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>(),
+            None => s.parse::<f32>().map(|fraction| fraction * 100.0),
+        }
+        .map_err(|_| ParseError::InvalidDutyCycle)?;
+
+        // Round off float error from scaling fractions, i.e. 0.33 to 33.0.
+        let percent = libm::roundf(percent * 1_000.0) / 1_000.0;
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(ParseError::InvalidDutyCycle);
+        }
+
+        Ok(match percent {
+            12.5 => DutyCycle::Eight,
+            25.0 => DutyCycle::Quarter,
+            33.0 => DutyCycle::Third,
+            50.0 => DutyCycle::Half,
+            // Duty cycles are only stored in whole percent.
+            _ if percent.fract() == 0.0 => DutyCycle::Percent(percent as u8),
+            _ => return Err(ParseError::InvalidDutyCycle),
+        })
+    }
+}
+
+impl TryFrom<&str> for DutyCycle {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 impl Default for DutyCycle {
     /// The default cycle is half.
     fn default() -> Self {
@@ -200,6 +289,44 @@ pub enum OscillatorType {
     Square,
 }
 
+/// Parses an oscillator type from it's name, i.e. "sine" or "Square".
+impl FromStr for OscillatorType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        [
+            ("sine", OscillatorType::Sine),
+            ("saw", OscillatorType::Saw),
+            ("triangle", OscillatorType::Triangle),
+            ("square", OscillatorType::Square),
+        ]
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        .map(|(_, kind)| kind)
+        .ok_or(ParseError::UnknownOscillatorType)
+    }
+}
+
+impl TryFrom<&str> for OscillatorType {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// An error returned from parsing oscillator settings from a string.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The name didn't match any [`OscillatorType`].
+    UnknownOscillatorType,
+    /// The duty cycle wasn't a whole percentage or fraction from 0 to 100%,
+    /// or one of the named cycles.
+    InvalidDutyCycle,
+}
+
 /// An error returned from building a lookup table for an oscillator.
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[derive(Debug)]
@@ -282,7 +409,10 @@ impl OscillatorType {
 /// regardless of what the backing implementation is.
 ///
 /// See [`RuntimeOscillator`] and [`LookupOscillator`] for implementations.
-pub trait Oscillator<S: Sample + FromSample<f32>> {
+///
+/// `S` is usually a sample, but can be a whole frame for oscillators
+/// producing several channels at once, see [`stereo::StereoLookupOscillator`].
+pub trait Oscillator<S: Copy> {
     /// Takes the next sample from the oscillator and
     /// advances the phase depending on the frequency.
     fn sample(&mut self) -> S;
@@ -300,6 +430,26 @@ pub trait Oscillator<S: Sample + FromSample<f32>> {
     }
 }
 
+/// A source of a pitched tone that can be retuned while playing.
+///
+/// Unifies the oscillator implementations (and anything else producing a
+/// tone at a frequency, like samplers) so that voices and patches can be
+/// written once and hold whichever source suits the platform, either
+/// generically or as a `&mut dyn ToneSource`.
+pub trait ToneSource {
+    /// Retunes the source to play at the provided frequency.
+    fn set_frequency(&mut self, frequency: Hertz);
+
+    /// Sets the linear amplitude the source's output is scaled by.
+    fn set_amplitude(&mut self, amplitude: f32);
+
+    /// Restarts the source's waveform from the beginning.
+    fn reset(&mut self);
+
+    /// Produces the next sample of the tone.
+    fn next(&mut self) -> f32;
+}
+
 /// Provides an oscillator that oscillates in a sine, saw, triangle,
 /// or square wave by generating the waveform at runtime.
 ///
@@ -308,29 +458,79 @@ pub trait Oscillator<S: Sample + FromSample<f32>> {
 /// is that it takes significantly more computation time per sample.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RuntimeOscillator {
     /// Specifies the type of the oscillator, used to
     /// determine which algorithm to use at runtime.
     osc_type: OscillatorType,
 
     sample_rate: usize,
+    /// The frequency the oscillator was tuned to, kept as is
+    /// so it comes back if the sample rate is raised again.
+    requested_frequency: Hertz,
+    /// The frequency that's played, the requested
+    /// frequency clamped to just under Nyquist.
     frequency: Hertz,
 
     /// Fractional duty cycle for square waves.
     duty_cycle: DutyCycle,
+    /// A continuous pulse width for square waves that overrides the
+    /// duty cycle while set, for sweeping with a modulation source.
+    pwm: Option<f32>,
+
+    /// Phase-inverts the output of square waves.
+    invert: bool,
+
+    /// The amplitude the output is scaled by when used as a [`ToneSource`].
+    amplitude: f32,
+
+    /// The furthest the pitch drifts from the frequency in cents, zero disables drift.
+    drift: f32,
+    /// Seeded randomness for the drift's random walk.
+    drift_rng: Rng,
+    /// How far the pitch has currently drifted in cents.
+    drift_cents: f32,
+    /// The frequency ratio of the current drift.
+    drift_ratio: f32,
+    /// Samples left until the drift takes it's next step.
+    drift_countdown: u32,
 
     phase: f32,
 }
 
+/// How often in samples the drift takes a step of it's random walk.
+const DRIFT_INTERVAL: u32 = 64;
+
+/// The largest step the drift takes each interval, as a fraction of the drift amount.
+const DRIFT_STEP: f32 = 0.01;
+
+/// How much of the drift is pulled back towards the frequency each
+/// interval, so that it wanders around the pitch rather than away.
+const DRIFT_LEAK: f32 = 0.0002;
+
+/// The seed used for the drift until one is set.
+const DEFAULT_DRIFT_SEED: u64 = 0xD41F7;
+
 impl RuntimeOscillator {
     /// Construct a new runtime oscillator.
+    ///
+    /// The frequency is clamped to between 0Hz and just under
+    /// Nyquist, see [`Hertz::clamp_to_nyquist`].
     pub fn new(osc_type: OscillatorType, sample_rate: usize, frequency: Hertz) -> Self {
         Self {
             osc_type,
             sample_rate,
-            frequency,
+            requested_frequency: frequency,
+            frequency: frequency.clamp_to_nyquist(sample_rate),
             duty_cycle: DutyCycle::Half,
+            pwm: None,
+            invert: false,
+            amplitude: 1.0,
+            drift: 0.0,
+            drift_rng: Rng::new(DEFAULT_DRIFT_SEED),
+            drift_cents: 0.0,
+            drift_ratio: 1.0,
+            drift_countdown: 0,
             phase: 0.0,
         }
     }
@@ -340,6 +540,83 @@ impl RuntimeOscillator {
         self.sample_rate
     }
 
+    /// Returns the frequency the oscillator plays before any drift, the
+    /// frequency it was tuned to clamped to just under Nyquist.
+    #[inline]
+    pub const fn get_frequency(&self) -> Hertz {
+        self.frequency
+    }
+
+    /// Changes the sample rate the oscillator is rendered at, i.e.
+    /// after switching audio devices, keeping the same frequency.
+    ///
+    /// The phase is kept so the waveform continues without a jump. The
+    /// tuned frequency is clamped to the new Nyquist, so a frequency
+    /// clamped at a lower sample rate is restored at a higher one.
+    pub fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.sample_rate = sample_rate;
+        self.frequency = self.requested_frequency.clamp_to_nyquist(sample_rate);
+    }
+
+    /// Sets the duty cycle used for square waves.
+    ///
+    /// Clears any pulse width set with [`RuntimeOscillator::set_pwm`].
+    pub fn set_duty_cycle(&mut self, duty_cycle: DutyCycle) {
+        self.duty_cycle = duty_cycle;
+        self.pwm = None;
+    }
+
+    /// Sets a continuous pulse width from 0.0 to 1.0 for square waves,
+    /// overriding the duty cycle.
+    ///
+    /// Intended to be called as often as every sample to sweep the
+    /// width with an LFO or envelope for pulse width modulation.
+    #[inline]
+    pub fn set_pwm(&mut self, width: f32) {
+        self.pwm = Some(width.clamp(0.0, 1.0));
+    }
+
+    /// Sets how far in cents the pitch slowly wanders from the
+    /// frequency, emulating the drift of analog oscillators.
+    ///
+    /// The drift is a seeded random walk that's continuous over
+    /// time, so it's the same on every render. Zero disables it.
+    pub fn set_drift(&mut self, cents: f32) {
+        self.drift = cents.max(0.0);
+        if self.drift == 0.0 {
+            self.drift_cents = 0.0;
+            self.drift_ratio = 1.0;
+        }
+    }
+
+    /// Reseeds the random walk of the drift.
+    pub fn set_drift_seed(&mut self, seed: u64) {
+        self.drift_rng.reseed(seed);
+    }
+
+    /// Takes the next step of the drift's random walk once an interval has passed.
+    #[inline]
+    fn update_drift(&mut self) {
+        if self.drift_countdown > 0 {
+            self.drift_countdown -= 1;
+            return;
+        }
+        self.drift_countdown = DRIFT_INTERVAL - 1;
+
+        let step = self.drift_rng.next_bipolar() * self.drift * DRIFT_STEP;
+        self.drift_cents =
+            ((self.drift_cents + step) * (1.0 - DRIFT_LEAK)).clamp(-self.drift, self.drift);
+        self.drift_ratio = libm::exp2f(self.drift_cents / 1200.0);
+    }
+
+    /// Sets if square waves are phase-inverted.
+    ///
+    /// Summing an inverted and non-inverted square with the same
+    /// parameters cancels out, which is useful for PWM tricks.
+    pub fn set_inverted(&mut self, invert: bool) {
+        self.invert = invert;
+    }
+
     /// Sample from the oscillator at the provided sample index/phase, with the provided frequency.
     ///
     /// This is unique to the RuntimeOscillator, because it calcualates the
@@ -357,9 +634,24 @@ impl RuntimeOscillator {
 impl<S: Sample + FromSample<f32>> Oscillator<S> for RuntimeOscillator {
     /// Sample from the oscillator at the provided sample index.
     fn sample(&mut self) -> S {
-        let sample = self.osc_type.sample(self.phase, self.duty_cycle);
+        let sample = match (self.osc_type, self.pwm) {
+            (OscillatorType::Square, Some(width)) => pulse(self.phase, width, self.invert),
+            (OscillatorType::Square, None) if self.invert => {
+                square_inverted(self.phase, self.duty_cycle)
+            }
+            (OscillatorType::Square, None) => square(self.phase, self.duty_cycle),
+            _ => self.osc_type.sample(self.phase, self.duty_cycle),
+        };
 
-        self.phase = self.phase + (self.frequency.hertz() / self.sample_rate as f32);
+        if self.drift > 0.0 {
+            self.update_drift();
+        }
+
+        // Keep the phase within a cycle so it doesn't lose precision over long renders.
+        self.phase += self.frequency.hertz() * self.drift_ratio / self.sample_rate as f32;
+        if self.phase >= 1.0 {
+            self.phase -= libm::floorf(self.phase);
+        }
 
         sample
     }
@@ -373,6 +665,43 @@ impl Signal for RuntimeOscillator {
     }
 }
 
+impl ToneSource for RuntimeOscillator {
+    /// Retunes the oscillator, clamping the frequency to between
+    /// 0Hz and just under Nyquist, see [`Hertz::clamp_to_nyquist`].
+    ///
+    /// Frequencies past Nyquist would alias, and negative frequencies
+    /// would run the phase backwards, so neither are played as is.
+    fn set_frequency(&mut self, frequency: Hertz) {
+        self.requested_frequency = frequency;
+        self.frequency = frequency.clamp_to_nyquist(self.sample_rate);
+    }
+
+    fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    fn next(&mut self) -> f32 {
+        let sample: f32 = self.sample();
+        sample * self.amplitude
+    }
+}
+
+/// Renders the oscillator scaled by it's amplitude, so it
+/// can be played directly as a source, i.e. for a test tone.
+impl AudioSource for RuntimeOscillator {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        for frame in buffer.iter_mut() {
+            *frame = ToneSource::next(self);
+        }
+    }
+}
+
 /// Provides an oscillator that oscillates in a sine, saw, triangle,
 /// or square wave by sampling from a pre-generated lookup table.
 ///
@@ -381,9 +710,8 @@ impl Signal for RuntimeOscillator {
 ///  duplicating memory.
 // TODO: ideally the table sample type would be typed so the table could be
 //  cached in a different/lower sample type without requiring conversion.
+#[derive(Clone)]
 pub struct LookupOscillator<'a, LookupSample: Sample + FromSample<f32>> {
-    sample_rate: usize,
-
     /// The table is implemented as a reference to allow a shared oscillator
     /// allocator to handle a pool of waveform lookup tables.
     ///
@@ -391,24 +719,56 @@ pub struct LookupOscillator<'a, LookupSample: Sample + FromSample<f32>> {
     /// rate) to share the same lookup table to avoid duplicating memory.
     table: &'a [LookupSample],
 
-    index: usize,
+    /// The position of the next sample in the table, wrapped at the table length.
+    position: f32,
+    /// How far the position moves through the table each sample.
+    ///
+    /// Playing the table at it's own rate steps 1 sample at a time,
+    /// retuning it as a [`ToneSource`] steps faster or slower.
+    step: f32,
+
+    /// The frequency of the waveform in the table, when known,
+    /// used to work out the step for a [`ToneSource`] frequency.
+    table_frequency: Option<Hertz>,
+    /// The amplitude the output is scaled by when used as a [`ToneSource`].
+    amplitude: f32,
 }
 
 impl<'a, LookupSample: Sample + FromSample<f32>> LookupOscillator<'a, LookupSample> {
     /// Constructs a new lookup table-based oscillator from the provided table.
-    pub fn new_from_table(sample_rate: usize, table: &'a [LookupSample]) -> Self {
-        // TODO: error is table.len() != sample_rate
+    ///
+    /// The table holds a whole number of waveform cycles and is looped
+    /// over, it doesn't need to be the same length as the sample rate.
+    pub fn new_from_table(table: &'a [LookupSample]) -> Self {
         Self {
-            sample_rate,
             table,
-            index: 0,
+            position: 0.0,
+            step: 1.0,
+            table_frequency: None,
+            amplitude: 1.0,
         }
     }
 
+    /// Sets the frequency the table was built for, which
+    /// allows retuning the oscillator as a [`ToneSource`].
+    ///
+    /// Tables from [`OscillatorType::build_table`] hold one second of
+    /// audio, so the frequency is the one the table was built with.
+    pub fn with_table_frequency(mut self, frequency: Hertz) -> Self {
+        self.table_frequency = Some(frequency);
+        self
+    }
+
     /// Take a sample at the specified sample index from the oscillator.
+    ///
+    /// An empty table produces silence.
     fn sample_at(&self, index: usize) -> LookupSample {
+        if self.table.is_empty() {
+            return LookupSample::EQUILIBRIUM;
+        }
+
         // Modulo ensures that the sample index is wrapped
-        // within the sample rate of the oscillator table.
+        // within the length of the oscillator table.
         self.table[index % self.table.len()]
     }
 }
@@ -418,17 +778,47 @@ impl<'a, LookupSample: Sample + FromSample<f32>> Oscillator<LookupSample>
 {
     /// Take a sample at the specified sample index from the oscillator.
     fn sample(&mut self) -> LookupSample {
-        let sample = self.table[self.index];
+        let sample = self.sample_at(self.position as usize);
 
-        self.index = self.index + 1;
-        if self.index >= self.sample_rate {
-            self.index = 0;
+        self.position += self.step;
+        if self.position >= self.table.len() as f32 {
+            self.position = libm::fmodf(self.position, self.table.len().max(1) as f32);
         }
 
         sample
     }
 }
 
+/// Retuning a lookup oscillator steps through it's table faster or
+/// slower, which requires knowing the frequency the table was built
+/// for with [`LookupOscillator::with_table_frequency`]. Without it
+/// the oscillator keeps playing at the table's own frequency.
+impl<'a, LookupSample: Sample + FromSample<f32>> ToneSource for LookupOscillator<'a, LookupSample>
+where
+    f32: FromSample<LookupSample>,
+{
+    fn set_frequency(&mut self, frequency: Hertz) {
+        if let Some(table_frequency) = self.table_frequency
+            && table_frequency.0 > 0.0
+        {
+            self.step = frequency.0 / table_frequency.0;
+        }
+    }
+
+    fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+
+    fn reset(&mut self) {
+        self.position = 0.0;
+    }
+
+    fn next(&mut self) -> f32 {
+        let sample: f32 = self.sample().to_sample();
+        sample * self.amplitude
+    }
+}
+
 /// Allows using the oscillator in conjunction with other Signal traits.
 impl<'a, LookupSample: Sample + FromSample<f32>> Signal for LookupOscillator<'a, LookupSample>
 where
@@ -459,6 +849,90 @@ pub struct OscillatorAllocator<
 impl<LookupSample: Sample + FromSample<f32>, const SAMPLE_RATE: usize, const MAX_TABLES: usize>
     OscillatorAllocator<LookupSample, SAMPLE_RATE, MAX_TABLES>
 {
+    /// Constructs a new allocator without any tables.
+    pub fn new() -> Self {
+        Self {
+            lookup: FnvIndexMap::new(),
+        }
+    }
+
+    /// Generates the table for an oscillator waveform, unless it already exists.
+    pub fn allocate(
+        &mut self,
+        osc: OscillatorType,
+        frequency: Hertz,
+        duty_cycle: DutyCycle,
+    ) -> Result<(), TableError> {
+        if self.lookup.contains_key(&(osc, frequency, duty_cycle)) {
+            return Ok(());
+        }
+
+        // TODO: this will create the table on stack which will be too big for most MCUs
+        let mut table: [LookupSample; SAMPLE_RATE] = array::from_fn(|_| 0.0.to_sample());
+        osc.build_table(&mut table, SAMPLE_RATE, frequency, duty_cycle)?;
+
+        self.lookup
+            .insert((osc, frequency, duty_cycle), RefCell::new(table))
+            .map_err(|_| TableError::TableFull)?;
+
+        Ok(())
+    }
+
+    /// Borrows an allocated table, returning `None` if it hasn't been allocated.
+    ///
+    /// Any number of oscillators can play from the same borrowed table at
+    /// once, i.e. one [`LookupOscillator`] per voice, without copying it:
+    ///
+    /// ```
+    /// use catalina_engine::audio::oscillator::*;
+    /// use catalina_engine::core::Hertz;
+    ///
+    /// let mut allocator = OscillatorAllocator::<f32, 1_000, 4>::new();
+    /// allocator.allocate(OscillatorType::Sine, Hertz(1.0), DutyCycle::Half).unwrap();
+    ///
+    /// let table = allocator.table(OscillatorType::Sine, Hertz(1.0), DutyCycle::Half).unwrap();
+    /// let voices: [LookupOscillator<f32>; 8] = core::array::from_fn(|_| {
+    ///     LookupOscillator::new_from_table(&table[..]).with_table_frequency(Hertz(1.0))
+    /// });
+    /// ```
+    ///
+    /// Panics if the table is already mutably borrowed.
+    pub fn table(
+        &self,
+        osc: OscillatorType,
+        frequency: Hertz,
+        duty_cycle: DutyCycle,
+    ) -> Option<Ref<'_, [LookupSample; SAMPLE_RATE]>> {
+        self.lookup
+            .get(&(osc, frequency, duty_cycle))
+            .map(RefCell::borrow)
+    }
+
+    /// Returns the number of tables that have been allocated.
+    pub fn len(&self) -> usize {
+        self.lookup.len()
+    }
+
+    /// Returns true if no tables have been allocated.
+    pub fn is_empty(&self) -> bool {
+        self.lookup.is_empty()
+    }
+
+    /// Frees the table for an oscillator waveform, i.e. when a patch no longer
+    /// uses it, returning true if it was allocated.
+    ///
+    /// Oscillators already holding a clone of the table keep playing from it.
+    pub fn remove(&mut self, osc: OscillatorType, frequency: Hertz, duty_cycle: DutyCycle) -> bool {
+        self.lookup
+            .swap_remove(&(osc, frequency, duty_cycle))
+            .is_some()
+    }
+
+    /// Frees every table, making room for the tables of a new patch.
+    pub fn clear(&mut self) {
+        self.lookup.clear();
+    }
+
     /// Get an oscillator either using an existing waveform lookup table, or by generating a new one.
 
     /// Tries to find an existing oscillator table with the specified
@@ -469,32 +943,457 @@ impl<LookupSample: Sample + FromSample<f32>, const SAMPLE_RATE: usize, const MAX
         frequency: Hertz,
         duty_cycle: DutyCycle,
     ) -> Result<RefCell<[LookupSample; SAMPLE_RATE]>, TableError> {
-        let table = match self
-            .lookup
-            .iter()
-            .find(|entry| entry.0.0 == osc && entry.0.1 == frequency && entry.0.2 == duty_cycle)
-        {
-            Some(table) => RefCell::clone(table.1),
-            None => {
-                // If there was no cached lookup table, then we need to generate it.
+        self.allocate(osc, frequency, duty_cycle)?;
 
-                // TODO: this will create the table on stack which will be too big for most MCUs
-                let mut table: [LookupSample; SAMPLE_RATE] = array::from_fn(|_| 0.0.to_sample());
-                osc.build_table(&mut table, SAMPLE_RATE, frequency, duty_cycle)?;
+        // Clone the ref cell so we can return it.
+        self.lookup
+            .get(&(osc, frequency, duty_cycle))
+            .cloned()
+            .ok_or(TableError::TableFull)
+    }
+}
 
-                let cell = RefCell::new(table);
+impl<LookupSample: Sample + FromSample<f32>, const SAMPLE_RATE: usize, const MAX_TABLES: usize>
+    Default for OscillatorAllocator<LookupSample, SAMPLE_RATE, MAX_TABLES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-                // Clone the ref cell so we can return it after insert.
-                let clone = RefCell::clone(&cell);
+// Tests.
 
-                self.lookup
-                    .insert((osc, frequency, duty_cycle), cell)
-                    .map_err(|_| TableError::TableFull)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
 
-                clone
-            }
+    #[test]
+    fn test_sample_with_amplitude() {
+        let frequency = Hertz(440.0);
+        let expected = |index| {
+            let sample: f32 = sample_sine(index, 48_000, frequency);
+            sample * 0.5 * 128.0
         };
 
-        Ok(table)
+        // Scaling before the conversion keeps the bit that halving an i8 drops.
+        let (mut scaled_error, mut halved_error) = (0.0, 0.0);
+        for index in 0..1_000 {
+            let scaled: i8 = sample_with(
+                OscillatorType::Sine,
+                index,
+                48_000,
+                frequency,
+                0.5,
+                DutyCycle::Half,
+            );
+            let halved: i8 = sample_sine::<i8>(index, 48_000, frequency) / 2;
+
+            scaled_error += (scaled as f32 - expected(index)).abs();
+            halved_error += (halved as f32 - expected(index)).abs();
+        }
+
+        assert!(
+            scaled_error < halved_error,
+            "{scaled_error} is not less than {halved_error}"
+        );
+
+        let full: f32 = sample_with(
+            OscillatorType::Square,
+            0,
+            48_000,
+            frequency,
+            0.25,
+            DutyCycle::Half,
+        );
+        self::assert_eq!(full, 0.25);
+    }
+
+    #[test]
+    fn test_duty_cycle_percent() {
+        self::assert_eq!(DutyCycle::Percent(75).to_fractional(), 0.75);
+        self::assert_eq!(DutyCycle::Percent(150).to_fractional(), 1.0);
+    }
+
+    #[test]
+    fn test_parse_oscillator_type() {
+        self::assert_eq!("sine".parse(), Ok(OscillatorType::Sine));
+        self::assert_eq!(OscillatorType::try_from("Saw"), Ok(OscillatorType::Saw));
+        self::assert_eq!(" triangle ".parse(), Ok(OscillatorType::Triangle));
+        self::assert_eq!(
+            OscillatorType::try_from("SQUARE"),
+            Ok(OscillatorType::Square)
+        );
+        self::assert_eq!(
+            "noise2".parse::<OscillatorType>(),
+            Err(ParseError::UnknownOscillatorType)
+        );
+    }
+
+    #[test]
+    fn test_parse_duty_cycle() {
+        self::assert_eq!("12.5%".parse(), Ok(DutyCycle::Eight));
+        self::assert_eq!(DutyCycle::try_from("25%"), Ok(DutyCycle::Quarter));
+        self::assert_eq!("33%".parse(), Ok(DutyCycle::Third));
+        self::assert_eq!("50 %".parse(), Ok(DutyCycle::Half));
+        self::assert_eq!("75%".parse(), Ok(DutyCycle::Percent(75)));
+        self::assert_eq!("0.33".parse(), Ok(DutyCycle::Third));
+        self::assert_eq!("0.125".parse(), Ok(DutyCycle::Eight));
+        self::assert_eq!("0.7".parse(), Ok(DutyCycle::Percent(70)));
+
+        for invalid in ["noise2", "150%", "-5%", "12.25%", ""] {
+            self::assert_eq!(
+                invalid.parse::<DutyCycle>(),
+                Err(ParseError::InvalidDutyCycle),
+                "{invalid}"
+            );
+        }
+    }
+
+    /// Counts the rising zero crossings of the next `frames` samples.
+    fn rising_crossings(osc: &mut RuntimeOscillator, frames: usize) -> usize {
+        let mut last: f32 = osc.sample();
+        let mut crossings = 0;
+        for _ in 1..frames {
+            let sample: f32 = osc.sample();
+            if last < 0.0 && sample >= 0.0 {
+                crossings += 1;
+            }
+            last = sample;
+        }
+        crossings
+    }
+
+    #[test]
+    fn test_sample_rate_change_keeps_frequency() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(440.0));
+        let before = rising_crossings(&mut osc, 48_000 / 10);
+
+        // A tenth of a second has the same amount of cycles at the new rate.
+        osc.set_sample_rate(96_000);
+        self::assert_eq!(osc.get_sample_rate(), 96_000);
+        let after = rising_crossings(&mut osc, 96_000 / 10);
+
+        assert!((before as i32 - 44).abs() <= 1, "{before} crossings");
+        assert!(
+            (after as i32 - before as i32).abs() <= 1,
+            "{after} crossings"
+        );
+    }
+
+    #[test]
+    fn test_pwm_sweep_tracks_width() {
+        // 480Hz at 48kHz is exactly 100 samples per cycle.
+        let mut osc = RuntimeOscillator::new(OscillatorType::Square, 48_000, Hertz(480.0));
+
+        for step in 1..10 {
+            let width = step as f32 / 10.0;
+            osc.set_pwm(width);
+
+            // High for `width` of each cycle, so the average is `2 * width - 1`.
+            let sum: f32 = (0..1_000).map(|_| -> f32 { osc.sample() }).sum();
+            let average = sum / 1_000.0;
+            assert!(
+                (average - (2.0 * width - 1.0)).abs() < 0.03,
+                "width {width} averaged {average}"
+            );
+        }
+
+        // Setting a duty cycle goes back to the enum.
+        osc.set_duty_cycle(DutyCycle::Half);
+        let sum: f32 = (0..1_000).map(|_| -> f32 { osc.sample() }).sum();
+        assert!((sum / 1_000.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_inverted_square() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Square, 48_000, Hertz(440.0));
+        osc.set_duty_cycle(DutyCycle::Quarter);
+
+        let mut inverted = RuntimeOscillator::new(OscillatorType::Square, 48_000, Hertz(440.0));
+        inverted.set_duty_cycle(DutyCycle::Quarter);
+        inverted.set_inverted(true);
+
+        for _ in 0..1_000 {
+            let a: f32 = osc.sample();
+            let b: f32 = inverted.sample();
+            self::assert_eq!(a, -b);
+        }
+
+        for phase in [0.0, 0.2, 0.5, 0.9] {
+            let a: f32 = square(phase, DutyCycle::Quarter);
+            let b: f32 = square_inverted(phase, DutyCycle::Quarter);
+            self::assert_eq!(a, -b);
+        }
+    }
+
+    #[test]
+    fn test_lookup_table_shorter_than_sample_rate() {
+        let table = [0.0, 0.5, 1.0, 0.5];
+        let mut osc = LookupOscillator::new_from_table(&table);
+
+        let samples: [f32; 10] = array::from_fn(|_| osc.sample());
+        self::assert_eq!(samples, [0.0, 0.5, 1.0, 0.5, 0.0, 0.5, 1.0, 0.5, 0.0, 0.5]);
+    }
+
+    /// A voice generic over where it's tone comes from.
+    struct Voice<'a> {
+        source: &'a mut dyn ToneSource,
+    }
+
+    impl Voice<'_> {
+        fn play(&mut self, frequency: Hertz, buffer: &mut [f32]) {
+            self.source.reset();
+            self.source.set_frequency(frequency);
+            self.source.set_amplitude(0.5);
+            for sample in buffer.iter_mut() {
+                *sample = self.source.next();
+            }
+        }
+    }
+
+    #[test]
+    fn test_tone_sources_are_interchangeable() {
+        const SAMPLE_RATE: usize = 4_800;
+
+        let mut table = [0.0f32; SAMPLE_RATE];
+        OscillatorType::Sine
+            .build_table(&mut table, SAMPLE_RATE, Hertz(100.0), DutyCycle::Half)
+            .unwrap();
+        let mut lookup =
+            LookupOscillator::new_from_table(&table).with_table_frequency(Hertz(100.0));
+        let mut runtime = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(100.0));
+
+        // Both play an octave above the table at half amplitude.
+        let mut from_lookup = [0.0; 200];
+        let mut from_runtime = [0.0; 200];
+        Voice {
+            source: &mut lookup,
+        }
+        .play(Hertz(200.0), &mut from_lookup);
+        Voice {
+            source: &mut runtime,
+        }
+        .play(Hertz(200.0), &mut from_runtime);
+
+        for (i, (a, b)) in from_lookup.iter().zip(from_runtime.iter()).enumerate() {
+            assert!((a - b).abs() < 1e-3, "sample {i}: {a} != {b}");
+        }
+        assert!(from_lookup.iter().all(|sample| sample.abs() <= 0.5 + 1e-6));
+        assert!(from_lookup.iter().any(|sample| sample.abs() > 0.49));
+    }
+
+    #[test]
+    fn test_lookup_empty_table() {
+        let mut osc = LookupOscillator::<f32>::new_from_table(&[]);
+        self::assert_eq!(osc.sample(), 0.0);
+    }
+
+    #[test]
+    fn test_clone_runtime_oscillator() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Square, 48_000, Hertz(440.0));
+        osc.set_duty_cycle(DutyCycle::Percent(30));
+        osc.set_inverted(true);
+
+        // Advance the phase so the clone has to carry it over.
+        for _ in 0..100 {
+            let _: f32 = osc.sample();
+        }
+
+        let mut clone = osc.clone();
+        self::assert_eq!(clone, osc);
+
+        for _ in 0..1_000 {
+            let a: f32 = osc.sample();
+            let b: f32 = clone.sample();
+            self::assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_render_sine_source() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(480.0));
+        osc.set_amplitude(0.5);
+
+        let mut buffer = [0.0f32; 480];
+        AudioSource::render(&mut osc, &mut buffer);
+
+        let peak = buffer.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 0.5).abs() < 1e-3, "peak {peak}");
+    }
+
+    #[test]
+    fn test_clear_allocator() {
+        let mut allocator = OscillatorAllocator::<f32, 100, 2>::new();
+        let sine = (OscillatorType::Sine, Hertz(1.0), DutyCycle::Half);
+        let saw = (OscillatorType::Saw, Hertz(1.0), DutyCycle::Half);
+
+        allocator.allocate(sine.0, sine.1, sine.2).unwrap();
+        allocator.allocate(saw.0, saw.1, saw.2).unwrap();
+        assert!(matches!(
+            allocator.allocate(OscillatorType::Triangle, Hertz(1.0), DutyCycle::Half),
+            Err(TableError::TableFull)
+        ));
+
+        self::assert_eq!(allocator.remove(saw.0, saw.1, saw.2), true);
+        self::assert_eq!(allocator.remove(saw.0, saw.1, saw.2), false);
+        self::assert_eq!(allocator.len(), 1);
+
+        allocator.clear();
+        assert!(allocator.is_empty());
+        assert!(allocator.table(sine.0, sine.1, sine.2).is_none());
+
+        // The table is generated again the next time it's looked up.
+        let table = allocator
+            .lookup_or_allocate(sine.0, sine.1, sine.2)
+            .unwrap();
+        self::assert_eq!(table.borrow()[25], 1.0);
+        self::assert_eq!(allocator.len(), 1);
+    }
+
+    #[test]
+    fn test_voices_share_table() {
+        let mut allocator = OscillatorAllocator::<f32, 1_000, 2>::new();
+        assert!(
+            allocator
+                .table(OscillatorType::Sine, Hertz(10.0), DutyCycle::Half)
+                .is_none()
+        );
+        allocator
+            .allocate(OscillatorType::Sine, Hertz(10.0), DutyCycle::Half)
+            .unwrap();
+        // Allocating again reuses the table.
+        allocator
+            .allocate(OscillatorType::Sine, Hertz(10.0), DutyCycle::Half)
+            .unwrap();
+
+        let table = allocator
+            .table(OscillatorType::Sine, Hertz(10.0), DutyCycle::Half)
+            .unwrap();
+
+        // A voice for each of 8 notes, all playing from the one table.
+        let mut voices: FnvIndexMap<u8, LookupOscillator<f32>, 8> = FnvIndexMap::new();
+        for voice in 0..8 {
+            let mut osc =
+                LookupOscillator::new_from_table(&table[..]).with_table_frequency(Hertz(10.0));
+            osc.set_frequency(Hertz(10.0 * (voice + 1) as f32));
+            assert!(voices.insert(voice, osc).is_ok());
+        }
+
+        for (voice, osc) in voices.iter_mut() {
+            let mut buffer = [0.0; 100];
+            for sample in buffer.iter_mut() {
+                *sample = ToneSource::next(osc);
+            }
+
+            // Each voice reads the sine at it's own rate.
+            let expected = libm::sinf(PI2 * 10.0 * (*voice + 1) as f32 * 25.0 / 1_000.0);
+            assert!((buffer[25] - expected).abs() < 1e-3, "voice {voice}");
+        }
+    }
+
+    /// Measures the frequency of each 100ms window from the rising zero crossings.
+    fn window_frequencies(osc: &mut RuntimeOscillator, windows: usize) -> Vec<f32> {
+        let mut last: f32 = osc.sample();
+        (0..windows)
+            .map(|_| {
+                let (mut first, mut latest, mut cycles) = (None, 0.0, 0);
+                for i in 0..4_800 {
+                    let sample: f32 = osc.sample();
+                    if last < 0.0 && sample >= 0.0 {
+                        // Interpolate where between the samples the crossing was.
+                        let crossing = i as f32 - sample / (sample - last);
+                        if first.is_none() {
+                            first = Some(crossing);
+                        } else {
+                            cycles += 1;
+                        }
+                        latest = crossing;
+                    }
+                    last = sample;
+                }
+                48_000.0 * cycles as f32 / (latest - first.unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drift_wanders_within_range() {
+        let cents = |frequency: f32| 1200.0 * libm::log2f(frequency / 440.0);
+
+        let mut steady = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(440.0));
+        for frequency in window_frequencies(&mut steady, 20) {
+            assert!(
+                cents(frequency).abs() < 0.5,
+                "steady drifted to {frequency}"
+            );
+        }
+
+        let mut drifting = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(440.0));
+        drifting.set_drift(10.0);
+        let drifted: Vec<f32> = window_frequencies(&mut drifting, 200)
+            .into_iter()
+            .map(cents)
+            .collect();
+
+        let low = drifted.iter().fold(f32::MAX, |a, b| a.min(*b));
+        let high = drifted.iter().fold(f32::MIN, |a, b| a.max(*b));
+        assert!(high - low > 2.0, "only wandered from {low} to {high} cents");
+        assert!(
+            low > -10.5 && high < 10.5,
+            "wandered from {low} to {high} cents"
+        );
+
+        // It's a slow wander rather than a wobble between each window.
+        for pair in drifted.windows(2) {
+            assert!(
+                (pair[1] - pair[0]).abs() < 2.0,
+                "{} -> {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_frequency_clamped_below_nyquist() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(30_000.0));
+        self::assert_eq!(osc.get_frequency(), Hertz(23_520.0));
+
+        // A 30kHz sine would fold back to 18kHz, crossing zero far less
+        // often than the clamped frequency just under Nyquist does.
+        let mut previous: f32 = Signal::next(&mut osc);
+        let mut crossings = 0;
+        for _ in 0..48_000 {
+            let sample: f32 = Signal::next(&mut osc);
+            if (sample < 0.0) != (previous < 0.0) {
+                crossings += 1;
+            }
+            previous = sample;
+        }
+        assert!(crossings > 2 * 23_000, "crossed zero {crossings} times");
+
+        ToneSource::set_frequency(&mut osc, Hertz(-440.0));
+        self::assert_eq!(osc.get_frequency(), Hertz(0.0));
+        ToneSource::set_frequency(&mut osc, Hertz(f32::NAN));
+        self::assert_eq!(osc.get_frequency(), Hertz(0.0));
+
+        // Lowering the sample rate clamps to the new Nyquist.
+        ToneSource::set_frequency(&mut osc, Hertz(20_000.0));
+        osc.set_sample_rate(22_050);
+        self::assert_eq!(osc.get_frequency(), Hertz(10_804.5));
+    }
+
+    #[test]
+    fn test_sample_rate_round_trip_restores_frequency() {
+        let mut osc = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(20_000.0));
+
+        // Clamped while the sample rate is too low for it.
+        osc.set_sample_rate(22_050);
+        self::assert_eq!(osc.get_frequency(), Hertz(10_804.5));
+
+        // And back to the tuned frequency once it's raised again.
+        osc.set_sample_rate(48_000);
+        self::assert_eq!(osc.get_frequency(), Hertz(20_000.0));
     }
 }
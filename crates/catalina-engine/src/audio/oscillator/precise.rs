@@ -0,0 +1,207 @@
+//! Double precision waveforms and an oscillator built on them, for
+//! mastering quality offline rendering.
+//!
+//! The rest of the oscillators work in `f32`, which is plenty for real-time
+//! use, but a `f32` phase accumulator gains a little error every sample
+//! that adds up to an audible drift over renders minutes long.
+
+use crate::audio::{
+    AudioSource,
+    oscillator::{DutyCycle, OscillatorType},
+    signal::Signal,
+};
+
+use core::f64::consts::TAU;
+
+/// Generates a sample of a sine wave at the provided phase, like [`super::sine`].
+#[inline]
+pub fn sine(phase: f64) -> f64 {
+    libm::sin(TAU * phase)
+}
+
+/// Generates a sample of a saw wave at the provided phase, like [`super::saw`].
+#[inline]
+pub fn saw(phase: f64) -> f64 {
+    1.0 - (phase % 1.0) * 2.0
+}
+
+/// Generates a sample of a triangle wave at the provided phase, like [`super::triangle`].
+#[inline]
+pub fn triangle(phase: f64) -> f64 {
+    let slope = phase % 1.0 * 2.0;
+    if slope < 1.0 {
+        -1.0 + slope * 2.0
+    } else {
+        3.0 - slope * 2.0
+    }
+}
+
+/// Generates a sample of a pulse wave at the provided phase, like [`super::pulse`].
+#[inline]
+pub fn pulse(phase: f64, width: f64, invert: bool) -> f64 {
+    let high = phase % 1.0 < width;
+    if high != invert { 1.0 } else { -1.0 }
+}
+
+/// An oscillator that keeps it's phase and frequency in `f64`.
+///
+/// Works like [`RuntimeOscillator`](super::RuntimeOscillator) but renders
+/// `f64` frames, convert to `f32` only once the render is mixed down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreciseOscillator {
+    osc_type: OscillatorType,
+
+    sample_rate: usize,
+    frequency: f64,
+
+    /// Fractional duty cycle for square waves.
+    duty_cycle: DutyCycle,
+
+    /// How far the phase advances each sample.
+    increment: f64,
+    /// The position in the cycle from 0.0 to 1.0.
+    phase: f64,
+}
+
+impl PreciseOscillator {
+    /// Construct a new double precision oscillator with a frequency in hertz.
+    pub fn new(osc_type: OscillatorType, sample_rate: usize, frequency: f64) -> Self {
+        Self {
+            osc_type,
+            sample_rate,
+            frequency,
+            duty_cycle: DutyCycle::Half,
+            increment: frequency / sample_rate as f64,
+            phase: 0.0,
+        }
+    }
+
+    /// Returns the frequency of the oscillator in hertz.
+    #[inline]
+    pub const fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Sets the frequency of the oscillator in hertz, keeping the phase.
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.frequency = frequency;
+        self.increment = frequency / self.sample_rate as f64;
+    }
+
+    /// Sets the duty cycle used for square waves.
+    pub fn set_duty_cycle(&mut self, duty_cycle: DutyCycle) {
+        self.duty_cycle = duty_cycle;
+    }
+
+    /// Returns the position in the cycle from 0.0 to 1.0.
+    #[inline]
+    pub const fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    /// Restarts the waveform from the beginning of it's cycle.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Takes the next sample from the oscillator and advances the phase.
+    pub fn next_sample(&mut self) -> f64 {
+        let sample = match self.osc_type {
+            OscillatorType::Sine => sine(self.phase),
+            OscillatorType::Saw => saw(self.phase),
+            OscillatorType::Triangle => triangle(self.phase),
+            OscillatorType::Square => {
+                pulse(self.phase, self.duty_cycle.to_fractional() as f64, false)
+            }
+        };
+
+        self.phase += self.increment;
+        if self.phase >= 1.0 {
+            self.phase -= libm::floor(self.phase);
+        }
+
+        sample
+    }
+}
+
+impl Signal for PreciseOscillator {
+    type Frame = f64;
+
+    #[inline]
+    fn next(&mut self) -> Self::Frame {
+        self.next_sample()
+    }
+}
+
+impl AudioSource for PreciseOscillator {
+    type Frame = f64;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        for frame in buffer.iter_mut() {
+            *frame = self.next_sample();
+        }
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::oscillator::{self, Oscillator, RuntimeOscillator},
+        core::Hertz,
+    };
+
+    /// Returns how far apart two phases are, in cycles.
+    fn phase_error(actual: f64, expected: f64) -> f64 {
+        let error = (actual - expected).rem_euclid(1.0);
+        error.min(1.0 - error)
+    }
+
+    #[test]
+    fn test_less_drift_than_f32() {
+        // Three minutes of A4 at 48kHz, which is 11/1200ths of a cycle per sample.
+        const SAMPLES: u64 = 48_000 * 180;
+
+        let mut single = RuntimeOscillator::new(OscillatorType::Sine, 48_000, Hertz(440.0));
+        let mut double = PreciseOscillator::new(OscillatorType::Sine, 48_000, 440.0);
+        for _ in 0..SAMPLES {
+            let _: f32 = single.sample();
+            double.next_sample();
+        }
+
+        let expected = (SAMPLES * 11 % 1_200) as f64 / 1_200.0;
+        let single_error = phase_error(single.phase as f64, expected);
+        let double_error = phase_error(double.phase(), expected);
+
+        assert!(double_error < 1e-6, "f64 drifted {double_error} cycles");
+        assert!(
+            double_error * 1_000.0 < single_error,
+            "f64 drifted {double_error} cycles, f32 {single_error}"
+        );
+    }
+
+    #[test]
+    fn test_waveforms_match_f32() {
+        for phase in [0.0, 0.1, 0.25, 0.4, 0.5, 0.75, 0.9] {
+            let expected: [f32; 3] = [
+                oscillator::sine(phase),
+                oscillator::saw(phase),
+                oscillator::triangle(phase),
+            ];
+            let actual = [
+                sine(phase as f64),
+                saw(phase as f64),
+                triangle(phase as f64),
+            ];
+
+            for (actual, expected) in actual.into_iter().zip(expected) {
+                assert!(
+                    (actual - expected as f64).abs() < 1e-6,
+                    "phase {phase}: {actual} != {expected}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! A lookup oscillator that plays stereo wavetables.
+//!
+//! Stereo tables hold a left and right sample for each position, so
+//! the two channels can differ, i.e. phase-offset or detuned copies of
+//! a wave for a wide sound, without running two oscillators.
+
+use crate::{
+    audio::{AudioSource, Frame, Stereo, oscillator::Oscillator, signal::Signal},
+    core::Hertz,
+};
+
+/// Provides an oscillator that plays stereo frames from a lookup table,
+/// like [`LookupOscillator`](super::LookupOscillator) does for mono tables.
+#[derive(Clone)]
+pub struct StereoLookupOscillator<'a> {
+    /// The table of left and right sample pairs, shared like a mono table.
+    table: &'a [Stereo<f32>],
+
+    /// The position of the next frame in the table, wrapped at the table length.
+    position: f32,
+    /// How far the position moves through the table each frame.
+    step: f32,
+
+    /// The frequency of the waveform in the table, when known,
+    /// used to work out the step for a new frequency.
+    table_frequency: Option<Hertz>,
+    /// The amplitude both channels are scaled by.
+    amplitude: f32,
+}
+
+impl<'a> StereoLookupOscillator<'a> {
+    /// Constructs a new oscillator looping over the provided table.
+    pub fn new_from_table(table: &'a [Stereo<f32>]) -> Self {
+        Self {
+            table,
+            position: 0.0,
+            step: 1.0,
+            table_frequency: None,
+            amplitude: 1.0,
+        }
+    }
+
+    /// Sets the frequency the table was built for, which
+    /// allows retuning the oscillator with [`Self::set_frequency`].
+    pub fn with_table_frequency(mut self, frequency: Hertz) -> Self {
+        self.table_frequency = Some(frequency);
+        self
+    }
+
+    /// Retunes the oscillator, if the table's frequency is known.
+    pub fn set_frequency(&mut self, frequency: Hertz) {
+        if let Some(table_frequency) = self.table_frequency
+            && table_frequency.0 > 0.0
+        {
+            self.step = frequency.0 / table_frequency.0;
+        }
+    }
+
+    /// Sets the amplitude both channels are scaled by.
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+
+    /// Restarts the oscillator from the start of the table.
+    pub fn reset(&mut self) {
+        self.position = 0.0;
+    }
+}
+
+impl<'a> Oscillator<Stereo<f32>> for StereoLookupOscillator<'a> {
+    /// Take the next frame from the table, silence if the table is empty.
+    fn sample(&mut self) -> Stereo<f32> {
+        if self.table.is_empty() {
+            return Stereo::<f32>::EQUILIBRIUM;
+        }
+
+        let [left, right] = self.table[self.position as usize % self.table.len()];
+
+        self.position += self.step;
+        if self.position >= self.table.len() as f32 {
+            self.position = libm::fmodf(self.position, self.table.len() as f32);
+        }
+
+        [left * self.amplitude, right * self.amplitude]
+    }
+}
+
+impl<'a> Signal for StereoLookupOscillator<'a> {
+    type Frame = Stereo<f32>;
+
+    fn next(&mut self) -> Self::Frame {
+        self.sample()
+    }
+}
+
+impl<'a> AudioSource for StereoLookupOscillator<'a> {
+    type Frame = Stereo<f32>;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        Oscillator::render(self, buffer);
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_distinct_channels() {
+        // A rising ramp on the left and a falling ramp on the right.
+        let table = [[0.0, 1.0], [0.25, 0.75], [0.5, 0.5], [0.75, 0.25]];
+        let mut osc = StereoLookupOscillator::new_from_table(&table);
+
+        let mut buffer = [[0.0; 2]; 6];
+        AudioSource::render(&mut osc, &mut buffer);
+        self::assert_eq!(
+            buffer,
+            [
+                [0.0, 1.0],
+                [0.25, 0.75],
+                [0.5, 0.5],
+                [0.75, 0.25],
+                [0.0, 1.0],
+                [0.25, 0.75],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_retunes_from_table_frequency() {
+        let table = [[0.0, 1.0], [0.25, 0.75], [0.5, 0.5], [0.75, 0.25]];
+        let mut osc =
+            StereoLookupOscillator::new_from_table(&table).with_table_frequency(Hertz(1.0));
+        osc.set_frequency(Hertz(2.0));
+        osc.set_amplitude(0.5);
+
+        // Twice the frequency skips every other frame.
+        self::assert_eq!(osc.sample(), [0.0, 0.5]);
+        self::assert_eq!(osc.sample(), [0.25, 0.25]);
+        self::assert_eq!(osc.sample(), [0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_empty_table_is_silent() {
+        let mut osc = StereoLookupOscillator::new_from_table(&[]);
+        self::assert_eq!(Signal::next(&mut osc), [0.0, 0.0]);
+    }
+}
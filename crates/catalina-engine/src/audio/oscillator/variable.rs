@@ -6,10 +6,17 @@
 //! Ported from Emilie Gillet's [implementation in Mutable Instrument's Plaits](https://github.com/pichenettes/eurorack/blob/master/plaits/dsp/oscillator/variable_shape_oscillator.h) from 2016.
 
 use crate::{
-    audio::{FromSample, Mono, Sample, oscillator::Oscillator, signal::Signal},
-    core::Hertz,
+    audio::{
+        AudioSource, FromSample, Mono, Sample,
+        oscillator::{Oscillator, ToneSource},
+        signal::Signal,
+    },
+    core::{Hertz, param::Smoothed},
 };
 
+/// The default time in seconds parameter changes are smoothed over.
+const DEFAULT_SMOOTHING_TIME: f32 = 0.005;
+
 /// Ported from https://github.com/pichenettes/eurorack/blob/master/plaits/dsp/oscillator/variable_shape_oscillator.h
 fn compute_naive_sample(
     phase: f32,
@@ -58,6 +65,7 @@ pub fn this_integrated_blep_sample(t: f32) -> f32 {
 /// Implements an oscillator that's waveform shape can be morphed and changed.
 ///
 /// Ported from [Mutable Instrument's Plaits](https://github.com/pichenettes/eurorack/blob/master/plaits/dsp/oscillator/variable_shape_oscillator.h) originally written by Emilie Gillet in 2023.
+#[derive(Clone)]
 pub struct VariableShapeOscillator {
     /// The sample rate of the audio engine.
     sample_rate: usize,
@@ -76,6 +84,15 @@ pub struct VariableShapeOscillator {
     slave_frequency: f32,
     pulse_width: f32,
     waveshape: f32,
+
+    // The targets the parameters are interpolated towards each sample.
+    master_frequency_target: Smoothed,
+    slave_frequency_target: Smoothed,
+    pulse_width_target: Smoothed,
+    waveshape_target: Smoothed,
+
+    /// The amplitude the output is scaled by when used as a [`ToneSource`].
+    amplitude: f32,
 }
 
 impl VariableShapeOscillator {
@@ -95,6 +112,13 @@ impl VariableShapeOscillator {
             slave_frequency: 0.1,
             pulse_width: 0.5,
             waveshape: 0.0,
+
+            master_frequency_target: Smoothed::new(sample_rate, DEFAULT_SMOOTHING_TIME, 0.0),
+            slave_frequency_target: Smoothed::new(sample_rate, DEFAULT_SMOOTHING_TIME, 0.1),
+            pulse_width_target: Smoothed::new(sample_rate, DEFAULT_SMOOTHING_TIME, 0.5),
+            waveshape_target: Smoothed::new(sample_rate, DEFAULT_SMOOTHING_TIME, 0.0),
+
+            amplitude: 1.0,
         };
 
         osc.set_frequency(440.0.into());
@@ -103,22 +127,53 @@ impl VariableShapeOscillator {
         osc.set_sync(false);
         osc.set_sync_frequency(220.0.into());
 
+        // Start at the initial settings rather than gliding to them.
+        osc.snap_parameters();
+
         osc
     }
 
+    /// Sets the time in seconds the frequency, pulse width and
+    /// waveshape take to reach new values. Zero changes them instantly.
+    pub fn set_smoothing_time(&mut self, seconds: f32) {
+        self.master_frequency_target.set_time(seconds);
+        self.slave_frequency_target.set_time(seconds);
+        self.pulse_width_target.set_time(seconds);
+        self.waveshape_target.set_time(seconds);
+    }
+
+    /// Jumps the frequency, pulse width and waveshape straight to their targets.
+    pub fn snap_parameters(&mut self) {
+        for target in [
+            &mut self.master_frequency_target,
+            &mut self.slave_frequency_target,
+            &mut self.pulse_width_target,
+            &mut self.waveshape_target,
+        ] {
+            target.set_immediate(target.target());
+        }
+
+        self.master_frequency = self.master_frequency_target.value();
+        self.slave_frequency = self.slave_frequency_target.value();
+        self.pulse_width = self.pulse_width_target.value();
+        self.waveshape = self.waveshape_target.value();
+    }
+
     /// Sets the frequency of the oscillator.
     pub fn set_frequency(&mut self, frequency: Hertz) {
         let freq: f32 = frequency.hertz() / self.sample_rate as f32;
-        self.master_frequency = if freq >= 0.25 { 0.25 } else { freq };
+        self.master_frequency_target
+            .set(if freq >= 0.25 { 0.25 } else { freq });
     }
 
     /// Sets the pulse width for square waves or saw, ramp, triangle waves otherwise.
     pub fn set_pulse_width(&mut self, pw: f32) {
-        if self.slave_frequency >= 0.25 {
-            self.pulse_width = 0.5;
+        let slave_frequency = self.slave_frequency_target.target();
+        if slave_frequency >= 0.25 {
+            self.pulse_width_target.set(0.5);
         } else {
-            self.pulse_width =
-                pw.clamp(self.slave_frequency * 2.0, 1.0 - 2.0 * self.slave_frequency);
+            self.pulse_width_target
+                .set(pw.clamp(slave_frequency * 2.0, 1.0 - 2.0 * slave_frequency));
         }
     }
 
@@ -126,7 +181,7 @@ impl VariableShapeOscillator {
     ///
     /// 0 is saw/ramp/triangle wave, 1 is square.
     pub fn set_waveshape(&mut self, waveshape: f32) {
-        self.waveshape = waveshape;
+        self.waveshape_target.set(waveshape);
     }
 
     /// Enables the sync oscillator.
@@ -137,14 +192,63 @@ impl VariableShapeOscillator {
     /// Sets the frequency of the sync oscillator.
     pub fn set_sync_frequency(&mut self, frequency: Hertz) {
         let freq = frequency.hertz() / self.sample_rate as f32;
-        self.pulse_width = if freq >= 0.25 { 0.5 } else { self.pulse_width };
-        self.slave_frequency = if freq >= 0.25 { 0.25 } else { freq };
+        if freq >= 0.25 {
+            self.pulse_width_target.set(0.5);
+        }
+        self.slave_frequency_target
+            .set(if freq >= 0.25 { 0.25 } else { freq });
+    }
+
+    /// Renders the waveform at the current settings into a table
+    /// for cheap playback with a [`LookupOscillator`](super::LookupOscillator).
+    ///
+    /// The parameters are snapped to their targets and the oscillator is
+    /// restarted, before and after rendering. For the table to loop cleanly
+    /// it should hold a whole number of periods, i.e. one period of
+    /// `sample_rate / frequency` samples, or a full `sample_rate` worth
+    /// like [`OscillatorType::build_table`](super::OscillatorType::build_table).
+    /// Play it back with the frequency the oscillator was tuned to as the
+    /// lookup oscillator's table frequency.
+    ///
+    /// The samples aren't scaled by the amplitude.
+    pub fn render_table(&mut self, table: &mut [f32]) {
+        self.snap_parameters();
+        ToneSource::reset(self);
+
+        // Run through the table once first, so the band-limiting carried
+        // between samples wraps around the end of the table seamlessly.
+        for _ in 0..table.len() {
+            let _: f32 = self.sample();
+        }
+        for sample in table.iter_mut() {
+            *sample = self.sample();
+        }
+
+        ToneSource::reset(self);
+    }
+
+    /// Moves the parameters a sample closer to their targets.
+    fn interpolate_parameters(&mut self) {
+        self.master_frequency = self.master_frequency_target.next();
+        self.slave_frequency = self.slave_frequency_target.next();
+        self.waveshape = self.waveshape_target.next();
+
+        // The frequency and pulse width glide independently, so keep the
+        // pulse width within the range the current frequency allows.
+        let pulse_width = self.pulse_width_target.next();
+        self.pulse_width = if self.slave_frequency >= 0.25 {
+            0.5
+        } else {
+            pulse_width.clamp(self.slave_frequency * 2.0, 1.0 - 2.0 * self.slave_frequency)
+        };
     }
 }
 
 impl<S: Sample + FromSample<f32>> super::Oscillator<S> for VariableShapeOscillator {
     /// Reads the next sample from the oscillator.
     fn sample(&mut self) -> S {
+        self.interpolate_parameters();
+
         let mut next_sample: f32 = self.next_sample;
 
         let mut reset = false;
@@ -257,3 +361,158 @@ impl Signal for VariableShapeOscillator {
         self.sample()
     }
 }
+
+/// The waveform's pitch comes from the sync (slave) oscillator, so retuning
+/// sets both the sync and master frequencies to keep hard sync in tune.
+impl ToneSource for VariableShapeOscillator {
+    fn set_frequency(&mut self, frequency: Hertz) {
+        VariableShapeOscillator::set_frequency(self, frequency);
+        self.set_sync_frequency(frequency);
+    }
+
+    fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+
+    fn reset(&mut self) {
+        self.master_phase = 0.0;
+        self.slave_phase = 0.0;
+        self.next_sample = 0.0;
+        self.high = false;
+    }
+
+    fn next(&mut self) -> f32 {
+        let sample: f32 = self.sample();
+        sample * self.amplitude
+    }
+}
+
+/// Renders the oscillator scaled by it's amplitude, so the
+/// morphing waveform can be played directly as a source.
+impl AudioSource for VariableShapeOscillator {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        for frame in buffer.iter_mut() {
+            *frame = ToneSource::next(self);
+        }
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::oscillator::LookupOscillator;
+
+    /// Returns the levels of the first few harmonics of each 100 sample period of a 480Hz tone.
+    fn spectrum_per_period(osc: &mut VariableShapeOscillator, periods: usize) -> Vec<[f32; 5]> {
+        (0..periods)
+            .map(|_| {
+                let samples: Vec<f32> = (0..100).map(|_| osc.sample()).collect();
+                core::array::from_fn(|harmonic| {
+                    let (mut re, mut im) = (0.0, 0.0);
+                    for (i, sample) in samples.iter().enumerate() {
+                        let phase = core::f32::consts::TAU * ((harmonic + 1) * i) as f32 / 100.0;
+                        re += sample * libm::cosf(phase);
+                        im += sample * libm::sinf(phase);
+                    }
+                    libm::sqrtf(re * re + im * im) / 50.0
+                })
+            })
+            .collect()
+    }
+
+    /// Renders a triangle that switches to a square part way through, returning
+    /// how far the spectrum moved between each period from the switch on.
+    fn waveshape_step(smoothing: f32) -> Vec<f32> {
+        let mut osc = VariableShapeOscillator::new(48_000);
+        osc.set_smoothing_time(smoothing);
+        osc.set_sync_frequency(480.0.into());
+        osc.set_frequency(480.0.into());
+        osc.set_pulse_width(0.5);
+        osc.snap_parameters();
+
+        let before = spectrum_per_period(&mut osc, 10);
+        osc.set_waveshape(1.0);
+        let after = spectrum_per_period(&mut osc, 40);
+
+        let mut previous = before[before.len() - 1];
+        after
+            .iter()
+            .map(|spectrum| {
+                let distance = spectrum
+                    .iter()
+                    .zip(previous.iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f32>();
+                previous = *spectrum;
+                libm::sqrtf(distance)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_render_mid_waveshape() {
+        let mut osc = VariableShapeOscillator::new(48_000);
+        ToneSource::set_frequency(&mut osc, 480.0.into());
+        osc.set_amplitude(0.5);
+        osc.set_pulse_width(0.5);
+        osc.set_waveshape(0.5);
+        osc.snap_parameters();
+
+        let mut buffer = [0.0; 400];
+        AudioSource::render(&mut osc, &mut buffer);
+
+        // Four periods of a half triangle, half square blend within the amplitude.
+        let peak = buffer.iter().fold(0.0_f32, |a, b| a.max(b.abs()));
+        assert!(peak > 0.3 && peak <= 0.5, "peak of {peak}");
+
+        // The blend is still periodic at the pitch.
+        for i in 100..300 {
+            assert!((buffer[i] - buffer[i + 100]).abs() < 0.01, "sample {i}");
+        }
+    }
+
+    #[test]
+    fn test_render_table_matches_direct_output() {
+        let mut osc = VariableShapeOscillator::new(48_000);
+        ToneSource::set_frequency(&mut osc, 480.0.into());
+        osc.set_pulse_width(0.3);
+        osc.set_waveshape(0.75);
+
+        // One 100 sample period of the 480Hz tone.
+        let mut table = [0.0; 100];
+        osc.render_table(&mut table);
+        let mut lookup =
+            LookupOscillator::new_from_table(&table).with_table_frequency(480.0.into());
+
+        // The direct output's first sample is skipped, it's band-limiting starts empty.
+        let _: f32 = osc.sample();
+        lookup.sample();
+        for i in 1..1000 {
+            let direct: f32 = osc.sample();
+            let played = lookup.sample();
+            assert!(
+                (direct - played).abs() < 0.01,
+                "sample {i}: {direct} vs {played}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_waveshape_change_is_smoothed() {
+        let instant = waveshape_step(0.0);
+        let smoothed = waveshape_step(0.05);
+        // Without smoothing the whole change lands in the first period.
+        assert!(instant[0] > 0.5, "instant change of {}", instant[0]);
+        assert!(instant[1..].iter().all(|distance| *distance < 0.1));
+
+        // With smoothing it's spread over several periods, and still settles.
+        let largest = smoothed.iter().fold(0.0_f32, |a, b| a.max(*b));
+        assert!(largest < instant[0] * 0.5, "largest change of {largest}");
+        assert!(smoothed.iter().sum::<f32>() > instant[0]);
+        assert!(smoothed[smoothed.len() - 1] < 0.01);
+    }
+}
@@ -0,0 +1,115 @@
+//! Sources that output a fixed level.
+//!
+//! Mostly useful as fixtures for testing mixers, gains and effects,
+//! but also as a DC offset or a silent placeholder in a chain.
+
+use super::Signal;
+use crate::audio::AudioSource;
+
+/// A source that outputs the same value for every sample, i.e. a DC offset.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConstantSource {
+    value: f32,
+}
+
+impl ConstantSource {
+    /// Constructs a new source that outputs `value`.
+    pub const fn new(value: f32) -> Self {
+        Self { value }
+    }
+
+    /// Returns the value the source outputs.
+    pub const fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Sets the value the source outputs.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value;
+    }
+}
+
+impl AudioSource for ConstantSource {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        buffer.fill(self.value);
+    }
+
+    fn render_add(&mut self, buffer: &'_ mut [Self::Frame]) {
+        for sample in buffer.iter_mut() {
+            *sample += self.value;
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        self.value == 0.0
+    }
+}
+
+impl Signal for ConstantSource {
+    type Frame = f32;
+
+    #[inline]
+    fn next(&mut self) -> Self::Frame {
+        self.value
+    }
+}
+
+/// A source that only outputs silence.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SilenceSource;
+
+impl AudioSource for SilenceSource {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        buffer.fill(0.0);
+    }
+
+    fn render_add(&mut self, _buffer: &'_ mut [Self::Frame]) {}
+
+    fn is_silent(&self) -> bool {
+        true
+    }
+}
+
+impl Signal for SilenceSource {
+    type Frame = f32;
+
+    #[inline]
+    fn next(&mut self) -> Self::Frame {
+        0.0
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_constant_fills_buffer() {
+        let mut buffer = [0.0; 100];
+        ConstantSource::new(0.5).render(&mut buffer);
+        self::assert_eq!(buffer, [0.5; 100]);
+        assert!(!ConstantSource::new(0.5).is_silent());
+        assert!(ConstantSource::new(0.0).is_silent());
+
+        ConstantSource::new(0.25).render_add(&mut buffer);
+        self::assert_eq!(buffer, [0.75; 100]);
+    }
+
+    #[test]
+    fn test_silence() {
+        let mut buffer = [1.0; 100];
+        SilenceSource.render_add(&mut buffer);
+        self::assert_eq!(buffer, [1.0; 100]);
+
+        SilenceSource.render(&mut buffer);
+        self::assert_eq!(buffer, [0.0; 100]);
+        assert!(SilenceSource.is_silent());
+    }
+}
@@ -32,11 +32,15 @@ use crate::audio::frame::Frame;
 use crate::audio::interpolate::Interpolator;
 use crate::audio::sample::{Duplex, Sample};
 use crate::core::ring_buffer;
+pub use constant::{ConstantSource, SilenceSource};
 use core;
 use core::cell::RefCell;
 use interpolate::Converter;
+pub use noise::{ColoredNoise, NoiseColor};
 
+pub mod constant;
 pub mod interpolate;
+pub mod noise;
 mod ops;
 
 #[cfg(feature = "alloc")]
@@ -0,0 +1,209 @@
+//! White, pink and brown noise generators.
+//!
+//! Useful both as an audio source (percussion, breath, wind) and as a
+//! random modulation source when routed through a mod matrix.
+
+use super::Signal;
+use crate::core::rng::Rng;
+
+/// The spectral shape of the noise produced by a [ColoredNoise] generator.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NoiseColor {
+    /// Equal power at every frequency.
+    White,
+    /// Power falls by 3dB per octave, equal power in every octave.
+    Pink,
+    /// Power falls by 6dB per octave, a random walk.
+    Brown,
+}
+
+/// Scales the summed pink filter bank back to roughly unity peak.
+const PINK_GAIN: f32 = 0.11;
+
+/// Leak factor of the brown noise integrator, keeps it from drifting off.
+const BROWN_LEAK: f32 = 0.02;
+
+/// Scales the leaky integrator back to roughly unity peak.
+const BROWN_GAIN: f32 = 3.5;
+
+/// The largest value below 1.0, noise is kept within -1.0..1.0.
+const MAX_OUTPUT: f32 = 1.0 - f32::EPSILON;
+
+/// A seedable noise generator of a chosen color yielding values in -1.0..1.0,
+/// unlike the white-only [`Noise`](super::Noise) signal from [`noise`](super::noise).
+///
+/// White noise is drawn from a seeded [Rng], so the same seed
+/// always produces the same sequence. Pink noise is shaped from it by
+/// Paul Kellet's refined filter bank, and brown noise by a leaky integrator.
+#[derive(Clone)]
+pub struct ColoredNoise {
+    /// The PRNG, advanced once per sample.
+    rng: Rng,
+    color: NoiseColor,
+
+    /// Pole states of the pink noise filter bank.
+    pink: [f32; 7],
+    /// State of the brown noise integrator.
+    brown: f32,
+}
+
+impl ColoredNoise {
+    /// Constructs a generator of the provided color.
+    ///
+    /// Generators with the same seed produce the same sequence,
+    /// use different seeds for noise sources that shouldn't match.
+    pub fn new(seed: u64, color: NoiseColor) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            color,
+            pink: [0.0; 7],
+            brown: 0.0,
+        }
+    }
+
+    /// Returns the color of the noise.
+    pub fn color(&self) -> NoiseColor {
+        self.color
+    }
+
+    /// Changes the color of the noise.
+    ///
+    /// The filter state is cleared so the new color
+    /// doesn't start from the old color's history.
+    pub fn set_color(&mut self, color: NoiseColor) {
+        self.color = color;
+        self.pink = [0.0; 7];
+        self.brown = 0.0;
+    }
+
+    /// Draws the next uniformly distributed value in -1.0..1.0.
+    #[inline]
+    fn next_white(&mut self) -> f32 {
+        self.rng.next_bipolar()
+    }
+
+    /// Generates the next sample, in -1.0..1.0.
+    #[inline]
+    pub fn next_sample(&mut self) -> f32 {
+        let white = self.next_white();
+
+        let out = match self.color {
+            NoiseColor::White => return white,
+            NoiseColor::Pink => {
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+
+                pink * PINK_GAIN
+            }
+            NoiseColor::Brown => {
+                self.brown = (self.brown + BROWN_LEAK * white) / (1.0 + BROWN_LEAK);
+                self.brown * BROWN_GAIN
+            }
+        };
+
+        // The shaped colors can briefly overshoot on long runs of same-signed values.
+        out.clamp(-1.0, MAX_OUTPUT)
+    }
+}
+
+impl Signal for ColoredNoise {
+    type Frame = f32;
+
+    #[inline]
+    fn next(&mut self) -> Self::Frame {
+        self.next_sample()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const BLOCK: usize = 1024;
+    const BLOCKS: usize = 32;
+
+    /// Average power per DFT bin over the bins `lo..hi`, across many blocks.
+    fn band_power(color: NoiseColor, lo: usize, hi: usize) -> f32 {
+        let mut noise = ColoredNoise::new(7, color);
+        let mut buffer = [0.0f32; BLOCK];
+        let mut total = 0.0;
+
+        for _ in 0..BLOCKS {
+            for sample in buffer.iter_mut() {
+                *sample = noise.next();
+            }
+
+            // Goertzel for every bin in the band.
+            for bin in lo..hi {
+                let w = 2.0 * core::f32::consts::PI * bin as f32 / BLOCK as f32;
+                let coeff = 2.0 * libm::cosf(w);
+                let (mut s1, mut s2) = (0.0f32, 0.0f32);
+                for &x in buffer.iter() {
+                    let s0 = x + coeff * s1 - s2;
+                    s2 = s1;
+                    s1 = s0;
+                }
+                total += s1 * s1 + s2 * s2 - coeff * s1 * s2;
+            }
+        }
+
+        total / ((hi - lo) * BLOCKS) as f32
+    }
+
+    /// The change in per-bin power from one octave to the next, in dB.
+    fn octave_slope(color: NoiseColor) -> f32 {
+        10.0 * libm::log10f(band_power(color, 64, 128) / band_power(color, 32, 64))
+    }
+
+    #[test]
+    fn test_output_in_range() {
+        for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+            let mut noise = ColoredNoise::new(0, color);
+            for _ in 0..200_000 {
+                let n = noise.next();
+                assert!((-1.0..1.0).contains(&n), "{color:?} produced {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = ColoredNoise::new(42, NoiseColor::Pink);
+        let mut b = ColoredNoise::new(42, NoiseColor::Pink);
+        for _ in 0..64 {
+            self::assert_eq!(a.next(), b.next());
+        }
+
+        let mut c = ColoredNoise::new(43, NoiseColor::Pink);
+        assert!((0..64).any(|_| a.next() != c.next()));
+    }
+
+    #[test]
+    fn test_white_is_flat() {
+        let slope = octave_slope(NoiseColor::White);
+        assert!(slope.abs() < 0.5, "white slope {slope}dB/octave");
+    }
+
+    #[test]
+    fn test_pink_rolls_off_3db_per_octave() {
+        let slope = octave_slope(NoiseColor::Pink);
+        assert!((slope + 3.0).abs() < 0.75, "pink slope {slope}dB/octave");
+    }
+
+    #[test]
+    fn test_brown_rolls_off_6db_per_octave() {
+        let slope = octave_slope(NoiseColor::Brown);
+        assert!((slope + 6.0).abs() < 0.75, "brown slope {slope}dB/octave");
+    }
+}
@@ -403,4 +403,44 @@ mod tests {
         let amp = [0.5];
         super::add_in_place_with_amp_per_channel(&mut a, &b, amp);
     }
+
+    #[test]
+    fn test_mono_frame_slice_round_trip() {
+        let samples = [0.0, 0.25, 0.5, 0.75];
+
+        let frames: &[[f32; 1]] = super::to_frame_slice(&samples[..]).unwrap();
+        assert_eq!(frames, &[[0.0], [0.25], [0.5], [0.75]]);
+
+        let back: &[f32] = super::to_sample_slice(frames);
+        assert_eq!(back, &samples);
+    }
+
+    #[test]
+    fn test_stereo_frame_slice_round_trip() {
+        let samples = [0.0, 0.5, 0.25, -0.5, 1.0, -1.0];
+
+        let frames: &[[f32; 2]] = super::to_frame_slice(&samples[..]).unwrap();
+        assert_eq!(frames, &[[0.0, 0.5], [0.25, -0.5], [1.0, -1.0]]);
+
+        let back: &[f32] = super::to_sample_slice(frames);
+        assert_eq!(back, &samples);
+    }
+
+    #[test]
+    fn test_stereo_frame_slice_mut() {
+        let mut samples = [0.0; 4];
+
+        let frames: &mut [[f32; 2]] = super::to_frame_slice_mut(&mut samples[..]).unwrap();
+        frames[1] = [0.5, -0.5];
+
+        assert_eq!(samples, [0.0, 0.0, 0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_frame_slice_uneven_channels() {
+        // Three samples can't be split into whole stereo frames.
+        let samples = [0.0, 0.5, 1.0];
+        let frames: Option<&[[f32; 2]]> = super::to_frame_slice(&samples[..]);
+        assert_eq!(frames, None);
+    }
 }
@@ -0,0 +1,58 @@
+//! Conversions between decibels and linear gain.
+//!
+//! Gain parameters are exposed to users in decibels but applied to
+//! samples as linear multipliers, these keep the two consistent.
+
+/// The level reported for silence, in decibels.
+///
+/// Silence is negative infinity in decibels, which is awkward to
+/// display or smooth, so levels are floored to this instead.
+pub const SILENCE_DB: f32 = -144.0;
+
+/// Converts a gain in decibels to a linear multiplier, e.g. -6 dB to ~0.5.
+pub fn db_to_linear(db: f32) -> f32 {
+    libm::powf(10.0, db / 20.0)
+}
+
+/// Converts a linear multiplier to a gain in decibels, e.g. 2.0 to ~6 dB.
+///
+/// Zero and negative gains are floored to [`SILENCE_DB`].
+pub fn linear_to_db(lin: f32) -> f32 {
+    if lin <= 0.0 {
+        return SILENCE_DB;
+    }
+
+    (20.0 * libm::log10f(lin)).max(SILENCE_DB)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_db_to_linear() {
+        self::assert_eq!(db_to_linear(0.0), 1.0);
+        assert!((db_to_linear(-6.0) - 0.501).abs() < 0.001);
+        assert!((db_to_linear(6.0) - 1.995).abs() < 0.001);
+        assert!((db_to_linear(-20.0) - 0.1).abs() < 0.000_1);
+    }
+
+    #[test]
+    fn test_linear_to_db() {
+        self::assert_eq!(linear_to_db(1.0), 0.0);
+        assert!((linear_to_db(0.5) + 6.021).abs() < 0.001);
+        self::assert_eq!(linear_to_db(0.0), SILENCE_DB);
+        self::assert_eq!(linear_to_db(-1.0), SILENCE_DB);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for db in [-96.0, -60.0, -12.5, -6.0, -0.1, 0.0, 3.0, 12.0, 24.0] {
+            let round_trip = linear_to_db(db_to_linear(db));
+            assert!((round_trip - db).abs() < 0.001, "{db} became {round_trip}");
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! A single error type that the errors from each module convert into.
+
+use core::fmt;
+
+use crate::{
+    audio::{
+        modulation::ModMatrixError,
+        oscillator::{ParseError, TableError},
+    },
+    core::param::ParamError,
+    instrument::{NoteError, events::EventQueueError, midi::MidiError},
+    music::{chord::ChordError, note::NoteParseError},
+    sequence::PatternError,
+};
+
+/// An error from anywhere in the engine.
+///
+/// Each module keeps it's own error type for granular handling,
+/// this wraps them so functions that span several modules can
+/// return `Result<_, EngineError>` and propagate with `?`.
+#[derive(Debug)]
+pub enum EngineError {
+    /// A note couldn't be played on an instrument.
+    Note(NoteError),
+    /// A pattern, track or song edit was out of range or out of room.
+    Pattern(PatternError),
+    /// A lookup table for an oscillator couldn't be built.
+    Table(TableError),
+    /// A route couldn't be added to a modulation matrix.
+    ModMatrix(ModMatrixError),
+    /// Oscillator settings couldn't be parsed from a string.
+    Parse(ParseError),
+    /// A parameter couldn't be set.
+    Param(ParamError),
+    /// A note name couldn't be parsed.
+    NoteParse(NoteParseError),
+    /// An event couldn't be queued for an instrument.
+    EventQueue(EventQueueError),
+    /// A chord couldn't be parsed or a progression built.
+    Chord(ChordError),
+    /// A MIDI message couldn't be decoded or a controller mapped.
+    Midi(MidiError),
+}
+
+/// Formats the wrapped error with it's `Debug` output,
+/// e.g. "NoVoices" for [`NoteError::NoVoices`].
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Note(error) => write!(f, "{error:?}"),
+            EngineError::Pattern(error) => write!(f, "{error:?}"),
+            EngineError::Table(error) => write!(f, "{error:?}"),
+            EngineError::ModMatrix(error) => write!(f, "{error:?}"),
+            EngineError::Parse(error) => write!(f, "{error:?}"),
+            EngineError::Param(error) => write!(f, "{error:?}"),
+            EngineError::NoteParse(error) => write!(f, "{error:?}"),
+            EngineError::EventQueue(error) => write!(f, "{error:?}"),
+            EngineError::Chord(error) => write!(f, "{error:?}"),
+            EngineError::Midi(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<NoteError> for EngineError {
+    fn from(error: NoteError) -> Self {
+        EngineError::Note(error)
+    }
+}
+
+impl From<PatternError> for EngineError {
+    fn from(error: PatternError) -> Self {
+        EngineError::Pattern(error)
+    }
+}
+
+impl From<TableError> for EngineError {
+    fn from(error: TableError) -> Self {
+        EngineError::Table(error)
+    }
+}
+
+impl From<ModMatrixError> for EngineError {
+    fn from(error: ModMatrixError) -> Self {
+        EngineError::ModMatrix(error)
+    }
+}
+
+impl From<ParseError> for EngineError {
+    fn from(error: ParseError) -> Self {
+        EngineError::Parse(error)
+    }
+}
+
+impl From<ParamError> for EngineError {
+    fn from(error: ParamError) -> Self {
+        EngineError::Param(error)
+    }
+}
+
+impl From<NoteParseError> for EngineError {
+    fn from(error: NoteParseError) -> Self {
+        EngineError::NoteParse(error)
+    }
+}
+
+impl From<EventQueueError> for EngineError {
+    fn from(error: EventQueueError) -> Self {
+        EngineError::EventQueue(error)
+    }
+}
+
+impl From<ChordError> for EngineError {
+    fn from(error: ChordError) -> Self {
+        EngineError::Chord(error)
+    }
+}
+
+impl From<MidiError> for EngineError {
+    fn from(error: MidiError) -> Self {
+        EngineError::Midi(error)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_table() -> Result<(), TableError> {
+        Err(TableError::IncorrectSize {
+            expected: 48_000,
+            actual: 16,
+        })
+    }
+
+    fn setup() -> Result<(), EngineError> {
+        build_table()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_propagates_table_error() {
+        assert!(matches!(
+            setup(),
+            Err(EngineError::Table(TableError::IncorrectSize {
+                expected: 48_000,
+                actual: 16
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_display_shows_wrapped_error() {
+        let error = EngineError::from(NoteError::NoVoices);
+        assert_eq!(std::format!("{error}"), "NoVoices");
+    }
+}
@@ -0,0 +1,150 @@
+//! Fixed-point frequencies for doing oscillator phase math
+//! in integer arithmetic on MCUs without an FPU.
+
+use core::ops::{Div, Mul};
+
+use crate::{core::Hertz, prelude::*};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The number of fractional bits in a [`FixedHertz`].
+pub const FRACTIONAL_BITS: u32 = 16;
+
+/// Frequency in hertz as an unsigned Q16.16 fixed-point number.
+///
+/// Covers 0 to just under 65536Hz with a resolution of about 0.000015Hz,
+/// which is finer than the 0.0001 [`Hertz`] considers equal.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct FixedHertz(pub u32);
+
+impl FixedHertz {
+    /// Builds a frequency from a whole number of hertz.
+    pub const fn from_int(hertz: u16) -> Self {
+        Self((hertz as u32) << FRACTIONAL_BITS)
+    }
+
+    /// Builds a frequency from the raw Q16.16 bits.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw Q16.16 bits of the frequency.
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the whole number of hertz, truncating the fraction.
+    pub const fn to_int(self) -> u16 {
+        (self.0 >> FRACTIONAL_BITS) as u16
+    }
+
+    /// Returns how far a 32-bit phase accumulator advances each sample, where
+    /// a full cycle of the waveform is the full range of the accumulator.
+    pub const fn phase_increment(self, sample_rate: u32) -> u32 {
+        // Shifting the Q16.16 frequency up by 16 bits scales a cycle to 2^32.
+        (((self.0 as u64) << (32 - FRACTIONAL_BITS)) / sample_rate as u64) as u32
+    }
+}
+
+/// Rounds the frequency to the nearest fixed-point value,
+/// saturating outside of the representable range.
+impl From<Hertz> for FixedHertz {
+    fn from(value: Hertz) -> Self {
+        let scaled = libm::roundf(value.hertz() * (1 << FRACTIONAL_BITS) as f32);
+        // Float to int casts saturate, and NaN becomes zero.
+        Self(scaled as u32)
+    }
+}
+
+impl From<FixedHertz> for Hertz {
+    fn from(value: FixedHertz) -> Self {
+        Hertz(value.0 as f32 / (1 << FRACTIONAL_BITS) as f32)
+    }
+}
+
+impl Add<FixedHertz> for FixedHertz {
+    type Output = FixedHertz;
+
+    fn add(self, rhs: FixedHertz) -> Self::Output {
+        FixedHertz(self.0 + rhs.0)
+    }
+}
+
+impl Sub<FixedHertz> for FixedHertz {
+    type Output = FixedHertz;
+
+    fn sub(self, rhs: FixedHertz) -> Self::Output {
+        FixedHertz(self.0 - rhs.0)
+    }
+}
+
+/// Multiplies two fixed-point values, i.e. a frequency by a ratio.
+impl Mul<FixedHertz> for FixedHertz {
+    type Output = FixedHertz;
+
+    fn mul(self, rhs: FixedHertz) -> Self::Output {
+        FixedHertz(((self.0 as u64 * rhs.0 as u64) >> FRACTIONAL_BITS) as u32)
+    }
+}
+
+/// Allows for direct multiplication with whole numbers, i.e. for harmonics.
+impl Mul<u32> for FixedHertz {
+    type Output = FixedHertz;
+
+    fn mul(self, rhs: u32) -> Self::Output {
+        FixedHertz(self.0 * rhs)
+    }
+}
+
+/// Allows for direct division by whole numbers, i.e. for sub-octaves.
+impl Div<u32> for FixedHertz {
+    type Output = FixedHertz;
+
+    fn div(self, rhs: u32) -> Self::Output {
+        FixedHertz(self.0 / rhs)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_hertz_round_trip() {
+        self::assert_eq!(FixedHertz::from(Hertz(440.0)), FixedHertz::from_int(440));
+
+        for hertz in [16.351, 27.5, 261.6256, 440.0, 4186.009, 20_000.0] {
+            let round_trip = Hertz::from(FixedHertz::from(Hertz(hertz)));
+            assert!(
+                (round_trip.hertz() - hertz).abs() < 0.002,
+                "{hertz} became {round_trip:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a4 = FixedHertz::from_int(440);
+
+        self::assert_eq!(a4 + FixedHertz::from_int(2), FixedHertz::from_int(442));
+        self::assert_eq!(a4 - FixedHertz::from_int(220), FixedHertz::from_int(220));
+        self::assert_eq!(a4 * 2, FixedHertz::from_int(880));
+        self::assert_eq!(a4 / 4, FixedHertz::from_int(110));
+        self::assert_eq!(a4 * FixedHertz::from(Hertz(1.5)), FixedHertz::from_int(660));
+    }
+
+    #[test]
+    fn test_phase_increment() {
+        // A quarter of the sample rate is a quarter of a cycle per sample.
+        self::assert_eq!(
+            FixedHertz::from_int(12_000).phase_increment(48_000),
+            1 << 30
+        );
+    }
+}
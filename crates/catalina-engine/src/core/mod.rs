@@ -2,6 +2,7 @@
 
 use crate::prelude::*;
 
+use core::fmt;
 use core::ops::Mul;
 
 use float_eq::float_eq;
@@ -9,7 +10,15 @@ use float_eq::float_eq;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod error;
+pub use error::EngineError;
+
+pub mod db;
+#[cfg(feature = "fixed-point")]
+pub mod fixed;
+pub mod param;
 pub mod ring_buffer;
+pub mod rng;
 
 /// Frequency in hertz, wraps an f32 with sufficiant 0.0001 precision for musical use.
 ///
@@ -19,9 +28,13 @@ pub mod ring_buffer;
 /// a crime.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-#[derive(Debug, Copy, Clone)]
+#[derive(Copy, Clone)]
 pub struct Hertz(pub f32);
 
+/// The highest frequency [`Hertz::clamp_to_nyquist`] allows, as a fraction of
+/// the sample rate, leaving a little headroom under Nyquist for pitch drift.
+const MAX_NYQUIST_RATIO: f32 = 0.49;
+
 impl Hertz {
     /// Builds a frequency from hertz.
     pub fn from_hertz(value: f32) -> Self {
@@ -32,6 +45,32 @@ impl Hertz {
     pub fn hertz(&self) -> f32 {
         self.0
     }
+
+    /// Clamps the frequency to the range that can be reproduced at the sample rate,
+    /// from 0Hz up to just under Nyquist (49% of the sample rate).
+    ///
+    /// Negative and NaN frequencies become 0Hz. Frequencies past Nyquist
+    /// would otherwise fold back down as lower, aliased frequencies.
+    pub fn clamp_to_nyquist(self, sample_rate: usize) -> Self {
+        let highest = sample_rate as f32 * MAX_NYQUIST_RATIO;
+        if self.0 > 0.0 {
+            Self(self.0.min(highest))
+        } else {
+            Self(0.0)
+        }
+    }
+
+    /// Builds a frequency from a fractional MIDI note number, where 69.0 is A4 (440Hz).
+    pub fn from_midi_float(note: f32) -> Self {
+        Self(440.0 * libm::exp2f((note - 69.0) / 12.0))
+    }
+
+    /// Returns the fractional MIDI note number of the frequency, where 69.0 is A4 (440Hz).
+    ///
+    /// The fraction is the distance to the next note, i.e. 0.5 is 50 cents sharp.
+    pub fn to_midi_float(&self) -> f32 {
+        69.0 + 12.0 * libm::log2f(self.0 / 440.0)
+    }
 }
 
 impl From<f32> for Hertz {
@@ -107,6 +146,24 @@ impl Sub<f32> for Hertz {
     }
 }
 
+/// Formats the frequency with its unit, e.g. "440.00 Hz".
+///
+/// Defaults to two decimal places, a precision
+/// can be given instead, e.g. `{:.0}` for "440 Hz".
+impl fmt::Display for Hertz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(2);
+        write!(f, "{:.*} Hz", precision, self.0)
+    }
+}
+
+/// Formats the frequency at full precision with its unit, e.g. "440.0 Hz".
+impl fmt::Debug for Hertz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} Hz", self.0)
+    }
+}
+
 /// Allows us to properly use frequencies as keys in hashmaps.
 impl Hash for Hertz {
     fn hash<H: Hasher>(&self, hasher: &mut H) {
@@ -123,3 +180,58 @@ impl Hash for Hertz {
         bits.hash(hasher);
     }
 }
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+    use pretty_assertions::assert_eq;
+
+    fn format(args: fmt::Arguments) -> heapless::String<32> {
+        let mut buf = heapless::String::new();
+        buf.write_fmt(args).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_display() {
+        self::assert_eq!(format(format_args!("{}", Hertz(440.0))), "440.00 Hz");
+        self::assert_eq!(format(format_args!("{}", Hertz(261.6256))), "261.63 Hz");
+        self::assert_eq!(format(format_args!("{:.0}", Hertz(27.5))), "28 Hz");
+        self::assert_eq!(format(format_args!("{:.3}", Hertz(16.351))), "16.351 Hz");
+    }
+
+    #[test]
+    fn test_debug() {
+        self::assert_eq!(format(format_args!("{:?}", Hertz(440.0))), "440.0 Hz");
+    }
+
+    #[test]
+    fn test_clamp_to_nyquist() {
+        self::assert_eq!(Hertz(440.0).clamp_to_nyquist(48_000), Hertz(440.0));
+        self::assert_eq!(Hertz(30_000.0).clamp_to_nyquist(48_000), Hertz(23_520.0));
+        self::assert_eq!(Hertz(-1.0).clamp_to_nyquist(48_000), Hertz(0.0));
+    }
+
+    #[test]
+    fn test_to_midi_float() {
+        self::assert_eq!(Hertz(440.0).to_midi_float(), 69.0);
+        assert!((Hertz(466.16).to_midi_float() - 70.0).abs() < 0.001);
+        assert!((Hertz(220.0).to_midi_float() - 57.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_midi_float_round_trip() {
+        self::assert_eq!(Hertz::from_midi_float(69.0), Hertz(440.0));
+
+        for note in [0.0, 21.5, 60.0, 69.25, 100.0, 127.0] {
+            let round_trip = Hertz::from_midi_float(note).to_midi_float();
+            assert!(
+                (round_trip - note).abs() < 0.001,
+                "{note} became {round_trip}"
+            );
+        }
+    }
+}
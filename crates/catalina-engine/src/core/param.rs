@@ -0,0 +1,228 @@
+//! Types for working with parameters that change while audio is rendering.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::audio::signal::Signal;
+
+/// Identifies a parameter that can be automated or modulated.
+///
+/// The meaning of each ID is up to the instrument or
+/// effect that owns the parameters.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ParamId(pub u16);
+
+/// The unit a parameter's value is expressed in, for display by hosts.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ParamUnit {
+    /// A plain value without a unit, i.e. a 0.0 to 1.0 level.
+    None,
+    Seconds,
+    Hertz,
+    Semitones,
+    /// Hundredths of a semitone.
+    Cents,
+    Decibels,
+}
+
+/// Describes a parameter of an instrument or effect to hosts.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParamDescriptor {
+    pub id: ParamId,
+    /// A human readable name for the parameter.
+    pub name: &'static str,
+    /// The lowest value the parameter accepts.
+    pub min: f32,
+    /// The highest value the parameter accepts.
+    pub max: f32,
+    /// The value the parameter starts at.
+    pub default: f32,
+    pub unit: ParamUnit,
+}
+
+impl ParamDescriptor {
+    /// Clamps a value to the range of the parameter.
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// An error returned from setting a parameter.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// The parameter doesn't exist on the instrument or effect.
+    UnknownParam(ParamId),
+}
+
+/// The number of time constants a one-pole smoother needs
+/// to get within 1% of it's target (`ln(100)`).
+const SETTLE_TIME_CONSTANTS: f32 = 4.605_17;
+
+/// Calculates the coefficient for a one-pole filter that moves
+/// `1 - 1/e` (~63%) of the way to it's target in the provided time.
+///
+/// A time of zero or less returns a coefficient of 1.0, an instant change.
+pub fn one_pole_coefficient(seconds: f32, sample_rate: usize) -> f32 {
+    if seconds > 0.0 {
+        let target: f32 = libm::logf(1. / core::f32::consts::E);
+        1.0 - libm::expf(target / (seconds * sample_rate as f32))
+    } else {
+        1.0 // instant change
+    }
+}
+
+/// A parameter value that glides towards it's target with
+/// a one-pole smoother instead of jumping to it instantly.
+///
+/// Changing raw parameters like gain or cutoff in the middle of
+/// a render causes audible zipper noise, smoothing the changes
+/// across several samples removes those discontinuities.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Smoothed {
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+
+    /// The time in seconds it takes to get within 1% of the target.
+    time: f32,
+    /// Cached one-pole coefficient derived from the smoothing time.
+    coefficient: f32,
+
+    /// The value at the current sample.
+    current: f32,
+    /// The value the parameter is gliding towards.
+    target: f32,
+}
+
+impl Smoothed {
+    /// Constructs a new smoothed parameter starting at the provided value.
+    ///
+    /// The time is how long in seconds a change takes to get within 1% of the target.
+    pub fn new(sample_rate: usize, time: f32, value: f32) -> Self {
+        Self {
+            sample_rate,
+            time,
+            coefficient: one_pole_coefficient(time / SETTLE_TIME_CONSTANTS, sample_rate),
+            current: value,
+            target: value,
+        }
+    }
+
+    /// Sets the time in seconds it takes to get within 1% of the target.
+    pub fn set_time(&mut self, seconds: f32) {
+        self.time = seconds;
+        self.coefficient = one_pole_coefficient(seconds / SETTLE_TIME_CONSTANTS, self.sample_rate);
+    }
+
+    /// Changes the sample rate, keeping the same smoothing time.
+    pub fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.sample_rate = sample_rate;
+        self.set_time(self.time);
+    }
+
+    /// Returns the smoothing time in seconds.
+    #[inline]
+    pub const fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets the value the parameter should glide towards.
+    #[inline]
+    pub fn set(&mut self, target: f32) {
+        self.target = target;
+    }
+
+    /// Jumps the parameter straight to the provided value without smoothing.
+    #[inline]
+    pub fn set_immediate(&mut self, value: f32) {
+        self.target = value;
+        self.current = value;
+    }
+
+    /// Returns the value the parameter is gliding towards.
+    #[inline]
+    pub const fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Returns the value of the parameter at the current sample.
+    #[inline]
+    pub const fn value(&self) -> f32 {
+        self.current
+    }
+
+    /// Returns true if the parameter hasn't reached it's target yet.
+    #[inline]
+    pub fn is_smoothing(&self) -> bool {
+        self.current != self.target
+    }
+}
+
+/// Allows using the smoothed parameter as a control signal.
+impl Signal for Smoothed {
+    type Frame = f32;
+
+    /// Advances the smoother by a sample and returns the new value.
+    fn next(&mut self) -> Self::Frame {
+        let next = self.current + self.coefficient * (self.target - self.current);
+
+        // Snap to the target once the remaining steps get too small to
+        // represent, otherwise we'd stall just short of the target forever.
+        self.current = if next == self.current || (self.target - next).abs() < 1e-6 {
+            self.target
+        } else {
+            next
+        };
+
+        self.current
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_coefficient() {
+        assert_eq!(one_pole_coefficient(0.0, 48_000), 1.0);
+    }
+
+    #[test]
+    fn test_converges_over_time() {
+        let mut param = Smoothed::new(48_000, 0.01, 0.0);
+        param.set(1.0);
+
+        // Halfway through the smoothing time it should still be moving.
+        for _ in 0..240 {
+            param.next();
+        }
+        assert!(param.value() > 0.5 && param.value() < 0.99);
+
+        // After the full smoothing time it should be within 1% of the target.
+        for _ in 240..480 {
+            param.next();
+        }
+        assert!((param.value() - 1.0).abs() <= 0.01);
+
+        // And eventually it settles exactly on the target.
+        for _ in 0..48_000 {
+            param.next();
+        }
+        assert_eq!(param.value(), 1.0);
+        assert!(!param.is_smoothing());
+    }
+
+    #[test]
+    fn test_set_immediate() {
+        let mut param = Smoothed::new(48_000, 0.01, 0.0);
+        param.set_immediate(0.5);
+        assert_eq!(param.next(), 0.5);
+    }
+}
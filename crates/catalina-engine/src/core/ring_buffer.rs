@@ -714,6 +714,73 @@ where
         None
     }
 
+    /// Pushes all of the given elements to the back of the buffer, overwriting the
+    /// oldest elements at the front of the buffer once it's full.
+    ///
+    /// Unlike calling `push` for each element this copies the elements in at most
+    /// two blocks, and only the newest `max_len` elements are copied at all.
+    ///
+    /// ```
+    /// use catalina_engine::core::ring_buffer::Bounded;
+    ///
+    /// let mut ring_buffer = Bounded::from([0i32; 4]);
+    /// ring_buffer.write_overwrite(&[1, 2, 3]);
+    /// assert_eq!(ring_buffer.slices(), (&[1, 2, 3][..], &[][..]));
+    /// ring_buffer.write_overwrite(&[4, 5, 6]);
+    /// assert_eq!(ring_buffer.slices(), (&[3, 4][..], &[5, 6][..]));
+    /// ```
+    pub fn write_overwrite(&mut self, elems: &[S::Element])
+    where
+        S: SliceMut,
+    {
+        let max_len = self.max_len();
+
+        // Anything older than the newest `max_len` elements would be overwritten anyway.
+        let elems = &elems[elems.len().saturating_sub(max_len)..];
+
+        let mut write = (self.start + self.len) % max_len;
+        let mut remaining = elems;
+        while !remaining.is_empty() {
+            // Copy up to the end of the data, then wrap around to the start.
+            let count = remaining.len().min(max_len - write);
+            self.data.slice_mut()[write..write + count].copy_from_slice(&remaining[..count]);
+            remaining = &remaining[count..];
+            write = (write + count) % max_len;
+        }
+
+        // Move the front past any elements that were overwritten.
+        let len = self.len + elems.len();
+        if len > max_len {
+            self.start = (self.start + len - max_len) % max_len;
+            self.len = max_len;
+        } else {
+            self.len = len;
+        }
+    }
+
+    /// Returns the most recently pushed `n` elements, or all of them if there are fewer
+    /// than `n`, as two slices ordered from oldest to newest like `slices`.
+    ///
+    /// ```
+    /// use catalina_engine::core::ring_buffer::Bounded;
+    ///
+    /// let mut ring_buffer = Bounded::from([0i32; 4]);
+    /// ring_buffer.write_overwrite(&[1, 2, 3]);
+    /// ring_buffer.write_overwrite(&[4, 5, 6]);
+    /// assert_eq!(ring_buffer.peek_last(3), (&[4][..], &[5, 6][..]));
+    /// assert_eq!(ring_buffer.peek_last(1), (&[6][..], &[][..]));
+    /// ```
+    pub fn peek_last(&self, n: usize) -> (&[S::Element], &[S::Element]) {
+        let (first, second) = self.slices();
+        let skip = self.len - n.min(self.len);
+
+        if skip >= first.len() {
+            (&second[skip - first.len()..], &second[..0])
+        } else {
+            (&first[skip..], second)
+        }
+    }
+
     /// Pop an element from the front of the ring buffer.
     ///
     /// If the buffer is empty, this returns `None`.
@@ -945,6 +1012,48 @@ mod tests {
         assert_eq!(rb.push(7), Some(4));
     }
 
+    #[test]
+    fn test_bounded_write_overwrite_wraps() {
+        let mut rb = ring_buffer::Bounded::from([0i32; 4]);
+        rb.write_overwrite(&[1, 2, 3]);
+        assert_eq!(rb.len(), 3);
+
+        // Wraps around the end of the data, dropping the oldest.
+        rb.write_overwrite(&[4, 5, 6]);
+        assert_eq!(rb.len(), 4);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+        assert_eq!(rb.pop(), Some(3));
+
+        // More elements than fit keeps just the newest.
+        rb.write_overwrite(&[7, 8, 9, 10, 11, 12]);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![9, 10, 11, 12]);
+
+        // Matches pushing the elements one at a time.
+        let mut pushed = ring_buffer::Bounded::from([0i32; 4]);
+        for elem in [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12] {
+            pushed.push(elem);
+        }
+        assert_eq!(
+            pushed.iter().collect::<Vec<_>>(),
+            rb.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_bounded_peek_last() {
+        let mut rb = ring_buffer::Bounded::from([0i32; 5]);
+        assert_eq!(rb.peek_last(3), (&[][..], &[][..]));
+
+        rb.write_overwrite(&[1, 2]);
+        assert_eq!(rb.peek_last(3), (&[1, 2][..], &[][..]));
+
+        rb.write_overwrite(&[3, 4, 5, 6, 7]);
+        let (older, newer) = rb.peek_last(4);
+        assert_eq!([older, newer].concat(), vec![4, 5, 6, 7]);
+        assert_eq!([rb.peek_last(2).0, rb.peek_last(2).1].concat(), vec![6, 7]);
+        assert_eq!(rb.peek_last(0), (&[][..], &[][..]));
+    }
+
     #[test]
     #[should_panic]
     fn test_bounded_get_out_of_range() {
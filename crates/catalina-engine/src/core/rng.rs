@@ -0,0 +1,105 @@
+//! A small seedable PRNG shared by everything that needs randomness,
+//! such as noise, drift and humanizing, without pulling in `rand`.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A SplitMix64 pseudo-random number generator.
+///
+/// Fast, statistically solid for audio use and a single `u64` of
+/// state, so it's cheap to keep one per voice. The same seed always
+/// produces the same sequence, keeping renders reproducible.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Constructs a new generator from a seed.
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Restarts the sequence from a new seed.
+    pub fn reseed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    /// Returns the next 64 random bits.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns the next 32 random bits.
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a uniformly distributed value in 0.0..1.0.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fit exactly in the f32 mantissa.
+        (self.next_u64() >> 40) as f32 / (1 << 24) as f32
+    }
+
+    /// Returns a uniformly distributed value in -1.0..1.0.
+    #[inline]
+    pub fn next_bipolar(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1 << 23) as f32 - 1.0
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            self::assert_eq!(a.next_u32(), b.next_u32());
+        }
+
+        let mut c = Rng::new(43);
+        assert!((0..100).any(|_| a.next_u32() != c.next_u32()));
+
+        // Reseeding starts the sequence over.
+        let first = Rng::new(7).next_u64();
+        a.reseed(7);
+        self::assert_eq!(a.next_u64(), first);
+    }
+
+    #[test]
+    fn test_float_ranges() {
+        let mut rng = Rng::new(1);
+        let (mut low, mut high) = (f32::MAX, f32::MIN);
+        for _ in 0..10_000 {
+            let value = rng.next_f32();
+            assert!((0.0..1.0).contains(&value), "{value} out of range");
+            low = low.min(value);
+            high = high.max(value);
+        }
+        assert!(low < 0.01 && high > 0.99);
+
+        let (mut low, mut high) = (f32::MAX, f32::MIN);
+        for _ in 0..10_000 {
+            let value = rng.next_bipolar();
+            assert!((-1.0..1.0).contains(&value), "{value} out of range");
+            low = low.min(value);
+            high = high.max(value);
+        }
+        assert!(low < -0.99 && high > 0.99);
+    }
+}
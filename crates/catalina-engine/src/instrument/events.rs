@@ -0,0 +1,291 @@
+//! A queue of timestamped events for playing instruments sample-accurately.
+//!
+//! Events are queued at a sample offset from the start of the next
+//! rendered block, and [`EventQueue::render`] splits the render at
+//! each event so it lands on exactly the right sample.
+
+use heapless::Vec;
+
+use crate::{
+    audio::AudioSource,
+    core::{EngineError, param::ParamId},
+    instrument::Instrument,
+    music::note::Note,
+};
+
+/// An error returned when queuing an event on an [`EventQueue`].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub enum EventQueueError {
+    QueueFull,
+}
+
+/// Something to do to an instrument at a point in time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InstrumentEvent {
+    NoteOn { note: Note, velocity: u8 },
+    NoteOff { note: Note },
+    SetParam { id: ParamId, value: f32 },
+}
+
+impl InstrumentEvent {
+    /// Applies the event to the instrument.
+    pub fn apply<I: Instrument>(self, instrument: &mut I) -> Result<(), EngineError> {
+        match self {
+            InstrumentEvent::NoteOn { note, velocity } => instrument.note_on(note, velocity)?,
+            InstrumentEvent::NoteOff { note } => instrument.note_off(note),
+            InstrumentEvent::SetParam { id, value } => instrument.set_param(id, value)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// An event waiting in an [`EventQueue`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QueuedEvent {
+    /// The sample offset from the start of the next rendered block.
+    pub offset: usize,
+    pub event: InstrumentEvent,
+}
+
+/// A bounded queue of up to `N` events, kept sorted by their sample offset.
+///
+/// Offsets count from the start of the next block rendered with
+/// [`EventQueue::render`], and count down as blocks are rendered,
+/// so events can be queued ahead of time across several blocks.
+#[derive(Debug, Clone)]
+pub struct EventQueue<const N: usize> {
+    events: Vec<QueuedEvent, N>,
+}
+
+impl<const N: usize> EventQueue<N> {
+    /// Constructs an empty queue.
+    pub const fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Queues an event at a sample offset from the start of the next block.
+    ///
+    /// Events at the same offset are applied in the order they were queued.
+    pub fn push(&mut self, offset: usize, event: InstrumentEvent) -> Result<(), EventQueueError> {
+        let index = self
+            .events
+            .partition_point(|queued| queued.offset <= offset);
+        self.events
+            .insert(index, QueuedEvent { offset, event })
+            .map_err(|_| EventQueueError::QueueFull)
+    }
+
+    /// Takes the next event if it's due at or before the offset.
+    pub fn pop_due(&mut self, offset: usize) -> Option<InstrumentEvent> {
+        if self.events.first()?.offset > offset {
+            return None;
+        }
+
+        Some(self.events.remove(0).event)
+    }
+
+    /// Returns the offset of the next event, if any.
+    pub fn next_offset(&self) -> Option<usize> {
+        self.events.first().map(|queued| queued.offset)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drops all of the queued events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Moves the queue forward by a number of samples, i.e. after
+    /// rendering a block without [`EventQueue::render`].
+    ///
+    /// Events that were due in the skipped samples become due immediately.
+    pub fn advance(&mut self, samples: usize) {
+        for queued in self.events.iter_mut() {
+            queued.offset = queued.offset.saturating_sub(samples);
+        }
+    }
+
+    /// Renders a block from the instrument, applying each
+    /// event that falls within the block at it's sample.
+    ///
+    /// Events past the end of the block are kept for the next block.
+    /// If the instrument rejects an event, i.e. with no free voices, the
+    /// rest of the events are still applied and the whole block is still
+    /// rendered, then the first error is returned.
+    pub fn render<I: Instrument>(
+        &mut self,
+        instrument: &mut I,
+        buffer: &mut [<I as AudioSource>::Frame],
+    ) -> Result<(), EngineError> {
+        let mut rendered = 0;
+        let mut result = Ok(());
+
+        while let Some(offset) = self.next_offset().filter(|offset| *offset < buffer.len()) {
+            // Render up to the event, then apply it.
+            if offset > rendered {
+                AudioSource::render(instrument, &mut buffer[rendered..offset]);
+                rendered = offset;
+            }
+
+            if let Some(event) = self.pop_due(offset)
+                && let Err(error) = event.apply(instrument)
+                && result.is_ok()
+            {
+                result = Err(error);
+            }
+        }
+
+        AudioSource::render(instrument, &mut buffer[rendered..]);
+        self.advance(buffer.len());
+
+        result
+    }
+}
+
+impl<const N: usize> Default for EventQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::signal::ConstantSource,
+        instrument::{GatedSource, NoteError, test_support::RecordingInstrument},
+        music::note::{AFour, CFour, EFour},
+    };
+    use pretty_assertions::assert_eq;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn instrument() -> GatedSource<ConstantSource> {
+        let mut instrument = GatedSource::new(ConstantSource::new(1.0), SAMPLE_RATE);
+        instrument.envelope_mut().set_attack_time(0.0, 0.0);
+        instrument.envelope_mut().set_sustain_level(1.0);
+        instrument
+    }
+
+    #[test]
+    fn test_note_on_lands_on_its_sample() {
+        let mut instrument = instrument();
+        let mut queue = EventQueue::<4>::new();
+        queue
+            .push(
+                100,
+                InstrumentEvent::NoteOn {
+                    note: AFour,
+                    velocity: 127,
+                },
+            )
+            .unwrap();
+
+        let mut buffer = [0.0; 256];
+        queue.render(&mut instrument, &mut buffer).unwrap();
+
+        assert!(buffer[..100].iter().all(|sample| *sample == 0.0));
+        assert!(buffer[100] > 0.0, "voice silent at sample 100");
+        assert!(instrument.is_note_on(AFour));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_events_carry_over_blocks() {
+        let mut instrument = instrument();
+        let mut queue = EventQueue::<4>::new();
+        queue
+            .push(300, InstrumentEvent::NoteOff { note: AFour })
+            .unwrap();
+        queue
+            .push(
+                10,
+                InstrumentEvent::NoteOn {
+                    note: AFour,
+                    velocity: 127,
+                },
+            )
+            .unwrap();
+        self::assert_eq!(queue.next_offset(), Some(10));
+
+        let mut buffer = [0.0; 256];
+        queue.render(&mut instrument, &mut buffer).unwrap();
+        assert!(instrument.is_note_on(AFour));
+
+        // The note off is now 44 samples into the next block.
+        self::assert_eq!(queue.next_offset(), Some(44));
+        queue.render(&mut instrument, &mut buffer).unwrap();
+        assert!(!instrument.is_note_on(AFour));
+    }
+
+    #[test]
+    fn test_same_offset_keeps_queued_order() {
+        let mut queue = EventQueue::<4>::new();
+        let on = InstrumentEvent::NoteOn {
+            note: CFour,
+            velocity: 100,
+        };
+        let off = InstrumentEvent::NoteOff { note: CFour };
+        queue.push(5, off).unwrap();
+        queue.push(5, on).unwrap();
+
+        self::assert_eq!(queue.pop_due(4), None);
+        self::assert_eq!(queue.pop_due(5), Some(off));
+        self::assert_eq!(queue.pop_due(5), Some(on));
+    }
+
+    #[test]
+    fn test_rejected_event_still_renders_block() {
+        let mut instrument = RecordingInstrument::with_voices(2);
+        let mut queue = EventQueue::<4>::new();
+        for (offset, note) in [(10, AFour), (20, CFour), (30, EFour)] {
+            queue
+                .push(
+                    offset,
+                    InstrumentEvent::NoteOn {
+                        note,
+                        velocity: 127,
+                    },
+                )
+                .unwrap();
+        }
+        queue
+            .push(300, InstrumentEvent::NoteOff { note: AFour })
+            .unwrap();
+
+        let mut buffer = [9.0; 256];
+        let result = queue.render(&mut instrument, &mut buffer);
+        assert!(matches!(
+            result,
+            Err(EngineError::Note(NoteError::NoVoices))
+        ));
+
+        // The whole block was rendered over the stale contents.
+        assert!(buffer.iter().all(|sample| *sample == 0.0));
+
+        // And the queue still moved on by the block.
+        self::assert_eq!(queue.next_offset(), Some(44));
+    }
+
+    #[test]
+    fn test_full_queue() {
+        let mut queue = EventQueue::<1>::new();
+        let off = InstrumentEvent::NoteOff { note: CFour };
+        queue.push(0, off).unwrap();
+        assert!(matches!(
+            queue.push(1, off),
+            Err(EventQueueError::QueueFull)
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+//! Plays a bare audio source as a monophonic instrument.
+
+use crate::{
+    audio::{AudioSource, envelope::adsr::Envelope, oscillator::ToneSource, signal::Signal},
+    core::Hertz,
+    instrument::{Instrument, NoteError, VelocityCurve},
+    music::note::Note,
+};
+
+/// Wraps an [`AudioSource`] so it can be played from a keyboard,
+/// i.e. to play a bare oscillator or a drone.
+///
+/// Pressing a note opens an envelope over the source and releasing it
+/// closes the envelope again. Sources built with [`GatedSource::tuned`]
+/// are also retuned to each note, others keep playing at their own pitch.
+///
+/// Only one note sounds at a time, pressing a new note while one is
+/// held moves the gate over to it without retriggering the envelope.
+pub struct GatedSource<S: AudioSource<Frame = f32>> {
+    source: S,
+    envelope: Envelope,
+
+    /// Retunes the source to a note's frequency, if it can be retuned.
+    retune: Option<fn(&mut S, Hertz)>,
+
+    /// The note holding the gate open, if any.
+    note: Option<Note>,
+    /// The gain from the velocity of the last pressed note.
+    velocity: f32,
+    /// How velocities are mapped to the gain.
+    velocity_curve: VelocityCurve,
+}
+
+impl<S: AudioSource<Frame = f32>> GatedSource<S> {
+    /// Wraps a source that keeps playing at its own pitch.
+    pub fn new(source: S, sample_rate: usize) -> Self {
+        Self {
+            source,
+            envelope: Envelope::new(sample_rate),
+            retune: None,
+            note: None,
+            velocity: 0.0,
+            velocity_curve: VelocityCurve::Linear,
+        }
+    }
+
+    /// Wraps a source that's retuned to the frequency of each pressed note.
+    pub fn tuned(source: S, sample_rate: usize) -> Self
+    where
+        S: ToneSource,
+    {
+        Self {
+            retune: Some(|source, frequency| ToneSource::set_frequency(source, frequency)),
+            ..Self::new(source, sample_rate)
+        }
+    }
+
+    /// Returns the wrapped source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the wrapped source mutably, i.e. to change its waveform.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the envelope applied to the source, i.e. to change its times.
+    pub fn envelope_mut(&mut self) -> &mut Envelope {
+        &mut self.envelope
+    }
+
+    /// Opens the gate for a note, retuning the source to the frequency.
+    fn press(&mut self, note: Note, frequency: Hertz, velocity: u8) {
+        if let Some(retune) = self.retune {
+            retune(&mut self.source, frequency);
+        }
+
+        self.note = Some(note);
+        self.velocity = self.velocity_curve.apply(velocity);
+    }
+
+    /// Unwraps the source.
+    pub fn into_source(self) -> S {
+        self.source
+    }
+}
+
+impl<S: AudioSource<Frame = f32>> AudioSource for GatedSource<S> {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        self.source.render(buffer);
+
+        let gate = self.note.is_some();
+        for sample in buffer.iter_mut() {
+            *sample *= self.envelope.process(gate) * self.velocity;
+        }
+    }
+
+    /// Silent once the note is released and the envelope has closed.
+    fn is_silent(&self) -> bool {
+        self.note.is_none() && self.envelope.is_idle()
+    }
+}
+
+impl<S: AudioSource<Frame = f32>> Signal for GatedSource<S> {
+    type Frame = f32;
+
+    fn next(&mut self) -> Self::Frame {
+        let mut sample = [0.0];
+        AudioSource::render(self, &mut sample);
+        sample[0]
+    }
+}
+
+impl<S: AudioSource<Frame = f32>> Instrument for GatedSource<S> {
+    fn init(&mut self) {}
+
+    fn note_on(&mut self, note: Note, velocity: u8) -> Result<(), NoteError> {
+        self.press(note, note.frequency(), velocity);
+        Ok(())
+    }
+
+    /// Retunes to the frequency as is, the gate is keyed by the nearest note.
+    fn note_on_freq(&mut self, frequency: Hertz, velocity: u8) -> Result<(), NoteError> {
+        let note = Note::nearest(frequency).ok_or(NoteError::OutOfRange)?;
+        self.press(note, frequency, velocity);
+        Ok(())
+    }
+
+    fn note_off(&mut self, note: Note) {
+        // Releasing a note that's since been replaced leaves the gate open.
+        if self.note == Some(note) {
+            self.note = None;
+        }
+    }
+
+    fn is_note_on(&self, note: Note) -> bool {
+        self.note == Some(note)
+    }
+
+    fn all_notes_off(&mut self) {
+        self.note = None;
+        self.envelope.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.envelope.set_sample_rate(sample_rate);
+    }
+
+    fn velocity_curve(&self) -> VelocityCurve {
+        self.velocity_curve
+    }
+
+    /// Applies from the next pressed note, the held note keeps it's level.
+    fn set_velocity_curve(&mut self, curve: VelocityCurve) {
+        self.velocity_curve = curve;
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::oscillator::{OscillatorType, RuntimeOscillator},
+        music::note,
+    };
+    use pretty_assertions::assert_eq;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    /// Renders 50ms and returns the peak level.
+    fn peak(instrument: &mut GatedSource<RuntimeOscillator>) -> f32 {
+        let mut buffer = [0.0; SAMPLE_RATE / 20];
+        AudioSource::render(instrument, &mut buffer);
+        buffer
+            .iter()
+            .fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_gates_source() {
+        let sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(100.0));
+        let mut instrument = GatedSource::tuned(sine, SAMPLE_RATE);
+        instrument.envelope_mut().set_attack_time(0.001, 0.0);
+        instrument.envelope_mut().set_sustain_level(1.0);
+        instrument.envelope_mut().set_release_time(0.001);
+
+        // Silent until a note is pressed.
+        assert!(instrument.is_silent());
+        self::assert_eq!(peak(&mut instrument), 0.0);
+
+        instrument.note_on(note::AFour, 127).unwrap();
+        assert!(instrument.is_note_on(note::AFour));
+        self::assert_eq!(instrument.source().get_frequency(), note::AFour.frequency());
+        let held = peak(&mut instrument);
+        assert!(held > 0.9, "held note peaked at {held}");
+
+        // Releasing another note leaves the gate open.
+        instrument.note_off(note::CFour);
+        assert!(peak(&mut instrument) > 0.9);
+
+        instrument.note_off(note::AFour);
+        assert!(!instrument.is_note_on(note::AFour));
+        assert!(!instrument.is_silent());
+        peak(&mut instrument);
+        self::assert_eq!(peak(&mut instrument), 0.0);
+        assert!(instrument.is_silent());
+    }
+
+    #[test]
+    fn test_untuned_source_keeps_pitch() {
+        let sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(100.0));
+        let mut instrument = GatedSource::new(sine, SAMPLE_RATE);
+
+        instrument.note_on(note::AFour, 100).unwrap();
+        self::assert_eq!(instrument.source().get_frequency(), Hertz(100.0));
+    }
+
+    #[test]
+    fn test_velocity_curve() {
+        let sine = RuntimeOscillator::new(OscillatorType::Sine, SAMPLE_RATE, Hertz(100.0));
+        let mut instrument = GatedSource::new(sine, SAMPLE_RATE);
+        instrument.envelope_mut().set_attack_time(0.001, 0.0);
+        instrument.envelope_mut().set_sustain_level(1.0);
+
+        instrument.note_on(note::AFour, 64).unwrap();
+        let linear = peak(&mut instrument);
+
+        instrument.set_velocity_curve(VelocityCurve::Exponential);
+        self::assert_eq!(instrument.velocity_curve(), VelocityCurve::Exponential);
+        instrument.note_on(note::AFour, 64).unwrap();
+        let exponential = peak(&mut instrument);
+
+        assert!(
+            exponential < linear * 0.6,
+            "{exponential} vs linear {linear}"
+        );
+    }
+}
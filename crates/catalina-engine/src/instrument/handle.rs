@@ -0,0 +1,193 @@
+//! Controlling an instrument from a different thread than it's rendered on.
+//!
+//! In std apps the audio callback owns the instrument, while notes come
+//! in on a UI or MIDI thread. [`InstrumentHandle`] owns the instrument on
+//! the audio thread, and [`InstrumentSender`]s send it events over a
+//! bounded channel that never blocks or allocates on the audio thread.
+
+use std::sync::mpsc::{Receiver, SyncSender, TryRecvError, TrySendError, sync_channel};
+
+use crate::{
+    audio::{AudioSource, signal::Signal},
+    core::param::ParamId,
+    instrument::{Instrument, InstrumentEvent},
+    music::note::Note,
+};
+
+/// An error returned when an [`InstrumentSender`] can't send an event.
+#[derive(Debug)]
+pub enum SendError {
+    /// The channel is full as the audio thread has fallen behind.
+    Full,
+    /// The [`InstrumentHandle`] was dropped, so the event can never be applied.
+    Disconnected,
+}
+
+/// Sends events to an [`InstrumentHandle`], from any thread.
+///
+/// Senders can be cloned to control the instrument from several threads.
+#[derive(Debug, Clone)]
+pub struct InstrumentSender {
+    sender: SyncSender<InstrumentEvent>,
+}
+
+impl InstrumentSender {
+    /// Sends an event to be applied at the start of the next render.
+    ///
+    /// Never blocks, returns [`SendError::Full`] if the audio
+    /// thread has fallen behind and the channel has filled up.
+    pub fn send(&self, event: InstrumentEvent) -> Result<(), SendError> {
+        self.sender.try_send(event).map_err(|error| match error {
+            TrySendError::Full(_) => SendError::Full,
+            TrySendError::Disconnected(_) => SendError::Disconnected,
+        })
+    }
+
+    /// Sends a note on to be applied at the start of the next render.
+    pub fn note_on(&self, note: Note, velocity: u8) -> Result<(), SendError> {
+        self.send(InstrumentEvent::NoteOn { note, velocity })
+    }
+
+    /// Sends a note off to be applied at the start of the next render.
+    pub fn note_off(&self, note: Note) -> Result<(), SendError> {
+        self.send(InstrumentEvent::NoteOff { note })
+    }
+
+    /// Sends a parameter change to be applied at the start of the next render.
+    pub fn set_param(&self, id: ParamId, value: f32) -> Result<(), SendError> {
+        self.send(InstrumentEvent::SetParam { id, value })
+    }
+}
+
+/// Owns an instrument on the audio thread, applying the
+/// events from it's [`InstrumentSender`]s before each render.
+pub struct InstrumentHandle<I: Instrument> {
+    instrument: I,
+    receiver: Receiver<InstrumentEvent>,
+}
+
+impl<I: Instrument> InstrumentHandle<I> {
+    /// Wraps the instrument, returning the handle for the audio thread and a
+    /// sender for the control thread. Up to `capacity` events can be waiting
+    /// between renders before sending fails.
+    ///
+    /// A capacity of 0 is raised to 1, as a zero capacity channel only
+    /// hands over events to a waiting receiver and the audio thread
+    /// never waits, so every send would fail.
+    pub fn new(instrument: I, capacity: usize) -> (Self, InstrumentSender) {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+
+        (
+            Self {
+                instrument,
+                receiver,
+            },
+            InstrumentSender { sender },
+        )
+    }
+
+    /// Returns the wrapped instrument.
+    pub fn instrument(&self) -> &I {
+        &self.instrument
+    }
+
+    /// Returns the wrapped instrument mutably, i.e. to set it up.
+    pub fn instrument_mut(&mut self) -> &mut I {
+        &mut self.instrument
+    }
+
+    /// Unwraps the instrument, dropping any events still waiting.
+    pub fn into_inner(self) -> I {
+        self.instrument
+    }
+
+    /// Applies all of the events that have been sent since the last render.
+    ///
+    /// Events the instrument rejects, i.e. a note on with no free voices,
+    /// are dropped as there's no one on the audio thread to report them to.
+    /// Returns false once every sender has been dropped.
+    pub fn apply_pending(&mut self) -> bool {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    let _ = event.apply(&mut self.instrument);
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+}
+
+impl<I: Instrument> AudioSource for InstrumentHandle<I> {
+    type Frame = <I as AudioSource>::Frame;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        self.apply_pending();
+        AudioSource::render(&mut self.instrument, buffer);
+    }
+}
+
+impl<I: Instrument> Signal for InstrumentHandle<I> {
+    type Frame = <I as Signal>::Frame;
+
+    fn next(&mut self) -> Self::Frame {
+        self.apply_pending();
+        Signal::next(&mut self.instrument)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::signal::ConstantSource, instrument::GatedSource, music::note::AFour};
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    #[test]
+    fn test_note_on_applies_on_next_render() {
+        let mut instrument = GatedSource::new(ConstantSource::new(1.0), SAMPLE_RATE);
+        instrument.envelope_mut().set_attack_time(0.0, 0.0);
+        instrument.envelope_mut().set_sustain_level(1.0);
+        let (mut handle, sender) = InstrumentHandle::new(instrument, 4);
+
+        std::thread::spawn(move || sender.note_on(AFour, 127).unwrap())
+            .join()
+            .unwrap();
+        assert!(!handle.instrument().is_note_on(AFour));
+
+        let mut buffer = [0.0; 16];
+        AudioSource::render(&mut handle, &mut buffer);
+        assert!(handle.instrument().is_note_on(AFour));
+        assert!(buffer.iter().all(|sample| *sample > 0.0));
+
+        // The only sender went with the thread.
+        assert!(!handle.apply_pending());
+    }
+
+    #[test]
+    fn test_full_channel() {
+        let instrument = GatedSource::new(ConstantSource::new(1.0), SAMPLE_RATE);
+        let (_handle, sender) = InstrumentHandle::new(instrument, 1);
+
+        sender.note_off(AFour).unwrap();
+        assert!(matches!(sender.note_off(AFour), Err(SendError::Full)));
+    }
+
+    #[test]
+    fn test_zero_capacity_holds_an_event() {
+        let instrument = GatedSource::new(ConstantSource::new(1.0), SAMPLE_RATE);
+        let (handle, sender) = InstrumentHandle::new(instrument, 0);
+
+        sender.note_off(AFour).unwrap();
+        assert!(matches!(sender.note_off(AFour), Err(SendError::Full)));
+
+        drop(handle);
+        assert!(matches!(
+            sender.note_off(AFour),
+            Err(SendError::Disconnected)
+        ));
+    }
+}
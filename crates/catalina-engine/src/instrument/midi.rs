@@ -0,0 +1,354 @@
+//! Decoding MIDI channel messages and playing instruments with them.
+//!
+//! Notes are pressed and released directly, while continuous controllers
+//! (CC) are routed to instrument parameters through a [`CcMap`].
+
+use heapless::Vec;
+
+use crate::{
+    core::{
+        EngineError,
+        param::{ParamError, ParamId},
+    },
+    instrument::{Instrument, NoteError},
+    music::note::Note,
+};
+
+/// An error returned when decoding a MIDI message or mapping a controller.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug)]
+pub enum MidiError {
+    /// The message is shorter than it's status byte requires.
+    Incomplete,
+    /// The first byte isn't a status byte, or a data byte is over 127.
+    InvalidData,
+    /// The message is valid MIDI that isn't handled, i.e. system messages.
+    Unsupported,
+    /// The [`CcMap`] has no room left for another controller.
+    MapFull,
+}
+
+/// A decoded MIDI channel message, with the channel from 0 to 15.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MidiMessage {
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+}
+
+impl MidiMessage {
+    /// Decodes a message from it's raw bytes, starting with the status byte.
+    ///
+    /// A note on with a velocity of 0 is decoded as a note off, as
+    /// many controllers send them instead to use running status.
+    pub fn decode(bytes: &[u8]) -> Result<Self, MidiError> {
+        let [status, data @ ..] = bytes else {
+            return Err(MidiError::Incomplete);
+        };
+        if status & 0x80 == 0 {
+            return Err(MidiError::InvalidData);
+        }
+
+        let channel = status & 0x0F;
+        let kind = status & 0xF0;
+        if !matches!(kind, 0x80 | 0x90 | 0xB0) {
+            return Err(MidiError::Unsupported);
+        }
+
+        let [first, second, ..] = data else {
+            return Err(MidiError::Incomplete);
+        };
+        if (first | second) & 0x80 != 0 {
+            return Err(MidiError::InvalidData);
+        }
+        let (first, second) = (*first, *second);
+
+        Ok(match kind {
+            0x90 if second > 0 => MidiMessage::NoteOn {
+                channel,
+                note: first,
+                velocity: second,
+            },
+            0x80 | 0x90 => MidiMessage::NoteOff {
+                channel,
+                note: first,
+                velocity: second,
+            },
+            _ => MidiMessage::ControlChange {
+                channel,
+                controller: first,
+                value: second,
+            },
+        })
+    }
+}
+
+/// Maps up to `N` continuous controllers to instrument parameters.
+///
+/// Controller values are scaled from 0 to 127 onto the range of
+/// the parameter, as described by [`Instrument::param_descriptors`].
+/// Messages from every channel are handled alike.
+#[derive(Debug, Clone)]
+pub struct CcMap<const N: usize> {
+    mappings: Vec<(u8, ParamId), N>,
+}
+
+impl<const N: usize> CcMap<N> {
+    /// Constructs a map without any controllers mapped.
+    pub const fn new() -> Self {
+        Self {
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Maps a controller to a parameter, replacing any
+    /// parameter the controller was already mapped to.
+    pub fn map(&mut self, controller: u8, id: ParamId) -> Result<(), MidiError> {
+        if let Some(mapping) = self.mappings.iter_mut().find(|(cc, _)| *cc == controller) {
+            mapping.1 = id;
+            return Ok(());
+        }
+
+        self.mappings
+            .push((controller, id))
+            .map_err(|_| MidiError::MapFull)
+    }
+
+    /// Removes the mapping for a controller, returning the parameter it was mapped to.
+    pub fn unmap(&mut self, controller: u8) -> Option<ParamId> {
+        let index = self.mappings.iter().position(|(cc, _)| *cc == controller)?;
+        Some(self.mappings.swap_remove(index).1)
+    }
+
+    /// Returns the parameter a controller is mapped to, if any.
+    pub fn param(&self, controller: u8) -> Option<ParamId> {
+        self.mappings
+            .iter()
+            .find(|(cc, _)| *cc == controller)
+            .map(|(_, id)| *id)
+    }
+
+    /// Sets the parameter mapped to the controller from a 0 to 127 value.
+    ///
+    /// Unmapped controllers are ignored, returning false. Fails if the
+    /// instrument doesn't describe the mapped parameter, as it's range
+    /// is needed to scale the value.
+    pub fn apply_cc<I: Instrument>(
+        &self,
+        instrument: &mut I,
+        controller: u8,
+        value: u8,
+    ) -> Result<bool, EngineError> {
+        let Some(id) = self.param(controller) else {
+            return Ok(false);
+        };
+
+        let descriptor = instrument
+            .param_descriptors()
+            .iter()
+            .find(|descriptor| descriptor.id == id)
+            .ok_or(ParamError::UnknownParam(id))?;
+
+        let scaled =
+            descriptor.min + (descriptor.max - descriptor.min) * (value.min(127) as f32 / 127.0);
+        instrument.set_param(id, descriptor.clamp(scaled))?;
+
+        Ok(true)
+    }
+
+    /// Plays a decoded message on the instrument, pressing and releasing
+    /// notes and routing control changes through the map.
+    pub fn handle<I: Instrument>(
+        &self,
+        instrument: &mut I,
+        message: MidiMessage,
+    ) -> Result<(), EngineError> {
+        // Notes below C0 aren't in the available octaves.
+        let note_from_midi = |note| Note::from_midi(note).ok_or(NoteError::OutOfRange);
+
+        match message {
+            MidiMessage::NoteOn { note, velocity, .. } => {
+                instrument.note_on(note_from_midi(note)?, velocity)?
+            }
+            MidiMessage::NoteOff { note, .. } => instrument.note_off(note_from_midi(note)?),
+            MidiMessage::ControlChange {
+                controller, value, ..
+            } => {
+                self.apply_cc(instrument, controller, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for CcMap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::{AudioSource, signal::Signal},
+        core::param::{ParamDescriptor, ParamUnit},
+        music::note,
+    };
+    use pretty_assertions::assert_eq;
+
+    const CUTOFF: ParamId = ParamId(0);
+
+    /// An instrument with a single filter cutoff parameter.
+    #[derive(Default)]
+    struct Filtered {
+        cutoff: f32,
+        held: Option<Note>,
+    }
+
+    impl AudioSource for Filtered {
+        type Frame = f32;
+
+        fn render(&mut self, _buffer: &'_ mut [Self::Frame]) {}
+    }
+
+    impl Signal for Filtered {
+        type Frame = f32;
+
+        fn next(&mut self) -> Self::Frame {
+            0.0
+        }
+    }
+
+    impl Instrument for Filtered {
+        fn init(&mut self) {}
+
+        fn param_descriptors(&self) -> &'static [ParamDescriptor] {
+            &[ParamDescriptor {
+                id: CUTOFF,
+                name: "Cutoff",
+                min: 100.0,
+                max: 1370.0,
+                default: 1370.0,
+                unit: ParamUnit::Hertz,
+            }]
+        }
+
+        fn set_param(&mut self, id: ParamId, value: f32) -> Result<(), ParamError> {
+            match id {
+                CUTOFF => self.cutoff = value,
+                _ => return Err(ParamError::UnknownParam(id)),
+            }
+            Ok(())
+        }
+
+        fn note_on(&mut self, note: Note, _velocity: u8) -> Result<(), NoteError> {
+            self.held = Some(note);
+            Ok(())
+        }
+
+        fn note_off(&mut self, note: Note) {
+            if self.held == Some(note) {
+                self.held = None;
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        self::assert_eq!(
+            MidiMessage::decode(&[0xB3, 74, 64]).unwrap(),
+            MidiMessage::ControlChange {
+                channel: 3,
+                controller: 74,
+                value: 64
+            }
+        );
+        self::assert_eq!(
+            MidiMessage::decode(&[0x90, 69, 0]).unwrap(),
+            MidiMessage::NoteOff {
+                channel: 0,
+                note: 69,
+                velocity: 0
+            }
+        );
+        assert!(matches!(
+            MidiMessage::decode(&[0xB0, 74]),
+            Err(MidiError::Incomplete)
+        ));
+        assert!(matches!(
+            MidiMessage::decode(&[74, 64]),
+            Err(MidiError::InvalidData)
+        ));
+        assert!(matches!(
+            MidiMessage::decode(&[0xF8]),
+            Err(MidiError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_cc_sets_scaled_param() {
+        let mut instrument = Filtered::default();
+        let mut map = CcMap::<4>::new();
+        map.map(74, CUTOFF).unwrap();
+
+        let message = MidiMessage::decode(&[0xB0, 74, 100]).unwrap();
+        map.handle(&mut instrument, message).unwrap();
+
+        // 100 of 127 along the 100Hz to 1370Hz range.
+        self::assert_eq!(instrument.cutoff, 1100.0);
+
+        // Unmapped controllers are left alone.
+        assert!(!map.apply_cc(&mut instrument, 1, 0).unwrap());
+        self::assert_eq!(instrument.cutoff, 1100.0);
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut instrument = Filtered::default();
+        let map = CcMap::<1>::new();
+
+        map.handle(
+            &mut instrument,
+            MidiMessage::decode(&[0x90, 69, 100]).unwrap(),
+        )
+        .unwrap();
+        self::assert_eq!(instrument.held, Some(note::AFour));
+
+        map.handle(
+            &mut instrument,
+            MidiMessage::decode(&[0x80, 69, 0]).unwrap(),
+        )
+        .unwrap();
+        self::assert_eq!(instrument.held, None);
+    }
+
+    #[test]
+    fn test_full_map() {
+        let mut map = CcMap::<1>::new();
+        map.map(1, CUTOFF).unwrap();
+        // Remapping the same controller doesn't take more room.
+        map.map(1, ParamId(2)).unwrap();
+        self::assert_eq!(map.param(1), Some(ParamId(2)));
+
+        assert!(matches!(map.map(2, CUTOFF), Err(MidiError::MapFull)));
+        self::assert_eq!(map.unmap(1), Some(ParamId(2)));
+        self::assert_eq!(map.param(1), None);
+    }
+}
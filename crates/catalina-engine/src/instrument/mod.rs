@@ -1,22 +1,329 @@
 use crate::{
     audio::{AudioSource, signal::Signal},
+    core::{
+        Hertz,
+        param::{ParamDescriptor, ParamError, ParamId},
+    },
     music::note::Note,
 };
 
+pub mod gated;
+pub use gated::GatedSource;
+
+pub mod events;
+pub use events::{EventQueue, InstrumentEvent};
+
+#[cfg(feature = "std")]
+pub mod handle;
+#[cfg(feature = "std")]
+pub use handle::{InstrumentHandle, InstrumentSender, SendError};
+
+pub mod midi;
+pub use midi::{CcMap, MidiMessage};
+
+pub mod velocity;
+pub use velocity::VelocityCurve;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_support;
+
 #[derive(Debug)]
 pub enum NoteError {
     NoVoices,
+    /// The frequency is too far outside of the available octaves to play.
+    OutOfRange,
 }
 
 pub trait Instrument: AudioSource + Signal {
     /// Initializes the instrument for use.
     fn init(&mut self);
 
-    // TODO: parameters
+    /// Describes the parameters the instrument exposes to hosts.
+    ///
+    /// The default implementation exposes no parameters.
+    fn param_descriptors(&self) -> &'static [ParamDescriptor] {
+        &[]
+    }
+
+    /// Sets one of the parameters from [`Instrument::param_descriptors`].
+    ///
+    /// Values outside of the parameter's range are clamped to it.
+    fn set_param(&mut self, id: ParamId, _value: f32) -> Result<(), ParamError> {
+        Err(ParamError::UnknownParam(id))
+    }
 
     /// Signals to the instrument that a note has been pressed.
+    ///
+    /// Fails with [`NoteError::NoVoices`] when the instrument can't play any more notes.
+    #[must_use = "the note isn't played when the instrument has no free voices"]
     fn note_on(&mut self, note: Note, velocity: u8) -> Result<(), NoteError>;
 
+    /// Signals to the instrument that an arbitrary frequency has been pressed,
+    /// i.e. from a theremin or a tuning table, without quantizing it to a note.
+    ///
+    /// Release it with [`Instrument::note_off`] for the [`Note::nearest`]
+    /// to the frequency. The default implementation plays the nearest note,
+    /// instruments that can play any frequency should override it.
+    #[must_use = "the note isn't played when the instrument has no free voices"]
+    fn note_on_freq(&mut self, frequency: Hertz, velocity: u8) -> Result<(), NoteError> {
+        let note = Note::nearest(frequency).ok_or(NoteError::OutOfRange)?;
+        self.note_on(note, velocity)
+    }
+
     /// Signals to the instrument that a note has been released.
     fn note_off(&mut self, note: Note);
+
+    /// Returns true if the note is currently pressed, i.e. for
+    /// highlighting keys or deciding whether to play legato.
+    ///
+    /// Notes that have been released but are still fading out
+    /// aren't held. The default implementation always returns false.
+    fn is_note_on(&self, _note: Note) -> bool {
+        false
+    }
+
+    /// Silences every note immediately, including any release tails.
+    ///
+    /// Called when the transport stops, once it has faded the output
+    /// out, so no notes are left hanging. The default implementation
+    /// does nothing.
+    fn all_notes_off(&mut self) {}
+
+    /// Bends the pitch of all sounding notes by the provided amount of semitones.
+    ///
+    /// Instruments should ramp towards the new bend across their next render
+    /// block to avoid zipper noise. The default implementation ignores bends.
+    fn pitch_bend(&mut self, _semitones: f32) {}
+
+    /// Glides a sounding note to a new note without retriggering it.
+    ///
+    /// Used by the sequencer for slid steps. Instruments that support
+    /// portamento should move the voice playing `from` over to `to`
+    /// and ramp it's pitch, the default implementation just releases
+    /// `from` and presses `to`.
+    #[must_use = "the note isn't played when the instrument has no free voices"]
+    fn glide(&mut self, from: Note, to: Note, velocity: u8) -> Result<(), NoteError> {
+        self.note_off(from);
+        self.note_on(to, velocity)
+    }
+
+    /// Presses all of the provided notes at once.
+    ///
+    /// Either every note is pressed or none are, if a note fails then
+    /// the notes of the chord that were already pressed are released.
+    #[must_use = "the chord isn't played when the instrument has no free voices"]
+    fn chord(&mut self, notes: &[Note], velocity: u8) -> Result<(), NoteError> {
+        for (i, note) in notes.iter().enumerate() {
+            if let Err(error) = self.note_on(*note, velocity) {
+                for pressed in &notes[..i] {
+                    self.note_off(*pressed);
+                }
+
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Changes the sample rate the instrument renders at, i.e. after
+    /// switching audio devices, without changing the pitch of sounding notes.
+    ///
+    /// The default implementation ignores the change.
+    fn set_sample_rate(&mut self, _sample_rate: usize) {}
+
+    /// Returns how the instrument maps note velocities to levels.
+    fn velocity_curve(&self) -> VelocityCurve {
+        VelocityCurve::Linear
+    }
+
+    /// Sets how the instrument maps note velocities to levels.
+    ///
+    /// The default implementation ignores the curve, for
+    /// instruments that don't respond to velocity.
+    fn set_velocity_curve(&mut self, _curve: VelocityCurve) {}
+
+    /// Returns the gain applied to the instrument's output.
+    fn master_gain(&self) -> f32 {
+        1.0
+    }
+
+    /// Sets the linear gain applied to the instrument's output.
+    ///
+    /// The default implementation ignores the gain, instruments
+    /// can use a [`MasterOutput`] to apply it and meter the result.
+    fn set_master_gain(&mut self, _gain: f32) {}
+
+    /// Returns the absolute peak level of the instrument's output after the master gain.
+    ///
+    /// The default implementation doesn't meter and always returns 0.0.
+    fn peak(&self) -> f32 {
+        0.0
+    }
+}
+
+/// Applies a master gain to an instrument's output and meters the peak level.
+///
+/// Instruments hold one of these and pass their rendered
+/// output through it to back the master gain and peak
+/// methods of [`Instrument`].
+///
+/// Gain changes are ramped linearly across the next processed block
+/// so automating the gain doesn't click. Changes made before anything
+/// has been processed apply straight away, there's nothing to ramp from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MasterOutput {
+    gain: f32,
+    /// The gain applied to the last processed sample, if any
+    /// have been, the start of the ramp to a new gain.
+    applied: Option<f32>,
+    peak: f32,
+}
+
+impl MasterOutput {
+    /// Constructs a new output stage at unity gain.
+    pub const fn new() -> Self {
+        Self {
+            gain: 1.0,
+            applied: None,
+            peak: 0.0,
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the linear gain, negative gains are clamped to silence.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.max(0.0);
+    }
+
+    /// The peak of the last processed block, or the highest
+    /// peak since then when processing sample by sample.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Applies the gain to a single sample and meters it.
+    ///
+    /// There's no block to ramp across when processing
+    /// sample by sample, so the gain is applied directly.
+    #[inline]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.applied = Some(self.gain);
+        self.apply(sample, self.gain)
+    }
+
+    /// Applies the gain to a rendered block in place,
+    /// metering the block's peak.
+    pub fn process_buffer(&mut self, buffer: &mut [f32]) {
+        self.peak = 0.0;
+
+        let from = self.applied.unwrap_or(self.gain);
+        if from == self.gain {
+            for sample in buffer.iter_mut() {
+                *sample = self.apply(*sample, self.gain);
+            }
+        } else {
+            // Ramp so the last sample of the block lands on the new gain.
+            let step = (self.gain - from) / buffer.len() as f32;
+            for (i, sample) in buffer.iter_mut().enumerate() {
+                *sample = self.apply(*sample, from + step * (i + 1) as f32);
+            }
+        }
+
+        if !buffer.is_empty() {
+            self.applied = Some(self.gain);
+        }
+    }
+
+    /// Scales a sample and meters the result.
+    #[inline]
+    fn apply(&mut self, sample: f32, gain: f32) -> f32 {
+        let output = sample * gain;
+        self.peak = self.peak.max(libm::fabsf(output));
+        output
+    }
+}
+
+impl Default for MasterOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instrument::test_support::RecordingInstrument, music::note};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_chord_rolls_back_when_full() {
+        let mut instrument = RecordingInstrument::with_voices(2);
+        instrument.note_on(note::AThree, 100).unwrap();
+
+        let result = instrument.chord(&[note::CFour, note::EFour, note::GFour], 100);
+        assert!(matches!(result, Err(NoteError::NoVoices)));
+
+        // Only the note from before the chord is still held.
+        self::assert_eq!(instrument.held.as_slice(), &[note::AThree]);
+    }
+
+    #[test]
+    fn test_note_on_freq_plays_nearest_note() {
+        let mut instrument = RecordingInstrument::with_voices(2);
+        instrument.note_on_freq(Hertz(445.0), 100).unwrap();
+        self::assert_eq!(instrument.held.as_slice(), &[note::AFour]);
+
+        let result = instrument.note_on_freq(Hertz(0.0), 100);
+        assert!(matches!(result, Err(NoteError::OutOfRange)));
+    }
+
+    #[test]
+    fn test_chord_presses_every_note() {
+        let mut instrument = RecordingInstrument::with_voices(2);
+        instrument.chord(&[note::CFour, note::GFour], 100).unwrap();
+        self::assert_eq!(instrument.held.as_slice(), &[note::CFour, note::GFour]);
+    }
+
+    #[test]
+    fn test_master_gain_ramps_across_block() {
+        let mut output = MasterOutput::new();
+        let mut buffer = [1.0; 64];
+        output.process_buffer(&mut buffer);
+        self::assert_eq!(buffer, [1.0; 64]);
+
+        output.set_gain(0.0);
+        let mut buffer = [1.0; 64];
+        output.process_buffer(&mut buffer);
+
+        // Falls steadily to the new gain, never jumping by more than a step.
+        let mut previous = 1.0;
+        for sample in buffer {
+            assert!(sample <= previous, "{sample} rose from {previous}");
+            assert!(previous - sample <= 1.0 / 64.0 + 1e-6);
+            previous = sample;
+        }
+        self::assert_eq!(buffer[63], 0.0);
+
+        // The next block stays at the new gain.
+        let mut buffer = [1.0; 64];
+        output.process_buffer(&mut buffer);
+        self::assert_eq!(buffer, [0.0; 64]);
+    }
+
+    #[test]
+    fn test_master_gain_before_processing_is_immediate() {
+        let mut output = MasterOutput::new();
+        output.set_gain(0.5);
+
+        let mut buffer = [1.0; 16];
+        output.process_buffer(&mut buffer);
+        self::assert_eq!(buffer, [0.5; 16]);
+        self::assert_eq!(output.peak(), 0.5);
+    }
 }
@@ -0,0 +1,306 @@
+//! Utilities for testing that instruments render the same output over time.
+//!
+//! Render an instrument through a fixed sequence of notes with
+//! [`render_sequence`], then compare the result against a reference
+//! buffer from a known good version with [`assert_close`].
+//!
+//! Code that drives instruments, such as the sequencer, can be tested
+//! against a [`RecordingInstrument`] that records the notes it's played.
+//!
+//! Available in the engine's own tests, and to other crates
+//! with the `test-util` feature (which requires `std`).
+
+use std::vec::Vec;
+
+use crate::{
+    audio::{AudioSource, signal::Signal},
+    instrument::{Instrument, NoteError},
+    music::note::Note,
+};
+
+/// A note event at a sample offset from the start of a rendered sequence.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SequenceEvent {
+    NoteOn {
+        frame: usize,
+        note: Note,
+        velocity: u8,
+    },
+    NoteOff {
+        frame: usize,
+        note: Note,
+    },
+}
+
+impl SequenceEvent {
+    /// Returns the sample offset the event happens at.
+    pub fn frame(&self) -> usize {
+        match self {
+            SequenceEvent::NoteOn { frame, .. } | SequenceEvent::NoteOff { frame, .. } => *frame,
+        }
+    }
+}
+
+/// Renders `samples` frames from the instrument, sending each
+/// event to it at the start of it's frame.
+///
+/// The events must be sorted by frame, events past the end are ignored.
+pub fn render_sequence<I>(
+    instrument: &mut I,
+    events: &[SequenceEvent],
+    samples: usize,
+) -> Result<Vec<f32>, NoteError>
+where
+    I: Instrument + AudioSource<Frame = f32>,
+{
+    let mut buffer = std::vec![0.0; samples];
+    let mut rendered = 0;
+
+    for event in events.iter().filter(|event| event.frame() < samples) {
+        // Render up to the event, then apply it.
+        instrument.render(&mut buffer[rendered..event.frame()]);
+        rendered = event.frame();
+
+        match *event {
+            SequenceEvent::NoteOn { note, velocity, .. } => instrument.note_on(note, velocity)?,
+            SequenceEvent::NoteOff { note, .. } => instrument.note_off(note),
+        }
+    }
+
+    instrument.render(&mut buffer[rendered..]);
+
+    Ok(buffer)
+}
+
+/// A note event received by a [`RecordingInstrument`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RecordedEvent {
+    On(Note),
+    Off(Note),
+    Glide(Note, Note),
+}
+
+/// A silent instrument that records the notes it's played.
+///
+/// Can be limited to holding a number of notes at once, past which note
+/// ons fail with [`NoteError::NoVoices`] like a polyphonic synth that's
+/// out of voices. Rejected notes aren't recorded.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingInstrument {
+    /// The events the instrument accepted, in the order they were received.
+    pub events: Vec<RecordedEvent>,
+    /// The notes that are currently held.
+    pub held: Vec<Note>,
+    /// The most notes that can be held at once, unlimited when `None`.
+    voices: Option<usize>,
+}
+
+impl RecordingInstrument {
+    /// Constructs an instrument that can hold any number of notes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs an instrument that can only hold `voices` notes at once.
+    pub fn with_voices(voices: usize) -> Self {
+        Self {
+            voices: Some(voices),
+            ..Self::default()
+        }
+    }
+
+    /// Holds a note if there's a voice free for it.
+    fn hold(&mut self, note: Note) -> Result<(), NoteError> {
+        if self.voices.is_some_and(|voices| self.held.len() >= voices) {
+            return Err(NoteError::NoVoices);
+        }
+
+        self.held.push(note);
+        Ok(())
+    }
+}
+
+impl AudioSource for RecordingInstrument {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        buffer.fill(0.0);
+    }
+}
+
+impl Signal for RecordingInstrument {
+    type Frame = f32;
+
+    fn next(&mut self) -> Self::Frame {
+        0.0
+    }
+}
+
+impl Instrument for RecordingInstrument {
+    fn init(&mut self) {}
+
+    fn note_on(&mut self, note: Note, _velocity: u8) -> Result<(), NoteError> {
+        self.hold(note)?;
+        self.events.push(RecordedEvent::On(note));
+        Ok(())
+    }
+
+    fn note_off(&mut self, note: Note) {
+        self.held.retain(|held| *held != note);
+        self.events.push(RecordedEvent::Off(note));
+    }
+
+    fn glide(&mut self, from: Note, to: Note, _velocity: u8) -> Result<(), NoteError> {
+        self.held.retain(|held| *held != from);
+        self.hold(to)?;
+        self.events.push(RecordedEvent::Glide(from, to));
+        Ok(())
+    }
+}
+
+/// Returns the largest difference between the samples of two buffers,
+/// or `None` if the buffers are different lengths.
+pub fn max_difference(actual: &[f32], expected: &[f32]) -> Option<f32> {
+    if actual.len() != expected.len() {
+        return None;
+    }
+
+    Some(
+        actual
+            .iter()
+            .zip(expected)
+            .fold(0.0_f32, |max, (a, b)| max.max(libm::fabsf(a - b))),
+    )
+}
+
+/// Asserts that every sample of `actual` is within `tolerance` of `expected`.
+///
+/// Panics with the first sample that differs, to
+/// make tracking down the regression easier.
+#[track_caller]
+pub fn assert_close(actual: &[f32], expected: &[f32], tolerance: f32) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "buffers are different lengths"
+    );
+
+    for (i, (a, b)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            libm::fabsf(a - b) <= tolerance,
+            "sample {i} differs: {a} != {b} (tolerance {tolerance})"
+        );
+    }
+}
+
+/// Picks `count` evenly spaced samples from a buffer.
+///
+/// Useful for keeping reference buffers for long renders
+/// short enough to be written out in a test.
+pub fn sample_points(buffer: &[f32], count: usize) -> Vec<f32> {
+    if count == 0 || buffer.is_empty() {
+        return Vec::new();
+    }
+
+    let stride = (buffer.len() / count).max(1);
+    buffer.iter().step_by(stride).take(count).copied().collect()
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio::{
+            oscillator::{Oscillator, OscillatorType, RuntimeOscillator},
+            signal::Signal,
+        },
+        music::note,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// A monophonic sine instrument.
+    struct Sine {
+        osc: Option<RuntimeOscillator>,
+    }
+
+    impl AudioSource for Sine {
+        type Frame = f32;
+
+        fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+            for sample in buffer.iter_mut() {
+                *sample = self.next();
+            }
+        }
+    }
+
+    impl Signal for Sine {
+        type Frame = f32;
+
+        fn next(&mut self) -> Self::Frame {
+            self.osc.as_mut().map_or(0.0, |osc| osc.sample())
+        }
+    }
+
+    impl Instrument for Sine {
+        fn init(&mut self) {}
+
+        fn note_on(&mut self, note: Note, _velocity: u8) -> Result<(), NoteError> {
+            self.osc = Some(RuntimeOscillator::new(
+                OscillatorType::Sine,
+                48_000,
+                note.frequency(),
+            ));
+            Ok(())
+        }
+
+        fn note_off(&mut self, _note: Note) {
+            self.osc = None;
+        }
+    }
+
+    const EVENTS: [SequenceEvent; 2] = [
+        SequenceEvent::NoteOn {
+            frame: 100,
+            note: note::AFour,
+            velocity: 127,
+        },
+        SequenceEvent::NoteOff {
+            frame: 300,
+            note: note::AFour,
+        },
+    ];
+
+    #[test]
+    fn test_render_sequence_places_events() {
+        let output = render_sequence(&mut Sine { osc: None }, &EVENTS, 400).unwrap();
+
+        self::assert_eq!(output.len(), 400);
+        assert!(output[..100].iter().all(|sample| *sample == 0.0));
+        assert!(output[100..300].iter().any(|sample| *sample > 0.9));
+        assert!(output[300..].iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn test_render_is_stable_across_runs() {
+        let first = render_sequence(&mut Sine { osc: None }, &EVENTS, 400).unwrap();
+        let second = render_sequence(&mut Sine { osc: None }, &EVENTS, 400).unwrap();
+        assert_close(&first, &second, 0.0);
+        self::assert_eq!(max_difference(&first, &second), Some(0.0));
+        self::assert_eq!(max_difference(&first, &second[..10]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "sample 101 differs")]
+    fn test_assert_close_reports_sample() {
+        let output = render_sequence(&mut Sine { osc: None }, &EVENTS, 400).unwrap();
+        assert_close(&output, &[0.0; 400], 1e-3);
+    }
+
+    #[test]
+    fn test_sample_points() {
+        let buffer = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        self::assert_eq!(sample_points(&buffer, 4), std::vec![0.0, 2.0, 4.0, 6.0]);
+    }
+}
@@ -0,0 +1,70 @@
+//! Curves for turning MIDI note velocities into levels.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How an incoming note velocity (0 to 127) maps to a level from 0.0 to 1.0.
+///
+/// Velocity feels linear to play, but loudness isn't perceived linearly,
+/// so a straight mapping bunches most of the dynamics into the soft end.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum VelocityCurve {
+    /// The level rises evenly with the velocity.
+    #[default]
+    Linear,
+    /// The level rises slowly at first and faster towards the top,
+    /// widening the range of soft notes for expressive playing.
+    Exponential,
+    /// The level rises quickly at first and levels off towards the top,
+    /// for a light touch or controllers that struggle to reach high velocities.
+    Logarithmic,
+    /// Every note plays at full level regardless of velocity, i.e. organs.
+    Fixed,
+}
+
+impl VelocityCurve {
+    /// Returns the level for a velocity, from 0.0 to 1.0.
+    ///
+    /// Velocities above 127 are treated as 127.
+    pub fn apply(self, velocity: u8) -> f32 {
+        let linear = velocity.min(127) as f32 / 127.0;
+
+        match self {
+            VelocityCurve::Linear => linear,
+            VelocityCurve::Exponential => linear * linear,
+            VelocityCurve::Logarithmic => libm::sqrtf(linear),
+            VelocityCurve::Fixed => 1.0,
+        }
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_endpoints_match() {
+        for curve in [
+            VelocityCurve::Linear,
+            VelocityCurve::Exponential,
+            VelocityCurve::Logarithmic,
+        ] {
+            self::assert_eq!(curve.apply(0), 0.0);
+            self::assert_eq!(curve.apply(127), 1.0);
+            self::assert_eq!(curve.apply(255), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_curves_shape_mid_velocities() {
+        let linear = VelocityCurve::Linear.apply(64);
+        assert!(VelocityCurve::Exponential.apply(64) < linear);
+        assert!(VelocityCurve::Logarithmic.apply(64) > linear);
+        self::assert_eq!(VelocityCurve::Fixed.apply(64), 1.0);
+    }
+}
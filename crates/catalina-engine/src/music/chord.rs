@@ -0,0 +1,389 @@
+//! A module for chords and voicing progressions of them.
+
+use core::str::FromStr;
+
+use heapless::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::music::{
+    interval::Interval, named_pitch::NamedPitch, note::Note, note::NoteParseError, octave::Octave,
+};
+
+/// The most notes in any chord, a seventh chord.
+pub const MAX_CHORD_NOTES: usize = 4;
+
+/// The notes of a chord, lowest first.
+pub type Voicing = Vec<Note, MAX_CHORD_NOTES>;
+
+/// An error returned from parsing a chord or building a progression.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChordError {
+    /// The chord didn't start with a valid root, such as "C" or "Bb".
+    InvalidRoot(NoteParseError),
+    /// The suffix after the root isn't a known chord quality.
+    UnknownQuality,
+    /// The progression has no room left for another chord.
+    ProgressionFull,
+}
+
+/// The kind of chord built on a root, which decides the intervals above it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Suspended2,
+    Suspended4,
+    Dominant7,
+    Major7,
+    Minor7,
+}
+
+impl ChordQuality {
+    /// Returns the intervals of the chord's notes above the root, including the root.
+    pub const fn intervals(self) -> &'static [Interval] {
+        match self {
+            ChordQuality::Major => &[
+                Interval::UNISON,
+                Interval::MAJOR_THIRD,
+                Interval::PERFECT_FIFTH,
+            ],
+            ChordQuality::Minor => &[
+                Interval::UNISON,
+                Interval::MINOR_THIRD,
+                Interval::PERFECT_FIFTH,
+            ],
+            ChordQuality::Diminished => {
+                &[Interval::UNISON, Interval::MINOR_THIRD, Interval::TRITONE]
+            }
+            ChordQuality::Augmented => &[
+                Interval::UNISON,
+                Interval::MAJOR_THIRD,
+                Interval::MINOR_SIXTH,
+            ],
+            ChordQuality::Suspended2 => &[
+                Interval::UNISON,
+                Interval::MAJOR_SECOND,
+                Interval::PERFECT_FIFTH,
+            ],
+            ChordQuality::Suspended4 => &[
+                Interval::UNISON,
+                Interval::PERFECT_FOURTH,
+                Interval::PERFECT_FIFTH,
+            ],
+            ChordQuality::Dominant7 => &[
+                Interval::UNISON,
+                Interval::MAJOR_THIRD,
+                Interval::PERFECT_FIFTH,
+                Interval::MINOR_SEVENTH,
+            ],
+            ChordQuality::Major7 => &[
+                Interval::UNISON,
+                Interval::MAJOR_THIRD,
+                Interval::PERFECT_FIFTH,
+                Interval::MAJOR_SEVENTH,
+            ],
+            ChordQuality::Minor7 => &[
+                Interval::UNISON,
+                Interval::MINOR_THIRD,
+                Interval::PERFECT_FIFTH,
+                Interval::MINOR_SEVENTH,
+            ],
+        }
+    }
+
+    /// Returns the quality for a chord symbol suffix, e.g. "m" or "maj7".
+    fn from_suffix(suffix: &str) -> Option<ChordQuality> {
+        Some(match suffix {
+            "" | "maj" => ChordQuality::Major,
+            "m" | "min" => ChordQuality::Minor,
+            "dim" => ChordQuality::Diminished,
+            "aug" | "+" => ChordQuality::Augmented,
+            "sus2" => ChordQuality::Suspended2,
+            "sus4" | "sus" => ChordQuality::Suspended4,
+            "7" => ChordQuality::Dominant7,
+            "maj7" => ChordQuality::Major7,
+            "m7" | "min7" => ChordQuality::Minor7,
+            _ => return None,
+        })
+    }
+}
+
+/// A chord, as a root and the quality built on it, e.g. "Fm" or "G7".
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct Chord {
+    pub root: NamedPitch,
+    pub quality: ChordQuality,
+}
+
+impl Chord {
+    pub const fn new(root: NamedPitch, quality: ChordQuality) -> Self {
+        Self { root, quality }
+    }
+
+    /// Returns the chord in root position with it's root in the provided octave.
+    ///
+    /// Notes that would be past the highest octave are left out.
+    pub fn notes(&self, octave: Octave) -> Voicing {
+        let root = Note::new(self.root, octave);
+
+        self.quality
+            .intervals()
+            .iter()
+            .filter_map(|interval| root.add_interval(*interval))
+            .collect()
+    }
+}
+
+/// Parses a chord symbol, a root such as "C", "F#" or "Bb" followed by a
+/// quality such as "m", "dim", "aug", "sus2", "sus4", "7", "maj7" or "m7".
+///
+/// A root on it's own is a major chord.
+impl FromStr for Chord {
+    type Err = ChordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        // Offset of each natural within a row of the circle of fifths.
+        let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('F') => 0,
+            Some('C') => 1,
+            Some('G') => 2,
+            Some('D') => 3,
+            Some('A') => 4,
+            Some('E') => 5,
+            Some('B') => 6,
+            _ => return Err(ChordError::InvalidRoot(NoteParseError::InvalidLetter)),
+        };
+
+        let rest = chars.as_str();
+        let suffix = rest.trim_start_matches(['#', '♯', 'b', '♭']);
+        let accidentals = &rest[..rest.len() - suffix.len()];
+
+        let sharps = accidentals
+            .chars()
+            .filter(|c| matches!(c, '#' | '♯'))
+            .count() as i8;
+        let flats = accidentals
+            .chars()
+            .filter(|c| matches!(c, 'b' | '♭'))
+            .count() as i8;
+        if (sharps > 0 && flats > 0) || sharps > 3 || flats > 3 {
+            return Err(ChordError::InvalidRoot(NoteParseError::InvalidAccidentals));
+        }
+
+        let root = NamedPitch::from_fifths(letter + 7 * (sharps - flats + 3))
+            .ok_or(ChordError::InvalidRoot(NoteParseError::InvalidAccidentals))?;
+        let quality = ChordQuality::from_suffix(suffix).ok_or(ChordError::UnknownQuality)?;
+
+        Ok(Chord::new(root, quality))
+    }
+}
+
+/// A sequence of up to `N` chords, voiced with smooth voice leading.
+///
+/// The first chord is played in root position from the starting octave,
+/// each chord after it is voiced in whichever inversion and octave moves
+/// the notes the least from the chord before, keeping the progression in
+/// a compact range rather than jumping around with each root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordProgression<const N: usize> {
+    chords: Vec<Chord, N>,
+    /// The octave the first chord's root is played in.
+    octave: Octave,
+}
+
+impl<const N: usize> ChordProgression<N> {
+    /// Constructs an empty progression starting in the provided octave.
+    pub const fn new(octave: Octave) -> Self {
+        Self {
+            chords: Vec::new(),
+            octave,
+        }
+    }
+
+    /// Constructs a progression from chord symbols, e.g. `["C", "Am", "F", "G7"]`.
+    pub fn from_symbols(octave: Octave, symbols: &[&str]) -> Result<Self, ChordError> {
+        let mut progression = Self::new(octave);
+        for symbol in symbols {
+            progression.push(symbol.parse()?)?;
+        }
+
+        Ok(progression)
+    }
+
+    /// Adds a chord to the end of the progression.
+    pub fn push(&mut self, chord: Chord) -> Result<(), ChordError> {
+        self.chords
+            .push(chord)
+            .map_err(|_| ChordError::ProgressionFull)
+    }
+
+    /// Returns the chords in the progression.
+    pub fn chords(&self) -> &[Chord] {
+        &self.chords
+    }
+
+    /// Voices every chord in the progression, in order.
+    pub fn voicings(&self) -> Vec<Voicing, N> {
+        let mut voicings = Vec::new();
+        let mut previous: Option<Voicing> = None;
+
+        for chord in self.chords.iter() {
+            let voicing = match &previous {
+                None => chord.notes(self.octave),
+                Some(previous) => voice_lead(chord, previous),
+            };
+
+            // There's room for a voicing per chord.
+            let _ = voicings.push(voicing.clone());
+            previous = Some(voicing);
+        }
+
+        voicings
+    }
+}
+
+/// Voices a chord in the close position that moves the least from the previous voicing.
+fn voice_lead(chord: &Chord, previous: &Voicing) -> Voicing {
+    let Some(lowest) = previous.first().map(Note::semitones) else {
+        return Voicing::new();
+    };
+
+    // The chord in root position from C0, to be inverted and moved up.
+    let root = Note::new(chord.root, Octave::Zero);
+    let intervals = chord.quality.intervals();
+
+    let mut best: Option<(u32, Voicing)> = None;
+    for inversion in 0..intervals.len() {
+        // Moving the lower notes up an octave inverts the chord.
+        let mut offsets: Vec<i16, MAX_CHORD_NOTES> = intervals
+            .iter()
+            .enumerate()
+            .map(|(index, interval)| {
+                interval.semitones() as i16 + if index < inversion { 12 } else { 0 }
+            })
+            .collect();
+        offsets.rotate_left(inversion);
+
+        // Try the octaves that put the bass note closest to the previous bass.
+        let bass = root.semitones() + offsets[0];
+        let octave = (lowest - bass).div_euclid(12);
+        for shift in [octave, octave + 1] {
+            let notes: Option<Voicing> = offsets
+                .iter()
+                .map(|offset| root.transpose(shift * 12 + offset))
+                .collect();
+            let Some(notes) = notes else {
+                continue;
+            };
+
+            let cost = movement(previous, &notes);
+            if best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                best = Some((cost, notes));
+            }
+        }
+    }
+
+    best.map(|(_, notes)| notes).unwrap_or_default()
+}
+
+/// Returns how far in semitones each note of a voicing is from the nearest note of the previous.
+fn movement(previous: &Voicing, next: &Voicing) -> u32 {
+    next.iter()
+        .map(|note| {
+            previous
+                .iter()
+                .map(|from| (note.semitones() - from.semitones()).unsigned_abs() as u32)
+                .min()
+                .unwrap_or(0)
+        })
+        .sum()
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::note;
+    use pretty_assertions::assert_eq;
+
+    fn semitones(voicing: &Voicing) -> std::vec::Vec<i16> {
+        voicing.iter().map(Note::semitones).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        self::assert_eq!(
+            "C".parse(),
+            Ok(Chord::new(NamedPitch::C, ChordQuality::Major))
+        );
+        self::assert_eq!(
+            "F#m7".parse(),
+            Ok(Chord::new(NamedPitch::FSharp, ChordQuality::Minor7))
+        );
+        self::assert_eq!(
+            "Bbmaj7".parse(),
+            Ok(Chord::new(NamedPitch::BFlat, ChordQuality::Major7))
+        );
+        self::assert_eq!(
+            "H".parse::<Chord>(),
+            Err(ChordError::InvalidRoot(NoteParseError::InvalidLetter))
+        );
+        self::assert_eq!("Cxyz".parse::<Chord>(), Err(ChordError::UnknownQuality));
+    }
+
+    #[test]
+    fn test_notes() {
+        let chord: Chord = "G7".parse().unwrap();
+        self::assert_eq!(
+            semitones(&chord.notes(Octave::Four)),
+            [note::GFour, note::BFour, note::DFive, note::FFive].map(|note| note.semitones())
+        );
+    }
+
+    #[test]
+    fn test_voice_leading() {
+        let progression =
+            ChordProgression::<4>::from_symbols(Octave::Four, &["C", "F", "G"]).unwrap();
+        let voicings = progression.voicings();
+
+        let expected = [
+            [note::CFour, note::EFour, note::GFour],
+            [note::CFour, note::FFour, note::AFour],
+            [note::BThree, note::DFour, note::GFour],
+        ];
+        for (voicing, expected) in voicings.iter().zip(expected) {
+            self::assert_eq!(semitones(voicing), expected.map(|note| note.semitones()));
+        }
+
+        for voicing in voicings.iter() {
+            let span = voicing.last().unwrap().semitones() - voicing[0].semitones();
+            assert!(span < 12, "voicing spans {span} semitones");
+        }
+        for pair in voicings.windows(2) {
+            let moved = movement(&pair[0], &pair[1]);
+            assert!(moved <= 5, "moved {moved} semitones");
+        }
+    }
+
+    #[test]
+    fn test_progression_full() {
+        let mut progression = ChordProgression::<1>::new(Octave::Four);
+        progression.push("C".parse().unwrap()).unwrap();
+        self::assert_eq!(
+            progression.push("G".parse().unwrap()),
+            Err(ChordError::ProgressionFull)
+        );
+    }
+}
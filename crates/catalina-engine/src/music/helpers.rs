@@ -1,5 +1,7 @@
 //! Helper functions.
 
+use crate::{core::Hertz, music::note::Note};
+
 /**
    MIT License
 
@@ -34,3 +36,44 @@ pub fn mel(f: f32) -> f32 {
 pub fn inv_mel(m: f32) -> f32 {
     700f32 * (10f32.powf(m / 2595f32) - 1f32)
 }
+
+/// Maps a frequency to a position along a piano keyboard spanning `low` to `high`,
+/// i.e. for drawing a keyboard visualizer.
+///
+/// Returns 0.0 at the low note and 1.0 at the high note, spaced by
+/// log-frequency so every semitone is the same width. Frequencies
+/// outside of the range land below 0.0 or above 1.0.
+pub fn piano_position(frequency: Hertz, low: Note, high: Note) -> f32 {
+    let low = low.frequency().to_midi_float();
+    let high = high.frequency().to_midi_float();
+    if high <= low {
+        return 0.0;
+    }
+
+    (frequency.to_midi_float() - low) / (high - low)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::note;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_piano_position() {
+        // The full 88 key piano, A0 to C8.
+        let position = |frequency: Hertz| piano_position(frequency, note::AZero, note::CEight);
+
+        self::assert_eq!(position(note::AZero.frequency()), 0.0);
+        assert!((position(note::CEight.frequency()) - 1.0).abs() < 1e-5);
+
+        // An octave is 12 of the 87 semitones across the keyboard.
+        let octave = position(note::AOne.frequency());
+        assert!((octave - 12.0 / 87.0).abs() < 1e-5, "A1 is at {octave}");
+        assert!(position(Hertz(20.0)) < 0.0);
+
+        self::assert_eq!(piano_position(Hertz(440.0), note::AFour, note::AFour), 0.0);
+    }
+}
@@ -0,0 +1,40 @@
+//! A module for the [`Interval`] type, the distance between two notes.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The distance between two notes in semitones.
+///
+/// The common intervals are available as constants,
+/// i.e. [`Interval::PERFECT_FIFTH`], and can be added
+/// to a note with [`Note::add_interval`](crate::music::note::Note::add_interval).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash, Debug)]
+pub struct Interval(u8);
+
+impl Interval {
+    pub const UNISON: Interval = Interval(0);
+    pub const MINOR_SECOND: Interval = Interval(1);
+    pub const MAJOR_SECOND: Interval = Interval(2);
+    pub const MINOR_THIRD: Interval = Interval(3);
+    pub const MAJOR_THIRD: Interval = Interval(4);
+    pub const PERFECT_FOURTH: Interval = Interval(5);
+    pub const TRITONE: Interval = Interval(6);
+    pub const PERFECT_FIFTH: Interval = Interval(7);
+    pub const MINOR_SIXTH: Interval = Interval(8);
+    pub const MAJOR_SIXTH: Interval = Interval(9);
+    pub const MINOR_SEVENTH: Interval = Interval(10);
+    pub const MAJOR_SEVENTH: Interval = Interval(11);
+    pub const OCTAVE: Interval = Interval(12);
+
+    /// Constructs an interval spanning the provided amount of semitones.
+    pub const fn new(semitones: u8) -> Self {
+        Interval(semitones)
+    }
+
+    /// Returns the amount of semitones the interval spans.
+    pub const fn semitones(&self) -> u8 {
+        self.0
+    }
+}
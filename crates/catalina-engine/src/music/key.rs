@@ -0,0 +1,121 @@
+//! A module for the [`Key`] type.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::music::{
+    named_pitch::NamedPitch,
+    pitch::{HasPitch, Pitch},
+};
+
+/// The mode of a key.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum Mode {
+    Major,
+    Minor,
+}
+
+/// A musical key, the tonic and mode determine the key signature.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct Key {
+    tonic: NamedPitch,
+    mode: Mode,
+}
+
+impl Key {
+    /// Constructs a key from it's tonic and mode.
+    pub const fn new(tonic: NamedPitch, mode: Mode) -> Self {
+        Self { tonic, mode }
+    }
+
+    /// Returns the tonic of the key.
+    pub const fn tonic(&self) -> NamedPitch {
+        self.tonic
+    }
+
+    /// Returns the mode of the key.
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the amount of sharps (positive) or flats (negative) in the key signature.
+    pub const fn signature(&self) -> i8 {
+        // Named pitches are ordered by the circle of fifths, so the
+        // distance from C is the amount of sharps or flats.
+        let fifths = self.tonic as i8 - NamedPitch::C as i8;
+
+        match self.mode {
+            Mode::Major => fifths,
+            // Minor keys share the signature of their relative major, a minor third up.
+            Mode::Minor => fifths - 3,
+        }
+    }
+
+    /// Returns true if notes outside of the key should be spelled with sharps.
+    pub const fn prefers_sharps(&self) -> bool {
+        self.signature() >= 0
+    }
+
+    /// Returns the spelling of a pitch in the key.
+    ///
+    /// Pitches in the key are spelled with the letter of their scale
+    /// degree, other pitches with sharps or flats following the signature.
+    pub fn spell(&self, pitch: Pitch) -> NamedPitch {
+        // The seven notes of a key signature are adjacent in the circle
+        // of fifths, starting a fifth below the tonic of the major key.
+        let first = NamedPitch::C as i8 + self.signature() - 1;
+        let diatonic = (first..first + 7)
+            .filter_map(NamedPitch::from_fifths)
+            .find(|named_pitch| named_pitch.pitch() == pitch);
+
+        match diatonic {
+            Some(named_pitch) => named_pitch,
+            None if self.prefers_sharps() => sharp_spelling(pitch),
+            None => pitch.into(),
+        }
+    }
+}
+
+/// Spells a pitch using sharps instead of flats.
+fn sharp_spelling(pitch: Pitch) -> NamedPitch {
+    match pitch {
+        Pitch::DFlat => NamedPitch::CSharp,
+        Pitch::EFlat => NamedPitch::DSharp,
+        Pitch::GFlat => NamedPitch::FSharp,
+        Pitch::AFlat => NamedPitch::GSharp,
+        Pitch::BFlat => NamedPitch::ASharp,
+        _ => pitch.into(),
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_signature() {
+        self::assert_eq!(Key::new(NamedPitch::C, Mode::Major).signature(), 0);
+        self::assert_eq!(Key::new(NamedPitch::FSharp, Mode::Major).signature(), 6);
+        self::assert_eq!(Key::new(NamedPitch::DFlat, Mode::Major).signature(), -5);
+        self::assert_eq!(Key::new(NamedPitch::A, Mode::Minor).signature(), 0);
+        self::assert_eq!(Key::new(NamedPitch::E, Mode::Minor).signature(), 1);
+        self::assert_eq!(Key::new(NamedPitch::D, Mode::Minor).signature(), -1);
+    }
+
+    #[test]
+    fn test_spell() {
+        let e_minor = Key::new(NamedPitch::E, Mode::Minor);
+        self::assert_eq!(e_minor.spell(Pitch::GFlat), NamedPitch::FSharp);
+        self::assert_eq!(e_minor.spell(Pitch::BFlat), NamedPitch::ASharp);
+
+        let d_minor = Key::new(NamedPitch::D, Mode::Minor);
+        self::assert_eq!(d_minor.spell(Pitch::BFlat), NamedPitch::BFlat);
+        self::assert_eq!(d_minor.spell(Pitch::DFlat), NamedPitch::DFlat);
+    }
+}
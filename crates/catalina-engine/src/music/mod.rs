@@ -1,5 +1,12 @@
+pub mod chord;
 pub mod helpers;
+pub mod interval;
+pub mod key;
 pub mod named_pitch;
 pub mod note;
 pub mod octave;
 pub mod pitch;
+pub mod rhythm;
+pub mod scale;
+
+pub use scale::quantize;
@@ -54,6 +54,7 @@ pub trait HasLetter {
 /// enharmonic name (could share the same pitch with another).
 #[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Ord, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum NamedPitch {
     /// The pitch F triple flat.
@@ -233,7 +234,26 @@ impl HasLetter for NamedPitch {
 }
 
 impl NamedPitch {
-    fn static_name(&self) -> &'static str {
+    /// Returns the number of accidentals applied to the letter of the
+    /// named pitch, negative for flats and positive for sharps.
+    ///
+    /// For example, `FSharp` returns 1 and `BDoubleFlat` returns -2.
+    pub const fn accidentals(&self) -> i8 {
+        // The variants are grouped in sevens from triple flat to triple
+        // sharp, so the group index gives us the accidental count.
+        (*self as u8 / 7) as i8 - 3
+    }
+
+    /// Returns the named pitch at an index in the circle of fifths,
+    /// where 0 is F triple flat and 48 is B triple sharp.
+    pub(crate) fn from_fifths(index: i8) -> Option<NamedPitch> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| ALL_PITCHES.get(index))
+            .copied()
+    }
+
+    pub(crate) fn static_name(&self) -> &'static str {
         match self {
             NamedPitch::FTripleFlat => "F♭𝄫",
             NamedPitch::CTripleFlat => "C♭𝄫",
@@ -477,6 +497,17 @@ mod tests {
         self::assert_eq!(NamedPitch::A.named_pitch(), NamedPitch::A);
     }
 
+    #[test]
+    fn test_accidentals() {
+        self::assert_eq!(NamedPitch::FTripleFlat.accidentals(), -3);
+        self::assert_eq!(NamedPitch::BDoubleFlat.accidentals(), -2);
+        self::assert_eq!(NamedPitch::EFlat.accidentals(), -1);
+        self::assert_eq!(NamedPitch::C.accidentals(), 0);
+        self::assert_eq!(NamedPitch::FSharp.accidentals(), 1);
+        self::assert_eq!(NamedPitch::GDoubleSharp.accidentals(), 2);
+        self::assert_eq!(NamedPitch::BTripleSharp.accidentals(), 3);
+    }
+
     #[test]
     fn test_pitch_conversion() {
         self::assert_eq!(NamedPitch::from(Pitch::C), NamedPitch::C);
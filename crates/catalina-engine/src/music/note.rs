@@ -22,12 +22,19 @@
    OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
    SOFTWARE.
 */
+use core::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
 use crate::{
     core::Hertz,
     music::{
-        named_pitch::NamedPitch,
+        interval::Interval,
+        key::Key,
+        named_pitch::{HasLetter, NamedPitch},
         octave::ALL_OCTAVES,
-        pitch::{ALL_PITCHES, HasPitch, Pitch},
+        pitch::{ALL_PITCHES, HasFrequency, HasPitch, Pitch},
     },
 };
 
@@ -38,6 +45,12 @@ use super::octave::Octave;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// The MIDI note number of C0, MIDI notes count from C-1.
+const MIDI_C0: i16 = 12;
+
+/// The amount of semitones B15, the highest available note, is above C0.
+const HIGHEST_SEMITONE: i32 = 16 * 12 - 1;
+
 /// A note type.
 ///
 /// This is a pitch with an octave.
@@ -52,6 +65,65 @@ pub struct Note {
 }
 
 impl Note {
+    /// Constructs a note from a named pitch and an octave.
+    pub const fn new(named_pitch: NamedPitch, octave: Octave) -> Self {
+        Self {
+            named_pitch,
+            octave,
+        }
+    }
+
+    /// Constructs a note from the amount of semitones above C0.
+    ///
+    /// Accidentals are spelled using the [`NamedPitch`] conversion from [`Pitch`].
+    /// Returns `None` if the note would be outside of the available octaves.
+    pub fn from_semitones(semitones: i16) -> Option<Self> {
+        if semitones < 0 {
+            return None;
+        }
+
+        let pitch = Pitch::try_from((semitones % 12) as u8).ok()?;
+        let octave = Octave::try_from(u8::try_from(semitones / 12).ok()?).ok()?;
+
+        Some(Self::new(pitch.into(), octave))
+    }
+
+    /// Returns the note closest to a frequency, i.e. to key an atonal
+    /// frequency by the note it's nearest to.
+    ///
+    /// Returns `None` if the note would be outside of the available octaves.
+    pub fn nearest(frequency: Hertz) -> Option<Self> {
+        let midi = libm::roundf(frequency.to_midi_float());
+        if !midi.is_finite() {
+            return None;
+        }
+
+        Self::from_semitones((midi as i16).checked_sub(MIDI_C0)?)
+    }
+
+    /// Returns the note for a MIDI note number, where 60 is C4 and 69 is A4.
+    ///
+    /// Returns `None` for the notes below C0 (MIDI 12)
+    /// that aren't in the available octaves.
+    pub fn from_midi(midi: u8) -> Option<Self> {
+        Self::from_semitones((midi as i16).checked_sub(MIDI_C0)?)
+    }
+
+    /// Snaps a fractional MIDI note number to the nearest note, i.e. for
+    /// picking notes with a knob or slider. 60.0 is C4 and 69.0 is A4.
+    ///
+    /// Clamped to the MIDI range, notes below C0 (MIDI 12)
+    /// can't be represented so they clamp to C0.
+    pub fn from_midi_float(midi: f32) -> Self {
+        let midi = (libm::roundf(midi) as i16).clamp(MIDI_C0, 127);
+        Self::from_semitones(midi - MIDI_C0).expect("clamped to the available octaves")
+    }
+
+    /// Returns the named pitch of the note.
+    pub const fn named_pitch(&self) -> NamedPitch {
+        self.named_pitch
+    }
+
     /// Returns the octave of the note.
     pub const fn octave(&self) -> Octave {
         self.octave
@@ -62,29 +134,284 @@ impl Note {
         self.named_pitch.pitch()
     }
 
-    /// Returns the frequency of the note in hertz.
-    pub fn frequency(&self) -> Hertz {
-        let mut octave = self.octave();
-        let base_frequency = self.pitch().base_frequency();
-
+    /// Returns the amount of octaves the pitch wraps by when
+    /// it's accidentals cross the B to C boundary, i.e. B♯ or C♭.
+    const fn octave_shift(&self) -> i8 {
         match self.named_pitch {
             NamedPitch::ATripleSharp
             | NamedPitch::BTripleSharp
             | NamedPitch::BDoubleSharp
-            | NamedPitch::BSharp => {
-                octave += 1;
-            }
+            | NamedPitch::BSharp => 1,
             NamedPitch::DTripleFlat
             | NamedPitch::CTripleFlat
             | NamedPitch::CDoubleFlat
-            | NamedPitch::CFlat => {
-                octave -= 1;
+            | NamedPitch::CFlat => -1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the amount of semitones the note is above C0.
+    pub fn semitones(&self) -> i16 {
+        (self.octave as i16 + self.octave_shift() as i16) * 12 + self.pitch() as i16
+    }
+
+    /// Transposes the note by an amount of semitones, keeping it's spelling
+    /// for whole octaves and re-spelling it otherwise.
+    ///
+    /// Returns `None` if the note would be outside of the available octaves.
+    pub fn transpose(&self, semitones: i16) -> Option<Note> {
+        if semitones % 12 == 0 {
+            let octaves = i8::try_from(semitones / 12).ok()?;
+            let octave = self.octave.checked_add(octaves)?;
+
+            return Some(Self::new(self.named_pitch, octave));
+        }
+
+        Note::from_semitones(self.semitones().checked_add(semitones)?)
+    }
+
+    /// Transposes the note by an amount of semitones, like [`Note::transpose`].
+    ///
+    /// Returns `None` if the note would be outside of the available octaves.
+    pub fn transpose_checked(&self, semitones: i16) -> Option<Note> {
+        self.transpose(semitones)
+    }
+
+    /// Transposes the note by an amount of semitones, like [`Note::transpose`],
+    /// clamping to C0 or B15 when the note would be outside of the available octaves.
+    ///
+    /// Useful for arpeggiators and sequencers where a wide range
+    /// shouldn't silently drop the notes at the extremes.
+    pub fn transpose_saturating(&self, semitones: i16) -> Note {
+        self.transpose(semitones).unwrap_or_else(|| {
+            let target = (self.semitones() as i32 + semitones as i32).clamp(0, HIGHEST_SEMITONE);
+            Note::from_semitones(target as i16).expect("clamped to the available octaves")
+        })
+    }
+
+    /// Iterates over every chromatic note from `low` up to and including `high`.
+    ///
+    /// The notes in between are spelled like [`Note::from_semitones`],
+    /// while `low` and `high` keep their own spelling. Yields nothing
+    /// if `low` is above `high`.
+    pub fn range(low: Note, high: Note) -> impl Iterator<Item = Note> {
+        let (from, to) = (low.semitones(), high.semitones());
+
+        (from..=to).filter_map(move |semitones| match semitones {
+            _ if semitones == from => Some(low),
+            _ if semitones == to => Some(high),
+            _ => Note::from_semitones(semitones),
+        })
+    }
+
+    /// Returns the note the provided interval above this note.
+    ///
+    /// Returns `None` if the note would be outside of the available octaves.
+    pub fn add_interval(&self, interval: Interval) -> Option<Note> {
+        self.transpose(interval.semitones() as i16)
+    }
+
+    /// Returns the frequency of the note in hertz.
+    ///
+    /// Looked up from [`NOTE_FREQUENCIES`] so no `exp2f` is needed
+    /// at runtime, which is slow on MCUs without an FPU.
+    pub fn frequency(&self) -> Hertz {
+        match NOTE_FREQUENCIES.get(self.semitones() as usize) {
+            Some(frequency) => Hertz(*frequency),
+            // Spellings like B#15 land just outside of the table.
+            None => {
+                let octave = self.octave() + self.octave_shift();
+                self.pitch().frequency_in_octave(octave)
+            }
+        }
+    }
+
+    /// Returns the name of the note spelled as it would be in the provided key.
+    ///
+    /// Notes in the key use the key's spelling, i.e. E♯ in F♯ major, and
+    /// other notes use sharps or flats depending on the key signature.
+    pub fn spelling_in_key(&self, key: Key) -> &'static str {
+        key.spell(self.pitch()).static_name()
+    }
+
+    /// Formats the note into a fixed-capacity string without allocating, e.g. "C#4".
+    ///
+    /// Sharps are written as `#` and flats as `b` so the output stays plain
+    /// ASCII for embedded displays. The buffer is cleared before writing.
+    pub fn format_into(&self, buf: &mut heapless::String<8>) -> fmt::Result {
+        buf.clear();
+        buf.push_str(self.named_pitch.letter())
+            .map_err(|_| fmt::Error)?;
+
+        let accidentals = self.named_pitch.accidentals();
+        let symbol = if accidentals < 0 { 'b' } else { '#' };
+        for _ in 0..accidentals.unsigned_abs() {
+            buf.push(symbol).map_err(|_| fmt::Error)?;
+        }
+
+        write!(buf, "{}", self.octave as u8)
+    }
+}
+
+/// An error returned from parsing a [`Note`] from a string such as "C#4".
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NoteParseError {
+    /// The note didn't start with a letter from A to G.
+    InvalidLetter,
+    /// There were more than three sharps or flats, or they were mixed.
+    InvalidAccidentals,
+    /// The octave was missing or outside of 0 to 15.
+    InvalidOctave,
+}
+
+/// Parses the form written by [`Note::format_into`], e.g. "C#4", "Bb3" or "F##10".
+///
+/// The letter may be lower case, and `♯`/`♭` are accepted as well as `#`/`b`.
+impl FromStr for Note {
+    type Err = NoteParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        // Offset of each natural within a row of the circle of fifths.
+        let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('F') => 0,
+            Some('C') => 1,
+            Some('G') => 2,
+            Some('D') => 3,
+            Some('A') => 4,
+            Some('E') => 5,
+            Some('B') => 6,
+            _ => return Err(NoteParseError::InvalidLetter),
+        };
+
+        let rest = chars.as_str();
+        let octave_start = rest
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or(NoteParseError::InvalidOctave)?;
+        let (accidentals, octave) = rest.split_at(octave_start);
+
+        let mut sharps: i8 = 0;
+        let mut flats: i8 = 0;
+        for c in accidentals.chars() {
+            match c {
+                '#' | '♯' => sharps += 1,
+                'b' | '♭' => flats += 1,
+                _ => return Err(NoteParseError::InvalidAccidentals),
             }
-            _ => {}
+        }
+        if (sharps > 0 && flats > 0) || sharps > 3 || flats > 3 {
+            return Err(NoteParseError::InvalidAccidentals);
         }
 
-        // Not sure why we need the +1.0 on the end, but without it all the tuning was 1 octave off.
-        base_frequency * 2.0_f32.powf(octave as u8 as f32)
+        let named_pitch = NamedPitch::from_fifths(letter + 7 * (sharps - flats + 3))
+            .ok_or(NoteParseError::InvalidAccidentals)?;
+        let octave = octave
+            .parse::<u8>()
+            .ok()
+            .and_then(|octave| Octave::try_from(octave).ok())
+            .ok_or(NoteParseError::InvalidOctave)?;
+
+        Ok(Note::new(named_pitch, octave))
+    }
+}
+
+/// Snaps a fractional MIDI note number to the nearest note, like
+/// [`Note::from_midi_float`] but failing outside of the MIDI range
+/// rather than clamping.
+impl TryFrom<f32> for Note {
+    type Error = &'static str;
+
+    fn try_from(midi: f32) -> Result<Self, Self::Error> {
+        let midi = libm::roundf(midi);
+        if !(MIDI_C0 as f32..=127.0).contains(&midi) {
+            return Err("MIDI note out of range.");
+        }
+
+        Ok(Self::from_midi_float(midi))
+    }
+}
+
+impl TryFrom<&str> for Note {
+    type Error = NoteParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Serializes a [`Note`] as it's name, e.g. "C#4", instead of as a struct.
+///
+/// Use it on a field with `#[serde(with = "catalina_engine::music::note::as_str")]`,
+/// or wrap the note in a [`NoteName`] where there's no field to annotate.
+#[cfg(feature = "serde")]
+pub mod as_str {
+    use core::fmt;
+
+    use serde::{Deserializer, Serializer, de};
+
+    use super::Note;
+
+    pub fn serialize<S: Serializer>(note: &Note, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut buf = heapless::String::<8>::new();
+        note.format_into(&mut buf)
+            .map_err(|_| serde::ser::Error::custom("note name too long"))?;
+        serializer.serialize_str(&buf)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Note, D::Error> {
+        struct NoteVisitor;
+
+        impl de::Visitor<'_> for NoteVisitor {
+            type Value = Note;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a note name such as \"C#4\"")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Note, E> {
+                s.parse()
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+            }
+        }
+
+        deserializer.deserialize_str(NoteVisitor)
+    }
+}
+
+/// A [`Note`] that (de)serializes as it's name, e.g. "C#4".
+///
+/// See [`as_str`] for annotating a `Note` field directly.
+#[cfg(feature = "serde")]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct NoteName(pub Note);
+
+#[cfg(feature = "serde")]
+impl Serialize for NoteName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        as_str::serialize(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for NoteName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        as_str::deserialize(deserializer).map(NoteName)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Note> for NoteName {
+    fn from(note: Note) -> Self {
+        NoteName(note)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<NoteName> for Note {
+    fn from(name: NoteName) -> Self {
+        name.0
     }
 }
 
@@ -286,6 +613,25 @@ pub const BTripleSharp: Note = BTripleSharpFour;
 
 // Statics.
 
+/// The frequency in hertz of every note from C0 to B15, indexed by [`Note::semitones`].
+///
+/// Built at compile time from the same base frequencies as
+/// [`Pitch::frequency_in_octave`], so looking a note up gives
+/// exactly the frequency it would otherwise be computed as.
+pub const NOTE_FREQUENCIES: [f32; HIGHEST_SEMITONE as usize + 1] = {
+    let mut table = [0.0; HIGHEST_SEMITONE as usize + 1];
+
+    let mut semitones = 0;
+    while semitones < table.len() {
+        // Each octave doubles the frequency, which is exact in floating point.
+        let base = ALL_PITCHES[semitones % 12].base_frequency().0;
+        table[semitones] = base * (1u32 << (semitones / 12)) as f32;
+        semitones += 1;
+    }
+
+    table
+};
+
 #[cfg(feature = "std")]
 use std::sync::LazyLock;
 
@@ -317,3 +663,249 @@ pub static ALL_PITCH_NOTES: LazyLock<[Note; 192]> = LazyLock::new(|| {
 
 //     all_notes.try_into().unwrap()
 // });
+
+impl HasFrequency for Note {
+    fn frequency(&self) -> f32 {
+        Note::frequency(self).hertz()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_into() {
+        let mut buf = heapless::String::<8>::new();
+
+        CSharpFour.format_into(&mut buf).unwrap();
+        self::assert_eq!(buf.as_str(), "C#4");
+
+        BFlatThree.format_into(&mut buf).unwrap();
+        self::assert_eq!(buf.as_str(), "Bb3");
+
+        AFour.format_into(&mut buf).unwrap();
+        self::assert_eq!(buf.as_str(), "A4");
+
+        FDoubleSharpTen.format_into(&mut buf).unwrap();
+        self::assert_eq!(buf.as_str(), "F##10");
+
+        CTripleFlatZero.format_into(&mut buf).unwrap();
+        self::assert_eq!(buf.as_str(), "Cbbb0");
+    }
+
+    #[test]
+    fn test_from_str() {
+        self::assert_eq!("C#4".parse(), Ok(CSharpFour));
+        self::assert_eq!("bb3".parse(), Ok(BFlatThree));
+        self::assert_eq!("F♯♯10".parse(), Ok(FDoubleSharpTen));
+        self::assert_eq!(Note::try_from("Cbbb0"), Ok(CTripleFlatZero));
+
+        self::assert_eq!("H4".parse::<Note>(), Err(NoteParseError::InvalidLetter));
+        self::assert_eq!("".parse::<Note>(), Err(NoteParseError::InvalidLetter));
+        self::assert_eq!(
+            "C#b4".parse::<Note>(),
+            Err(NoteParseError::InvalidAccidentals)
+        );
+        self::assert_eq!(
+            "C####4".parse::<Note>(),
+            Err(NoteParseError::InvalidAccidentals)
+        );
+        self::assert_eq!("C#".parse::<Note>(), Err(NoteParseError::InvalidOctave));
+        self::assert_eq!("C16".parse::<Note>(), Err(NoteParseError::InvalidOctave));
+        self::assert_eq!("C4x".parse::<Note>(), Err(NoteParseError::InvalidOctave));
+
+        // Every note survives being written out and parsed back.
+        let mut buf = heapless::String::<8>::new();
+        for note in ALL_PITCH_NOTES.iter() {
+            note.format_into(&mut buf).unwrap();
+            self::assert_eq!(buf.parse(), Ok(*note));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_string_round_trip() {
+        use serde::de::{IntoDeserializer, value::Error};
+
+        let mut buf = heapless::String::<8>::new();
+        for note in [CSharpFour, BFlatThree, FDoubleSharpTen, CTripleFlatZero] {
+            note.format_into(&mut buf).unwrap();
+            let name = NoteName::deserialize(buf.as_str().into_deserializer());
+            self::assert_eq!(name.map(Note::from), Ok::<_, Error>(note));
+
+            let field = as_str::deserialize(buf.as_str().into_deserializer());
+            self::assert_eq!(field, Ok::<_, Error>(note));
+        }
+
+        let invalid: Result<NoteName, Error> = NoteName::deserialize("X9".into_deserializer());
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_frequency_table() {
+        for octave in ALL_OCTAVES.iter() {
+            for pitch in ALL_PITCHES.iter() {
+                let note = Note::new(pitch.into(), *octave);
+                let computed = pitch.frequency_in_octave(*octave).hertz();
+                let looked_up = NOTE_FREQUENCIES[note.semitones() as usize];
+
+                assert!(
+                    (looked_up - computed).abs() <= computed * 1e-6,
+                    "{note:?}: {looked_up} != {computed}"
+                );
+                self::assert_eq!(note.frequency(), Hertz(looked_up));
+            }
+        }
+
+        self::assert_eq!(AFour.frequency(), Hertz(440.0));
+    }
+
+    #[test]
+    fn test_from_midi() {
+        self::assert_eq!(Note::from_midi(60), Some(CFour));
+        self::assert_eq!(Note::from_midi(69), Some(AFour));
+        self::assert_eq!(Note::from_midi(12), Some(CZero));
+        self::assert_eq!(Note::from_midi(11), None);
+    }
+
+    #[test]
+    fn test_from_midi_float() {
+        self::assert_eq!(Note::from_midi_float(60.4), CFour);
+        self::assert_eq!(
+            Note::from_midi_float(60.6),
+            Note::from_semitones(49).unwrap()
+        );
+        self::assert_eq!(Note::from_midi_float(69.0), AFour);
+
+        // Clamped to the notes MIDI and the octaves can represent.
+        self::assert_eq!(Note::from_midi_float(-3.0), CZero);
+        self::assert_eq!(
+            Note::from_midi_float(500.0),
+            Note::from_semitones(115).unwrap()
+        );
+
+        self::assert_eq!(Note::try_from(60.4), Ok(CFour));
+        assert!(Note::try_from(128.0).is_err());
+        assert!(Note::try_from(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_nearest() {
+        self::assert_eq!(Note::nearest(Hertz(440.0)), Some(AFour));
+        self::assert_eq!(Note::nearest(Hertz(445.0)), Some(AFour));
+        self::assert_eq!(
+            Note::nearest(Hertz(460.0)),
+            Some(Note::from_semitones(58).unwrap())
+        );
+        self::assert_eq!(Note::nearest(CZero.frequency()), Some(CZero));
+        self::assert_eq!(Note::nearest(Hertz(1.0)), None);
+        self::assert_eq!(Note::nearest(Hertz(0.0)), None);
+    }
+
+    #[test]
+    fn test_semitones() {
+        self::assert_eq!(CZero.semitones(), 0);
+        self::assert_eq!(AFour.semitones(), 57);
+        self::assert_eq!(BSharpThree.semitones(), CFour.semitones());
+        self::assert_eq!(CFlatFour.semitones(), BThree.semitones());
+
+        self::assert_eq!(Note::from_semitones(57), Some(AFour));
+        self::assert_eq!(Note::from_semitones(49), Some(DFlatFour));
+        self::assert_eq!(Note::from_semitones(-1), None);
+        self::assert_eq!(Note::from_semitones(16 * 12), None);
+    }
+
+    #[test]
+    fn test_transpose() {
+        self::assert_eq!(CSharpFour.transpose(12), Some(CSharpFive));
+        self::assert_eq!(CSharpFour.transpose(-24), Some(CSharpTwo));
+        self::assert_eq!(CFour.transpose(7), Some(GFour));
+        self::assert_eq!(
+            Note::new(NamedPitch::C, Octave::Fifteen).transpose(12),
+            None
+        );
+        self::assert_eq!(CZero.transpose(-1), None);
+    }
+
+    #[test]
+    fn test_transpose_saturating() {
+        let b_fifteen = Note::new(NamedPitch::B, Octave::Fifteen);
+        let a_fifteen = Note::new(NamedPitch::A, Octave::Fifteen);
+
+        self::assert_eq!(
+            a_fifteen.transpose_saturating(1),
+            Note::new(NamedPitch::BFlat, Octave::Fifteen)
+        );
+        self::assert_eq!(a_fifteen.transpose_saturating(3), b_fifteen);
+        self::assert_eq!(b_fifteen.transpose_saturating(1), b_fifteen);
+        self::assert_eq!(b_fifteen.transpose_saturating(i16::MAX), b_fifteen);
+        self::assert_eq!(
+            Note::new(NamedPitch::C, Octave::Fifteen).transpose_saturating(12),
+            b_fifteen
+        );
+
+        self::assert_eq!(DZero.transpose_saturating(-1), DFlatZero);
+        self::assert_eq!(DZero.transpose_saturating(-5), CZero);
+        self::assert_eq!(CZero.transpose_saturating(-12), CZero);
+        self::assert_eq!(CZero.transpose_saturating(i16::MIN), CZero);
+
+        // In range it's the same as transposing.
+        self::assert_eq!(CSharpFour.transpose_saturating(12), CSharpFive);
+        self::assert_eq!(CSharpFour.transpose_checked(12), Some(CSharpFive));
+        self::assert_eq!(b_fifteen.transpose_checked(1), None);
+        self::assert_eq!(CZero.transpose_checked(-1), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let notes: Vec<Note> = Note::range(CFour, CFive).collect();
+        self::assert_eq!(notes.len(), 13);
+        self::assert_eq!(notes[0], CFour);
+        self::assert_eq!(notes[1], DFlatFour);
+        self::assert_eq!(notes[12], CFive);
+
+        // Endpoints keep their spelling.
+        let notes: Vec<Note> = Note::range(CSharpFour, DSharpFour).collect();
+        self::assert_eq!(notes, [CSharpFour, DFour, DSharpFour]);
+
+        self::assert_eq!(Note::range(CFour, CFour).count(), 1);
+        self::assert_eq!(Note::range(CFive, CFour).count(), 0);
+    }
+
+    #[test]
+    fn test_add_interval() {
+        self::assert_eq!(CFour.add_interval(Interval::PERFECT_FIFTH), Some(GFour));
+        self::assert_eq!(CFour.add_interval(Interval::OCTAVE), Some(CFive));
+        self::assert_eq!(AFour.add_interval(Interval::MINOR_THIRD), Some(CFive));
+        self::assert_eq!(CFour.add_interval(Interval::UNISON), Some(CFour));
+        self::assert_eq!(
+            Note::new(NamedPitch::C, Octave::Fifteen).add_interval(Interval::OCTAVE),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spelling_in_key() {
+        use crate::music::key::Mode;
+
+        let f_sharp_major = Key::new(NamedPitch::FSharp, Mode::Major);
+        self::assert_eq!(GFlatFour.spelling_in_key(f_sharp_major), "F♯");
+        self::assert_eq!(DFlatFour.spelling_in_key(f_sharp_major), "C♯");
+        self::assert_eq!(EFlatFour.spelling_in_key(f_sharp_major), "D♯");
+        self::assert_eq!(AFlatFour.spelling_in_key(f_sharp_major), "G♯");
+        self::assert_eq!(BFlatFour.spelling_in_key(f_sharp_major), "A♯");
+        self::assert_eq!(FFour.spelling_in_key(f_sharp_major), "E♯");
+
+        let d_flat_major = Key::new(NamedPitch::DFlat, Mode::Major);
+        self::assert_eq!(CSharpFour.spelling_in_key(d_flat_major), "D♭");
+        self::assert_eq!(DSharpFour.spelling_in_key(d_flat_major), "E♭");
+        self::assert_eq!(FSharpFour.spelling_in_key(d_flat_major), "G♭");
+        self::assert_eq!(GSharpFour.spelling_in_key(d_flat_major), "A♭");
+        self::assert_eq!(ASharpFour.spelling_in_key(d_flat_major), "B♭");
+        self::assert_eq!(BFour.spelling_in_key(d_flat_major), "B");
+    }
+}
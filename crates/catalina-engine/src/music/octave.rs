@@ -98,6 +98,60 @@ impl Octave {
             Octave::Fifteen => "15",
         }
     }
+
+    /// Returns the octave with the provided number, or `None` if it's above 15.
+    ///
+    /// Matches on the number rather than transmuting so that
+    /// it stays sound if the variants of the enum ever change.
+    const fn from_number(number: u8) -> Option<Octave> {
+        Some(match number {
+            0 => Octave::Zero,
+            1 => Octave::One,
+            2 => Octave::Two,
+            3 => Octave::Three,
+            4 => Octave::Four,
+            5 => Octave::Five,
+            6 => Octave::Six,
+            7 => Octave::Seven,
+            8 => Octave::Eight,
+            9 => Octave::Nine,
+            10 => Octave::Ten,
+            11 => Octave::Eleven,
+            12 => Octave::Twelve,
+            13 => Octave::Thirteen,
+            14 => Octave::Fourteen,
+            15 => Octave::Fifteen,
+            _ => return None,
+        })
+    }
+
+    /// Adds an amount of octaves, returning `None` instead of
+    /// panicking if the result is outside of octaves 0 to 15.
+    pub fn checked_add(self, rhs: i8) -> Option<Octave> {
+        let new_octave = (self as i8).checked_add(rhs)?;
+
+        u8::try_from(new_octave)
+            .ok()
+            .and_then(|octave| Octave::try_from(octave).ok())
+    }
+
+    /// Subtracts an amount of octaves, returning `None` instead of
+    /// panicking if the result is outside of octaves 0 to 15.
+    pub fn checked_sub(self, rhs: i8) -> Option<Octave> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    /// Adds an amount of octaves, clamping the result to octaves 0 to 15.
+    pub fn saturating_add(self, rhs: i8) -> Octave {
+        let new_octave = (self as i8).saturating_add(rhs).clamp(0, 15);
+
+        Octave::from_number(new_octave as u8).expect("clamped to the range of octaves")
+    }
+
+    /// Subtracts an amount of octaves, clamping the result to octaves 0 to 15.
+    pub fn saturating_sub(self, rhs: i8) -> Octave {
+        self.saturating_add(rhs.saturating_neg())
+    }
 }
 
 impl Add for Octave {
@@ -106,10 +160,7 @@ impl Add for Octave {
     fn add(self, rhs: Self) -> Self::Output {
         let new_octave = self as u8 + rhs as u8;
 
-        assert!(new_octave <= 15, "Octave overflow");
-
-        // SAFETY: The new octave is guaranteed to be less than or equal to 15.
-        unsafe { mem::transmute(new_octave) }
+        Octave::from_number(new_octave).expect("Octave overflow")
     }
 }
 
@@ -121,10 +172,7 @@ impl Sub for Octave {
             .checked_sub(rhs as u8)
             .expect("Octave underflow.");
 
-        assert!(new_octave <= 15, "Octave overflow");
-
-        // SAFETY: The new octave is guaranteed to be less than or equal to 15.
-        unsafe { mem::transmute(new_octave) }
+        Octave::from_number(new_octave).expect("Octave overflow")
     }
 }
 
@@ -132,12 +180,7 @@ impl TryFrom<u8> for Octave {
     type Error = &'static str;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if value > 15 {
-            Err("Octave overflow.")
-        } else {
-            // SAFETY: The new octave is guaranteed to be less than or equal to 15.
-            Ok(unsafe { mem::transmute::<u8, Octave>(value) })
-        }
+        Octave::from_number(value).ok_or("Octave overflow.")
     }
 }
 
@@ -153,8 +196,7 @@ impl Add<i8> for Octave {
             panic!("Octave underflow.");
         }
 
-        // SAFETY: The new octave is guaranteed to be less than or equal to 15.
-        unsafe { mem::transmute(new_octave) }
+        Octave::from_number(new_octave as u8).expect("Octave overflow.")
     }
 }
 
@@ -250,6 +292,23 @@ mod tests {
         let _ = Octave::Zero - 1;
     }
 
+    #[test]
+    fn test_try_from_u8() {
+        for value in 0..=16u8 {
+            match Octave::try_from(value) {
+                Ok(octave) => {
+                    self::assert_eq!(octave as u8, value);
+                    self::assert_eq!(octave, ALL_OCTAVES[value as usize]);
+                }
+                Err(error) => {
+                    self::assert_eq!(value, 16);
+                    self::assert_eq!(error, "Octave overflow.");
+                }
+            }
+        }
+        self::assert_eq!(Octave::try_from(u8::MAX), Err("Octave overflow."));
+    }
+
     #[test]
     fn test_add_assign_self() {
         let mut a = Octave::Four;
@@ -284,4 +343,25 @@ mod tests {
             "0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15"
         );
     }
+
+    #[test]
+    fn test_checked() {
+        self::assert_eq!(Octave::Four.checked_add(2), Some(Octave::Six));
+        self::assert_eq!(Octave::Fifteen.checked_add(1), None);
+        self::assert_eq!(Octave::Zero.checked_add(-1), None);
+        self::assert_eq!(Octave::Four.checked_sub(4), Some(Octave::Zero));
+        self::assert_eq!(Octave::Zero.checked_sub(1), None);
+        self::assert_eq!(Octave::Fifteen.checked_sub(-1), None);
+        self::assert_eq!(Octave::Four.checked_sub(i8::MIN), None);
+    }
+
+    #[test]
+    fn test_saturating() {
+        self::assert_eq!(Octave::Four.saturating_add(2), Octave::Six);
+        self::assert_eq!(Octave::Fifteen.saturating_add(1), Octave::Fifteen);
+        self::assert_eq!(Octave::Four.saturating_add(i8::MAX), Octave::Fifteen);
+        self::assert_eq!(Octave::Zero.saturating_sub(1), Octave::Zero);
+        self::assert_eq!(Octave::Four.saturating_sub(i8::MIN), Octave::Fifteen);
+        self::assert_eq!(Octave::Four.saturating_add(-100), Octave::Zero);
+    }
 }
@@ -1,6 +1,6 @@
 //! A module for the [`Pitch`] enum.
 
-use crate::core::Hertz;
+use crate::{core::Hertz, music::octave::Octave};
 
 /**
    MIT License
@@ -27,7 +27,10 @@ use crate::core::Hertz;
    SOFTWARE.
 */
 // Traits.
-use super::helpers::mel;
+use super::{
+    helpers::{mel, piano_position},
+    note::Note,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -70,6 +73,12 @@ pub trait HasFrequency {
             frequency * (1.0 + 1.0 / 16.8196 / 8.0),
         )
     }
+
+    /// Returns the position of the frequency along a piano keyboard spanning
+    /// `low` to `high`, from 0.0 to 1.0, see [`piano_position`].
+    fn piano_position(&self, low: Note, high: Note) -> f32 {
+        piano_position(Hertz(self.frequency()), low, high)
+    }
 }
 
 /// A trait for types that have a mel property.
@@ -136,6 +145,31 @@ impl Pitch {
             Pitch::B => 30.87,
         })
     }
+
+    /// Returns the frequency of the pitch in the provided octave, the
+    /// octave 0 [`Pitch::base_frequency`] doubled once per octave.
+    pub fn frequency_in_octave(&self, octave: Octave) -> Hertz {
+        self.base_frequency() * libm::exp2f(octave as u8 as f32)
+    }
+
+    /// Returns the pitch a semitone up, and true if it
+    /// wrapped around from B into the next octave.
+    pub fn next(self) -> (Pitch, bool) {
+        let index = self as usize + 1;
+        (ALL_PITCHES[index % 12], index == 12)
+    }
+
+    /// Returns the pitch a semitone down, and true if it
+    /// wrapped around from C into the previous octave.
+    pub fn prev(self) -> (Pitch, bool) {
+        let index = self as usize + 11;
+        (ALL_PITCHES[index % 12], self == Pitch::C)
+    }
+
+    /// Iterates over the twelve pitches of the chromatic scale, starting from this pitch.
+    pub fn chromatic(self) -> impl Iterator<Item = Pitch> {
+        (0..12).map(move |offset| ALL_PITCHES[(self as usize + offset) % 12])
+    }
 }
 
 // impl HasBaseFrequency for Pitch {
@@ -215,4 +249,29 @@ mod tests {
         self::assert_eq!(Pitch::G.pitch(), Pitch::G);
         self::assert_eq!(Pitch::G.base_frequency().hertz(), 24.50);
     }
+
+    #[test]
+    fn test_frequency_in_octave() {
+        self::assert_eq!(Pitch::A.frequency_in_octave(Octave::Four), Hertz(440.0));
+        self::assert_eq!(Pitch::A.frequency_in_octave(Octave::Zero), Hertz(27.5));
+        assert!((Pitch::C.frequency_in_octave(Octave::Four).hertz() - 261.63).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_next_prev() {
+        self::assert_eq!(Pitch::C.next(), (Pitch::DFlat, false));
+        self::assert_eq!(Pitch::B.next(), (Pitch::C, true));
+        self::assert_eq!(Pitch::D.prev(), (Pitch::DFlat, false));
+        self::assert_eq!(Pitch::C.prev(), (Pitch::B, true));
+    }
+
+    #[test]
+    fn test_chromatic() {
+        let mut pitches = Pitch::A.chromatic();
+        self::assert_eq!(pitches.next(), Some(Pitch::A));
+        self::assert_eq!(pitches.next(), Some(Pitch::BFlat));
+        self::assert_eq!(pitches.next(), Some(Pitch::B));
+        self::assert_eq!(pitches.next(), Some(Pitch::C));
+        self::assert_eq!(pitches.count(), 8);
+    }
 }
@@ -0,0 +1,155 @@
+//! A module for note values, the musical lengths of notes.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The length of a note as a division of a whole note.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum Division {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    ThirtySecond,
+}
+
+/// The musical length of a note, i.e. a dotted quarter or an eighth note triplet.
+///
+/// Converts to a length in samples at a tempo, or to a count of
+/// steps or ticks at a resolution, so the length of a sequenced
+/// note can be programmed musically.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum NoteValue {
+    /// The plain length of the division.
+    Straight(Division),
+    /// One and a half times the length of the division.
+    Dotted(Division),
+    /// Two thirds the length of the division, three fit in the space of two.
+    Triplet(Division),
+}
+
+/// The meter of a piece, i.e. 4/4 or 6/8.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct TimeSignature {
+    /// The amount of beats in a bar, the top number.
+    pub beats: u8,
+    /// The note value of a beat as a division of a whole note, the bottom number.
+    pub note_value: u8,
+}
+
+impl TimeSignature {
+    /// Common time, four quarter notes in a bar.
+    pub const COMMON: TimeSignature = TimeSignature::new(4, 4);
+
+    pub const fn new(beats: u8, note_value: u8) -> Self {
+        Self { beats, note_value }
+    }
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TimeSignature::COMMON
+    }
+}
+
+/// The resolution note values are counted in internally.
+///
+/// 48 units a beat divides evenly for every dotted and
+/// triplet value down to thirty-second notes.
+const UNITS_PER_BEAT: u32 = 48;
+
+impl Division {
+    /// Returns the length of the division in [`UNITS_PER_BEAT`].
+    const fn units(self) -> u32 {
+        match self {
+            Division::Whole => UNITS_PER_BEAT * 4,
+            Division::Half => UNITS_PER_BEAT * 2,
+            Division::Quarter => UNITS_PER_BEAT,
+            Division::Eighth => UNITS_PER_BEAT / 2,
+            Division::Sixteenth => UNITS_PER_BEAT / 4,
+            Division::ThirtySecond => UNITS_PER_BEAT / 8,
+        }
+    }
+}
+
+impl NoteValue {
+    /// Returns the length of the note value in [`UNITS_PER_BEAT`].
+    const fn units(self) -> u32 {
+        match self {
+            NoteValue::Straight(division) => division.units(),
+            NoteValue::Dotted(division) => division.units() * 3 / 2,
+            NoteValue::Triplet(division) => division.units() * 2 / 3,
+        }
+    }
+
+    /// Returns the length of the note value in beats (quarter notes).
+    pub fn beats(self) -> f64 {
+        self.units() as f64 / UNITS_PER_BEAT as f64
+    }
+
+    /// Returns the length of the note value in samples at a tempo, rounded to the nearest sample.
+    pub fn to_samples(self, bpm: f32, sample_rate: usize) -> u64 {
+        let samples_per_beat = sample_rate as f64 * 60.0 / bpm as f64;
+        libm::round(self.beats() * samples_per_beat) as u64
+    }
+
+    /// Returns the length of the note value in steps or ticks at a
+    /// resolution per beat, rounded to the nearest step.
+    ///
+    /// Pass a PPQN such as 96 for ticks, or the transport's steps
+    /// per beat for the length of a sequenced note in steps.
+    pub fn to_steps(self, ppqn: u32) -> u32 {
+        (self.units() * ppqn + UNITS_PER_BEAT / 2) / UNITS_PER_BEAT
+    }
+}
+
+impl From<Division> for NoteValue {
+    fn from(division: Division) -> Self {
+        NoteValue::Straight(division)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_to_samples() {
+        let quarter = NoteValue::Straight(Division::Quarter);
+        self::assert_eq!(quarter.to_samples(120.0, 44_100), 22_050);
+        self::assert_eq!(
+            NoteValue::Dotted(Division::Quarter).to_samples(120.0, 44_100),
+            33_075
+        );
+        self::assert_eq!(
+            NoteValue::Triplet(Division::Quarter).to_samples(120.0, 44_100),
+            14_700
+        );
+        self::assert_eq!(
+            NoteValue::from(Division::Whole).to_samples(120.0, 44_100),
+            88_200
+        );
+    }
+
+    #[test]
+    fn test_to_steps() {
+        self::assert_eq!(NoteValue::Straight(Division::Quarter).to_steps(96), 96);
+        self::assert_eq!(NoteValue::Dotted(Division::Eighth).to_steps(96), 72);
+        self::assert_eq!(NoteValue::Triplet(Division::Eighth).to_steps(96), 32);
+        self::assert_eq!(NoteValue::Triplet(Division::ThirtySecond).to_steps(96), 8);
+
+        // At 4 steps a beat a sixteenth note is a single step.
+        self::assert_eq!(NoteValue::Straight(Division::Sixteenth).to_steps(4), 1);
+        self::assert_eq!(NoteValue::Straight(Division::Half).to_steps(4), 8);
+    }
+}
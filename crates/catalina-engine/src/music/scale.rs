@@ -0,0 +1,129 @@
+//! A module for the [`Scale`] enum and quantizing frequencies to a scale.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::Hertz,
+    music::{
+        note::{self, Note},
+        pitch::Pitch,
+    },
+};
+
+/// An enum representing a musical scale.
+///
+/// Scales are described by the intervals of their
+/// degrees in semitones above the root of the scale.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum Scale {
+    /// All twelve semitones.
+    Chromatic,
+    /// The major (Ionian) scale.
+    Major,
+    /// The natural minor (Aeolian) scale.
+    NaturalMinor,
+    /// The harmonic minor scale.
+    HarmonicMinor,
+    /// The major pentatonic scale.
+    MajorPentatonic,
+    /// The minor pentatonic scale.
+    MinorPentatonic,
+}
+
+impl Scale {
+    /// Returns the intervals of the scale degrees in semitones above the root.
+    pub const fn intervals(&self) -> &'static [u8] {
+        match self {
+            Scale::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+        }
+    }
+
+    /// Returns true if the pitch is in the scale starting at the provided root.
+    pub fn contains(&self, root: Pitch, pitch: Pitch) -> bool {
+        let interval = (pitch as u8 + 12 - root as u8) % 12;
+        self.intervals().contains(&interval)
+    }
+}
+
+/// Quantizes a frequency to the nearest note of a scale.
+///
+/// Only the pitch of the root is used, the octave of the returned
+/// note is picked from the frequency. Distances are measured in
+/// cents so that notes are equally far apart in every octave.
+pub fn quantize(frequency: Hertz, scale: &Scale, root: Note) -> Note {
+    let lowest = note::CZero.frequency().hertz();
+    // B15, the highest note that can be represented.
+    let highest = 16 * 12 - 1;
+
+    // The nearest chromatic note, the closest scale note is at most a few semitones away.
+    let semitones = 12.0 * libm::log2f(frequency.hertz().max(lowest) / lowest);
+    let nearest = (libm::roundf(semitones) as i16).clamp(0, highest);
+
+    let mut closest: Option<(Note, f32)> = None;
+    for offset in -6..=6 {
+        let Some(candidate) = Note::from_semitones(nearest + offset) else {
+            continue;
+        };
+
+        if !scale.contains(root.pitch(), candidate.pitch()) {
+            continue;
+        }
+
+        let distance = libm::fabsf(libm::log2f(
+            frequency.hertz() / candidate.frequency().hertz(),
+        ));
+        if closest.is_none_or(|(_, closest)| distance < closest) {
+            closest = Some((candidate, distance));
+        }
+    }
+
+    // Every scale has a note within half an octave, unless the
+    // frequency is clamped to the very edge of the note range.
+    closest.map(|(note, _)| note).unwrap_or(root)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_contains() {
+        assert!(Scale::Major.contains(Pitch::C, Pitch::E));
+        assert!(!Scale::Major.contains(Pitch::C, Pitch::EFlat));
+        assert!(Scale::Major.contains(Pitch::D, Pitch::GFlat));
+        assert!(Scale::NaturalMinor.contains(Pitch::A, Pitch::C));
+    }
+
+    #[test]
+    fn test_quantize_between_c_and_d() {
+        // C4 is ~261.6Hz and D4 is ~293.7Hz, the midpoint in cents is ~277.2Hz.
+        self::assert_eq!(
+            quantize(Hertz(270.0), &Scale::Major, note::CFour),
+            note::CFour
+        );
+        self::assert_eq!(
+            quantize(Hertz(285.0), &Scale::Major, note::CFour),
+            note::DFour
+        );
+    }
+
+    #[test]
+    fn test_quantize_skips_gaps() {
+        // F isn't in the pentatonic scale, E is a semitone away and G is two.
+        self::assert_eq!(
+            quantize(note::FFour.frequency(), &Scale::MajorPentatonic, note::CTwo),
+            note::EFour
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Clock sources that tell the [`super::transport::Transport`] what time it is.
+
+/// A source of time for the transport, measured in samples.
+///
+/// Abstracting the clock lets the transport be driven by the audio
+/// engine while rendering, or stepped explicitly in tests.
+pub trait Clock {
+    /// Returns the current position of the clock in samples.
+    fn now(&self) -> u64;
+}
+
+/// A clock that counts the samples rendered by the audio engine.
+///
+/// Call [`SampleClock::advance`] after each rendered block.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SampleClock {
+    position: u64,
+}
+
+impl SampleClock {
+    pub const fn new() -> Self {
+        Self { position: 0 }
+    }
+
+    /// Advances the clock by the amount of frames that were rendered.
+    #[inline]
+    pub fn advance(&mut self, frames: usize) {
+        self.position += frames as u64;
+    }
+}
+
+impl Clock for SampleClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        self.position
+    }
+}
+
+/// A clock that only moves when told to, used to step
+/// the transport deterministically in tests.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ManualClock {
+    position: u64,
+}
+
+impl ManualClock {
+    pub const fn new() -> Self {
+        Self { position: 0 }
+    }
+
+    /// Moves the clock forward by an explicit amount of samples.
+    #[inline]
+    pub fn advance(&mut self, samples: u64) {
+        self.position += samples;
+    }
+
+    /// Jumps the clock to an explicit sample position.
+    #[inline]
+    pub fn set(&mut self, position: u64) {
+        self.position = position;
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        self.position
+    }
+}
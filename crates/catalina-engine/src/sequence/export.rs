@@ -0,0 +1,224 @@
+//! Flattens a [`Project`] into a list of timed note events for use
+//! by external tools, without depending on a MIDI file library.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    music::note::Note,
+    sequence::{Project, clock::ManualClock, transport::Transport},
+};
+
+/// What happens at a [`TimedEvent`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    NoteOn { note: Note, velocity: u8 },
+    NoteOff { note: Note },
+}
+
+/// A note event at an absolute position from the start of the project.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// The position of the event in samples, including any micro-timing.
+    pub sample: u64,
+    /// The step the event falls on, counted across all the patterns.
+    pub step: u64,
+    /// The index of the track within the pattern the event came from.
+    pub track: usize,
+    pub kind: EventKind,
+}
+
+/// Exports every note in the project as note on and off events, sorted by time.
+///
+/// Patterns are laid out one after the other, each lasting as long as it's
+/// longest track. Steps are timed the same way as the [`Transport`] plays
+/// them, including the project's swing and micro-timing, and note offs are
+/// placed `length` steps after their note on. At the same sample note offs
+/// come before note ons, so a note retriggered on the next step is released
+/// first.
+pub fn to_events<const PATTERNS: usize, const TRACKS: usize, const STEPS: usize>(
+    project: &Project<PATTERNS, TRACKS, STEPS>,
+    bpm: f32,
+    sample_rate: usize,
+) -> Vec<TimedEvent> {
+    let mut transport = Transport::new(ManualClock::new(), sample_rate, bpm);
+    transport.set_swing(project.swing());
+
+    let mut events = Vec::new();
+    let mut pattern_start: u64 = 0;
+    for (pattern_index, pattern) in project.patterns.iter().enumerate() {
+        let Some(pattern) = pattern else {
+            continue;
+        };
+        let transpose = project.pattern_transpose(pattern_index).unwrap_or(0);
+
+        for (track_index, track) in pattern.iter_active() {
+            for (step_index, step) in track.iter_active() {
+                let on_step = pattern_start + step_index as u64;
+                for note in step.notes() {
+                    let Some(pitch) = project.transpose_policy().apply(note.note(), transpose)
+                    else {
+                        continue;
+                    };
+                    let off_step = on_step + note.length() as u64;
+
+                    events.push(TimedEvent {
+                        sample: transport.nudged_step_position(on_step, step.micro_timing()),
+                        step: on_step,
+                        track: track_index,
+                        kind: EventKind::NoteOn {
+                            note: pitch,
+                            velocity: note.velocity(),
+                        },
+                    });
+                    events.push(TimedEvent {
+                        sample: transport.nudged_step_position(off_step, step.micro_timing()),
+                        step: off_step,
+                        track: track_index,
+                        kind: EventKind::NoteOff { note: pitch },
+                    });
+                }
+            }
+        }
+
+        pattern_start += pattern.length();
+    }
+
+    // The sort is stable, so events at the same time keep the order they were added in.
+    events.sort_by_key(|event| (event.sample, matches!(event.kind, EventKind::NoteOn { .. })));
+    events
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        music::note,
+        sequence::{
+            TransposePolicy,
+            pattern::{self, Pattern, Step, Track},
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_one_note_pattern() {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(note::AFour, 2, 100))
+            .unwrap();
+        let mut track = Track::<16>::new();
+        track.set_step(4, step).unwrap();
+        let mut pattern = Pattern::<1, 16>::new();
+        pattern.set_track(0, track).unwrap();
+
+        let mut project = Project::<2, 1, 16>::new();
+        project.push_pattern(pattern).unwrap();
+
+        // 120 BPM at 48kHz is 24,000 samples a beat, 6,000 a step.
+        let events = to_events(&project, 120.0, 48_000);
+        self::assert_eq!(
+            events,
+            [
+                TimedEvent {
+                    sample: 24_000,
+                    step: 4,
+                    track: 0,
+                    kind: EventKind::NoteOn {
+                        note: note::AFour,
+                        velocity: 100
+                    },
+                },
+                TimedEvent {
+                    sample: 36_000,
+                    step: 6,
+                    track: 0,
+                    kind: EventKind::NoteOff { note: note::AFour },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swung_project() {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(note::AFour, 1, 100))
+            .unwrap();
+        let mut track = Track::<4>::new();
+        track.set_step(1, step).unwrap();
+        let mut pattern = Pattern::<1, 4>::new();
+        pattern.set_track(0, track).unwrap();
+
+        let mut project = Project::<1, 1, 4>::new();
+        project.push_pattern(pattern).unwrap();
+        project.set_swing(1.0);
+
+        // Full swing plays the odd steps a third of a step late.
+        let samples: Vec<u64> = to_events(&project, 120.0, 48_000)
+            .iter()
+            .map(|event| event.sample)
+            .collect();
+        self::assert_eq!(samples, [8_000, 12_000]);
+    }
+
+    #[test]
+    fn test_patterns_follow_each_other() {
+        let mut project = Project::<2, 1, 4>::new();
+        for pitch in [note::CFour, note::CFour] {
+            let mut step = Step::new();
+            step.set_note(0, pattern::Note::new(pitch, 4, 100)).unwrap();
+            let mut track = Track::<4>::new();
+            track.set_step(0, step).unwrap();
+            let mut pattern = Pattern::<1, 4>::new();
+            pattern.set_track(0, track).unwrap();
+            project.push_pattern(pattern).unwrap();
+        }
+
+        let events = to_events(&project, 120.0, 48_000);
+        let kinds: Vec<(u64, bool)> = events
+            .iter()
+            .map(|event| (event.step, matches!(event.kind, EventKind::NoteOn { .. })))
+            .collect();
+
+        // The first note is released before the second pattern retriggers it.
+        self::assert_eq!(kinds, [(0, true), (4, false), (4, true), (8, false)]);
+    }
+
+    #[test]
+    fn test_transposed_project() {
+        let mut project = Project::<2, 1, 4>::new();
+        for pitch in [note::CFour, note::EFour] {
+            let mut step = Step::new();
+            step.set_note(0, pattern::Note::new(pitch, 1, 100)).unwrap();
+            let mut track = Track::<4>::new();
+            track.set_step(0, step).unwrap();
+            let mut pattern = Pattern::<1, 4>::new();
+            pattern.set_track(0, track).unwrap();
+            project.push_pattern(pattern).unwrap();
+        }
+
+        // Up an octave, with the second pattern a further fifth up.
+        project.set_transpose(12);
+        project.get_pattern_mut(1).unwrap().set_transpose(7);
+
+        let pitches: Vec<Note> = to_events(&project, 120.0, 48_000)
+            .iter()
+            .filter_map(|event| match event.kind {
+                EventKind::NoteOn { note, .. } => Some(note),
+                EventKind::NoteOff { .. } => None,
+            })
+            .collect();
+        self::assert_eq!(pitches, [note::CFive, note::BFive]);
+
+        // Skipping drops the notes pushed past the highest octave.
+        project.set_transpose(i16::MAX);
+        project.set_transpose_policy(TransposePolicy::Skip);
+        assert!(to_events(&project, 120.0, 48_000).is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+//! Undo and redo for pattern edits, i.e. for an editor UI.
+//!
+//! Edits are made through [`PatternEdit`]s rather than on the pattern
+//! directly, applying an edit returns the edit that reverses it, which
+//! the [`History`] keeps to step back and forth through the changes.
+
+use heapless::{Deque, Vec};
+
+use crate::sequence::{
+    PatternError,
+    pattern::{Note, Pattern, Step, Track},
+};
+
+/// A single change to a [`Pattern`] that can be applied and reversed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternEdit {
+    /// Sets a note in a slot of a step, programming the step if it's empty.
+    SetNote {
+        track: usize,
+        step: usize,
+        slot: usize,
+        note: Note,
+    },
+    /// Clears the note in a slot of a step.
+    ClearNote {
+        track: usize,
+        step: usize,
+        slot: usize,
+    },
+    /// Replaces a whole step, or erases it with `None`.
+    SetStep {
+        track: usize,
+        step: usize,
+        value: Option<Step>,
+    },
+    /// Sets the length of a track in steps.
+    SetLength { track: usize, length: u8 },
+}
+
+impl PatternEdit {
+    /// Applies the edit to the pattern, returning the edit that reverses it.
+    ///
+    /// Fails if the track isn't in use, or the step or slot is out
+    /// of range, in which case the pattern is left unchanged.
+    pub fn apply<const TRACKS: usize, const STEPS: usize>(
+        self,
+        pattern: &mut Pattern<TRACKS, STEPS>,
+    ) -> Result<PatternEdit, PatternError> {
+        match self {
+            PatternEdit::SetNote {
+                track,
+                step,
+                slot,
+                note,
+            } => {
+                let (track_edit, previous) = Self::step_edit(pattern, track, step, slot)?;
+                let mut programmed = previous.clone().unwrap_or_default();
+                programmed.set_note(slot, note)?;
+                track_edit.set_step(step, programmed)?;
+
+                Ok(PatternEdit::SetStep {
+                    track,
+                    step,
+                    value: previous,
+                })
+            }
+            PatternEdit::ClearNote { track, step, slot } => {
+                let (track_edit, previous) = Self::step_edit(pattern, track, step, slot)?;
+                if let Some(mut cleared) = previous.clone() {
+                    cleared.clear_note(slot)?;
+                    track_edit.set_step(step, cleared)?;
+                }
+
+                Ok(PatternEdit::SetStep {
+                    track,
+                    step,
+                    value: previous,
+                })
+            }
+            PatternEdit::SetStep { track, step, value } => {
+                let track_edit = Self::track(pattern, track)?;
+                if step >= STEPS {
+                    return Err(PatternError::OutOfRange);
+                }
+
+                let previous = match value {
+                    Some(value) => track_edit.set_step(step, value)?,
+                    None => track_edit.take_step(step)?,
+                };
+
+                Ok(PatternEdit::SetStep {
+                    track,
+                    step,
+                    value: previous,
+                })
+            }
+            PatternEdit::SetLength { track, length } => {
+                let track_edit = Self::track(pattern, track)?;
+                let previous = track_edit.length();
+                track_edit.set_length(length);
+
+                Ok(PatternEdit::SetLength {
+                    track,
+                    length: previous,
+                })
+            }
+        }
+    }
+
+    /// Returns the track at the index, failing if it isn't in use.
+    fn track<const TRACKS: usize, const STEPS: usize>(
+        pattern: &mut Pattern<TRACKS, STEPS>,
+        track: usize,
+    ) -> Result<&mut Track<STEPS>, PatternError> {
+        pattern.track_mut(track).ok_or(PatternError::NoTrack)
+    }
+
+    /// Checks the indices of a note edit, returning the track and the step as it was.
+    fn step_edit<const TRACKS: usize, const STEPS: usize>(
+        pattern: &mut Pattern<TRACKS, STEPS>,
+        track: usize,
+        step: usize,
+        slot: usize,
+    ) -> Result<(&mut Track<STEPS>, Option<Step>), PatternError> {
+        let track = Self::track(pattern, track)?;
+        if step >= STEPS || slot >= Step::SLOTS {
+            return Err(PatternError::OutOfRange);
+        }
+
+        let previous = track.step(step).cloned();
+        Ok((track, previous))
+    }
+}
+
+/// A bounded undo and redo history of [`PatternEdit`]s.
+///
+/// Keeps up to `DEPTH` edits to undo, once full the oldest
+/// edit is forgotten to make room for the next one.
+#[derive(Debug, Clone)]
+pub struct History<const DEPTH: usize> {
+    /// The edits that reverse the applied edits, most recent last.
+    undo: Deque<PatternEdit, DEPTH>,
+    /// The edits that reapply the undone edits, most recent last.
+    redo: Vec<PatternEdit, DEPTH>,
+}
+
+impl<const DEPTH: usize> History<DEPTH> {
+    /// Constructs a new empty history.
+    pub const fn new() -> Self {
+        Self {
+            undo: Deque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Applies an edit to the pattern and records it so it can be undone.
+    ///
+    /// Applying a new edit forgets any edits that were undone.
+    pub fn apply<const TRACKS: usize, const STEPS: usize>(
+        &mut self,
+        pattern: &mut Pattern<TRACKS, STEPS>,
+        edit: PatternEdit,
+    ) -> Result<(), PatternError> {
+        let inverse = edit.apply(pattern)?;
+        self.redo.clear();
+        self.push_undo(inverse);
+        Ok(())
+    }
+
+    /// Reverses the most recent edit, returning false if there's nothing to undo.
+    ///
+    /// If the edit can't be reversed, i.e. the track was removed from the
+    /// pattern since, the error is returned and the edit is kept.
+    pub fn undo<const TRACKS: usize, const STEPS: usize>(
+        &mut self,
+        pattern: &mut Pattern<TRACKS, STEPS>,
+    ) -> Result<bool, PatternError> {
+        let Some(inverse) = self.undo.pop_back() else {
+            return Ok(false);
+        };
+
+        match inverse.clone().apply(pattern) {
+            Ok(edit) => {
+                // The redo stack can't hold more than was undone, so there's always room.
+                let _ = self.redo.push(edit);
+                Ok(true)
+            }
+            Err(error) => {
+                // Keep the edit so the history stays in step with the pattern.
+                let _ = self.undo.push_back(inverse);
+                Err(error)
+            }
+        }
+    }
+
+    /// Reapplies the most recently undone edit, returning false if there's nothing to redo.
+    pub fn redo<const TRACKS: usize, const STEPS: usize>(
+        &mut self,
+        pattern: &mut Pattern<TRACKS, STEPS>,
+    ) -> Result<bool, PatternError> {
+        let Some(edit) = self.redo.pop() else {
+            return Ok(false);
+        };
+
+        match edit.clone().apply(pattern) {
+            Ok(inverse) => {
+                self.push_undo(inverse);
+                Ok(true)
+            }
+            Err(error) => {
+                let _ = self.redo.push(edit);
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns true if there's an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns true if there's an undone edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets every edit, i.e. after loading a different pattern.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Records an edit to undo, forgetting the oldest when full.
+    fn push_undo(&mut self, inverse: PatternEdit) {
+        if self.undo.is_full() {
+            self.undo.pop_front();
+        }
+        let _ = self.undo.push_back(inverse);
+    }
+}
+
+impl<const DEPTH: usize> Default for History<DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::note;
+    use pretty_assertions::assert_eq;
+
+    fn pattern() -> Pattern<2, 16> {
+        let mut pattern = Pattern::new();
+        pattern.set_track(0, Track::new()).unwrap();
+        pattern
+    }
+
+    #[test]
+    fn test_undo_redo_note() {
+        let mut pattern = pattern();
+        let mut history = History::<8>::new();
+        assert!(!history.can_undo());
+
+        let edit = PatternEdit::SetNote {
+            track: 0,
+            step: 4,
+            slot: 0,
+            note: Note::new(note::CFour, 1, 100),
+        };
+        history.apply(&mut pattern, edit).unwrap();
+        let programmed = pattern.clone();
+        self::assert_eq!(
+            programmed.track(0).unwrap().step(4).unwrap().note(0),
+            Some(&Note::new(note::CFour, 1, 100))
+        );
+
+        // Undoing erases the step the note programmed, not just the note.
+        self::assert_eq!(history.undo(&mut pattern).unwrap(), true);
+        self::assert_eq!(pattern, self::pattern());
+        self::assert_eq!(history.undo(&mut pattern).unwrap(), false);
+
+        self::assert_eq!(history.redo(&mut pattern).unwrap(), true);
+        self::assert_eq!(pattern, programmed);
+        self::assert_eq!(history.redo(&mut pattern).unwrap(), false);
+    }
+
+    #[test]
+    fn test_edits_reverse() {
+        let mut pattern = pattern();
+        let mut history = History::<8>::new();
+
+        let note = Note::new(note::EFour, 2, 90);
+        history
+            .apply(
+                &mut pattern,
+                PatternEdit::SetNote {
+                    track: 0,
+                    step: 0,
+                    slot: 3,
+                    note: note.clone(),
+                },
+            )
+            .unwrap();
+        let programmed = pattern.clone();
+
+        history
+            .apply(
+                &mut pattern,
+                PatternEdit::ClearNote {
+                    track: 0,
+                    step: 0,
+                    slot: 3,
+                },
+            )
+            .unwrap();
+        history
+            .apply(
+                &mut pattern,
+                PatternEdit::SetLength {
+                    track: 0,
+                    length: 12,
+                },
+            )
+            .unwrap();
+        self::assert_eq!(pattern.track(0).unwrap().length(), 12);
+        self::assert_eq!(pattern.track(0).unwrap().step(0).unwrap().note(3), None);
+
+        history.undo(&mut pattern).unwrap();
+        history.undo(&mut pattern).unwrap();
+        self::assert_eq!(pattern, programmed);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut pattern = pattern();
+        let mut history = History::<2>::new();
+
+        for length in [4, 8, 12] {
+            let edit = PatternEdit::SetLength { track: 0, length };
+            history.apply(&mut pattern, edit).unwrap();
+        }
+
+        // Only the two most recent edits can be undone.
+        history.undo(&mut pattern).unwrap();
+        history.undo(&mut pattern).unwrap();
+        self::assert_eq!(history.undo(&mut pattern).unwrap(), false);
+        self::assert_eq!(pattern.track(0).unwrap().length(), 4);
+    }
+
+    #[test]
+    fn test_invalid_edit() {
+        let mut pattern = pattern();
+        let mut history = History::<2>::new();
+
+        let result = history.apply(
+            &mut pattern,
+            PatternEdit::SetLength {
+                track: 1,
+                length: 4,
+            },
+        );
+        assert!(matches!(result, Err(PatternError::NoTrack)));
+
+        let result = history.apply(
+            &mut pattern,
+            PatternEdit::ClearNote {
+                track: 0,
+                step: 16,
+                slot: 0,
+            },
+        );
+        assert!(matches!(result, Err(PatternError::OutOfRange)));
+        assert!(!history.can_undo());
+    }
+}
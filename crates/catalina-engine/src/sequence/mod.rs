@@ -1,17 +1,126 @@
 use heapless::Vec;
 
-use crate::sequence::pattern::Pattern;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    music::{note::Note, rhythm::TimeSignature},
+    sequence::pattern::Pattern,
+};
+
+pub mod clock;
+#[cfg(feature = "alloc")]
+pub mod export;
+pub mod history;
 pub mod pattern;
+pub mod player;
+pub mod song;
+pub mod transport;
+
+/// (De)serializes an array sized by a const generic as a sequence,
+/// since serde only implements arrays of up to 32 items.
+///
+/// Use it on a field with `#[serde(with = "super::serde_array")]`.
+#[cfg(feature = "serde")]
+mod serde_array {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+    pub fn serialize<S: Serializer, T: Serialize, const N: usize>(
+        array: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        array.as_slice().serialize(serializer)
+    }
 
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[T; N], D::Error> {
+        let items = heapless::Vec::<T, N>::deserialize(deserializer)?;
+        let len = items.len();
+        items
+            .into_array()
+            .map_err(|_| de::Error::invalid_length(len, &"as many items as the array holds"))
+    }
+}
+
+#[derive(Debug)]
 pub enum PatternError {
     PatternsFull,
+    /// The song has no room left for another entry.
+    SongFull,
+    /// There isn't a pattern at the provided index.
+    NoPattern,
+    /// There isn't a track at the provided index of the pattern.
+    NoTrack,
+    /// The index is past the number of patterns, steps or
+    /// note slots that are available.
+    OutOfRange,
+}
+
+/// What happens to notes that a transpose would move outside of the available octaves.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TransposePolicy {
+    /// Clamp the note to C0 or B15, see [`Note::transpose_saturating`].
+    #[default]
+    Clamp,
+    /// Don't play the note.
+    Skip,
+}
+
+impl TransposePolicy {
+    /// Transposes a note by an amount of semitones, returning
+    /// `None` if the note is out of range and should be skipped.
+    pub fn apply(self, note: Note, semitones: i16) -> Option<Note> {
+        match self {
+            TransposePolicy::Clamp => Some(note.transpose_saturating(semitones)),
+            TransposePolicy::Skip => note.transpose(semitones),
+        }
+    }
+}
+
+/// The settings that apply to a whole project, such as it's tempo.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProjectSettings {
+    /// The tempo in beats per minute.
+    pub bpm: f32,
+    pub time_signature: TimeSignature,
+    /// How far every other step is pushed late, from 0.0 (straight) to 1.0.
+    ///
+    /// See [`Transport::set_swing`](transport::Transport::set_swing).
+    pub swing: f32,
+    /// How many semitones every note is transposed by when played.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transpose: i16,
+    /// What happens to notes the transpose moves out of range.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transpose_policy: TransposePolicy,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            bpm: 120.0,
+            time_signature: TimeSignature::COMMON,
+            swing: 0.0,
+            transpose: 0,
+            transpose_policy: TransposePolicy::Clamp,
+        }
+    }
 }
 
 /// A project provide a collection of patterns.
+///
+/// The patterns are saved along with the project's settings
+/// when it's serialized, so a loaded project plays the same.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Project<const PATTERNS: usize, const TRACKS: usize, const STEPS: usize> {
     /// The list of patterns in the track.
     patterns: Vec<Option<Pattern<TRACKS, STEPS>>, PATTERNS>,
+
+    /// The tempo, time signature and swing of the project.
+    settings: ProjectSettings,
 }
 
 impl<const PATTERNS: usize, const TRACKS: usize, const STEPS: usize>
@@ -20,9 +129,122 @@ impl<const PATTERNS: usize, const TRACKS: usize, const STEPS: usize>
     pub fn new() -> Self {
         Self {
             patterns: Vec::new(),
+            settings: ProjectSettings::default(),
         }
     }
 
+    /// Returns the tempo, time signature and swing of the project.
+    pub const fn settings(&self) -> &ProjectSettings {
+        &self.settings
+    }
+
+    /// Replaces all the project's settings, i.e. after loading them.
+    pub fn set_settings(&mut self, settings: ProjectSettings) {
+        self.settings = settings;
+    }
+
+    /// Returns the tempo of the project in beats per minute.
+    pub const fn bpm(&self) -> f32 {
+        self.settings.bpm
+    }
+
+    /// Sets the tempo of the project in beats per minute.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.settings.bpm = bpm;
+    }
+
+    /// Returns the time signature of the project.
+    pub const fn time_signature(&self) -> TimeSignature {
+        self.settings.time_signature
+    }
+
+    /// Sets the time signature of the project.
+    pub fn set_time_signature(&mut self, time_signature: TimeSignature) {
+        self.settings.time_signature = time_signature;
+    }
+
+    /// Returns how far every other step is pushed late, from 0.0 to 1.0.
+    pub const fn swing(&self) -> f32 {
+        self.settings.swing
+    }
+
+    /// Sets how far every other step is pushed late, clamped from 0.0 (straight) to 1.0.
+    pub fn set_swing(&mut self, swing: f32) {
+        self.settings.swing = swing.clamp(0.0, 1.0);
+    }
+
+    /// Returns how many semitones every note in the project is transposed by.
+    pub const fn transpose(&self) -> i16 {
+        self.settings.transpose
+    }
+
+    /// Transposes every note in the project by an amount of semitones when played,
+    /// on top of each pattern's own transpose, without editing the notes.
+    pub fn set_transpose(&mut self, semitones: i16) {
+        self.settings.transpose = semitones;
+    }
+
+    /// Returns what happens to notes the transpose moves out of range.
+    pub const fn transpose_policy(&self) -> TransposePolicy {
+        self.settings.transpose_policy
+    }
+
+    /// Sets whether notes the transpose moves out of range are clamped or skipped.
+    pub fn set_transpose_policy(&mut self, policy: TransposePolicy) {
+        self.settings.transpose_policy = policy;
+    }
+
+    /// Returns the semitones the notes of the pattern at the provided index are
+    /// transposed by, the project's and the pattern's transpose together.
+    ///
+    /// Returns `None` if there isn't a pattern there.
+    pub fn pattern_transpose(&self, index: usize) -> Option<i16> {
+        self.patterns
+            .get(index)?
+            .as_ref()
+            .map(|pattern| self.settings.transpose.saturating_add(pattern.transpose()))
+    }
+
+    /// Adds a pattern to the end of the project, returning it's index.
+    pub fn push_pattern(&mut self, pattern: Pattern<TRACKS, STEPS>) -> Result<usize, PatternError> {
+        self.patterns
+            .push(Some(pattern))
+            .map_err(|_| PatternError::PatternsFull)?;
+
+        Ok(self.patterns.len() - 1)
+    }
+
+    /// Copies the pattern at `src` into the slot at `dst`, replacing any pattern already there.
+    ///
+    /// Slots between the last pattern and `dst` are left empty.
+    pub fn duplicate_pattern(&mut self, src: usize, dst: usize) -> Result<(), PatternError> {
+        let Some(Some(pattern)) = self.patterns.get(src) else {
+            return Err(PatternError::NoPattern);
+        };
+        let pattern = pattern.clone();
+
+        if dst >= PATTERNS {
+            return Err(PatternError::OutOfRange);
+        }
+
+        while self.patterns.len() <= dst {
+            // The capacity was checked above.
+            let _ = self.patterns.push(None);
+        }
+
+        self.patterns[dst] = Some(pattern);
+        Ok(())
+    }
+
+    /// Returns the length in steps of the pattern at the provided
+    /// index, or `None` if there isn't a pattern there.
+    pub fn pattern_length(&self, index: usize) -> Option<u64> {
+        self.patterns
+            .get(index)?
+            .as_ref()
+            .map(|pattern| pattern.length())
+    }
+
     /// Retrieves a reference to a pattern in the track.
     pub fn get_pattern(&mut self, index: usize) -> Option<&Pattern<TRACKS, STEPS>> {
         if index > self.patterns.len() {
@@ -49,3 +271,126 @@ impl<const PATTERNS: usize, const TRACKS: usize, const STEPS: usize>
         Some(pattern)
     }
 }
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        music::note,
+        sequence::{
+            clock::ManualClock,
+            pattern::{self, Step, Track},
+            transport::Transport,
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_duplicate_pattern() {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(note::CFour, 1, 100))
+            .unwrap();
+        let mut track = Track::<16>::new();
+        track.set_step(0, step).unwrap();
+        let mut pattern = Pattern::<2, 16>::new();
+        pattern.set_track(0, track).unwrap();
+
+        let mut project = Project::<4, 2, 16>::new();
+        project.push_pattern(pattern).unwrap();
+        project.duplicate_pattern(0, 2).unwrap();
+
+        self::assert_eq!(project.patterns[1], None);
+        self::assert_eq!(project.patterns[2], project.patterns[0]);
+
+        // Editing the copy leaves the original alone.
+        project.get_pattern_mut(2).unwrap().clear();
+        self::assert_eq!(project.pattern_length(0), Some(16));
+        self::assert_eq!(project.pattern_length(2), Some(0));
+
+        assert!(matches!(
+            project.duplicate_pattern(1, 3),
+            Err(PatternError::NoPattern)
+        ));
+        assert!(matches!(
+            project.duplicate_pattern(0, 4),
+            Err(PatternError::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_project_settings() {
+        let mut project = Project::<1, 1, 16>::new();
+        self::assert_eq!(project.settings(), &ProjectSettings::default());
+
+        project.set_bpm(96.0);
+        project.set_time_signature(TimeSignature::new(6, 8));
+        project.set_swing(2.0);
+        self::assert_eq!(
+            project.settings(),
+            &ProjectSettings {
+                bpm: 96.0,
+                time_signature: TimeSignature::new(6, 8),
+                swing: 1.0,
+                transpose: 0,
+                transpose_policy: TransposePolicy::Clamp,
+            }
+        );
+
+        // Loading settings saved from another project.
+        let saved = *project.settings();
+        let mut loaded = Project::<1, 1, 16>::new();
+        loaded.set_settings(saved);
+        self::assert_eq!(loaded.bpm(), 96.0);
+        self::assert_eq!(loaded.time_signature(), TimeSignature::new(6, 8));
+        self::assert_eq!(loaded.swing(), 1.0);
+
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.apply_settings(loaded.settings());
+        self::assert_eq!(transport.bpm(), 96.0);
+        self::assert_eq!(transport.swing(), 1.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = ProjectSettings {
+            bpm: 96.0,
+            time_signature: TimeSignature::new(6, 8),
+            swing: 0.25,
+            transpose: -3,
+            transpose_policy: TransposePolicy::Skip,
+        };
+
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: ProjectSettings = serde_json::from_str(&json).unwrap();
+        self::assert_eq!(loaded, settings);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_settings_saved_with_project() {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(note::CFour, 1, 100))
+            .unwrap();
+        let mut track = Track::<64>::new();
+        track.set_step(40, step).unwrap();
+        let mut pattern = Pattern::<2, 64>::new();
+        pattern.set_track(1, track).unwrap();
+
+        let mut project = Project::<2, 2, 64>::new();
+        project.push_pattern(pattern).unwrap();
+        project.set_bpm(96.0);
+        project.set_time_signature(TimeSignature::new(6, 8));
+        project.set_swing(0.25);
+
+        let json = serde_json::to_string(&project).unwrap();
+        let mut loaded: Project<2, 2, 64> = serde_json::from_str(&json).unwrap();
+        self::assert_eq!(loaded.settings(), project.settings());
+        self::assert_eq!(loaded.get_pattern(0), project.get_pattern(0));
+
+        // A project saved with more steps than the loader holds is rejected.
+        assert!(serde_json::from_str::<Project<2, 2, 16>>(&json).is_err());
+    }
+}
@@ -1,9 +1,19 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    core::rng::Rng,
+    music::{note, pitch::Pitch, scale::Scale},
+    sequence::PatternError,
+};
+
 /// Represents a note in a sequence that has a pitch, length, velocity, etc.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Note {
+    /// The musical note (pitch and octave) to trigger.
+    note: note::Note,
+
     /// The length of the note in steps.
     length: u32,
 
@@ -14,32 +24,570 @@ pub struct Note {
     velocity: u8,
 }
 
+impl Note {
+    /// Constructs a new sequenced note.
+    pub const fn new(note: note::Note, length: u32, velocity: u8) -> Self {
+        Self {
+            note,
+            length,
+            velocity,
+        }
+    }
+
+    /// Returns the musical note that is triggered.
+    pub const fn note(&self) -> note::Note {
+        self.note
+    }
+
+    /// Returns the length of the note in steps.
+    pub const fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Returns the velocity the note is pressed with.
+    pub const fn velocity(&self) -> u8 {
+        self.velocity
+    }
+}
+
+/// How likely each kind of change is when mutating a pattern with [`Pattern::mutate`].
+///
+/// Probabilities are from 0.0 (never) to 1.0 (always) and are rolled
+/// once per step or note, the default makes no changes at all.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MutateParams {
+    /// Chance of toggling each step, clearing programmed steps and
+    /// filling empty ones with a copy of another step of the track.
+    pub toggle: f32,
+
+    /// Chance of nudging the velocity of each note.
+    pub velocity: f32,
+    /// The most a velocity is nudged by either way.
+    pub velocity_amount: u8,
+
+    /// Chance of moving each note up or down a degree of the scale.
+    pub transpose: f32,
+    /// The scale notes are transposed within.
+    pub scale: Scale,
+    /// The root of the scale.
+    pub root: Pitch,
+}
+
+impl Default for MutateParams {
+    fn default() -> Self {
+        Self {
+            toggle: 0.0,
+            velocity: 0.0,
+            velocity_amount: 16,
+            transpose: 0.0,
+            scale: Scale::Chromatic,
+            root: Pitch::C,
+        }
+    }
+}
+
+impl Note {
+    /// Randomly nudges the velocity and transposes the note.
+    fn mutate(&mut self, rng: &mut Rng, params: &MutateParams) {
+        if rng.next_f32() < params.velocity {
+            let nudge = rng.next_bipolar() * params.velocity_amount as f32;
+            self.velocity = (self.velocity as f32 + nudge).clamp(1.0, 127.0) as u8;
+        }
+
+        if rng.next_f32() < params.transpose {
+            let direction = if rng.next_u32() & 1 == 0 { 1 } else { -1 };
+
+            // Walk to the next note of the scale, staying put at the edge of the range.
+            let next_degree = (1..=12)
+                .filter_map(|distance| self.note.transpose(direction * distance))
+                .find(|note| params.scale.contains(params.root, note.pitch()));
+            if let Some(note) = next_degree {
+                self.note = note;
+            }
+        }
+    }
+}
+
+/// The resolution of step micro-timing in ticks per step.
+pub const MICRO_TIMING_TICKS: i16 = 128;
+
 /// A single step in a pattern containing notes and/or automation parameters.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Step {
     /// The nodes triggered by the pattern step.
-    notes: [Option<Note>; 8],
+    notes: [Option<Note>; Step::SLOTS],
+
+    /// Glide from the notes of the previous step instead of retriggering.
+    slide: bool,
+
+    /// Nudges the step earlier (negative) or later (positive) than the
+    /// grid, in [`MICRO_TIMING_TICKS`] of a step.
+    micro_timing: i8,
+}
+
+impl Step {
+    /// The number of notes a step can hold.
+    pub const SLOTS: usize = 8;
+
+    pub const fn new() -> Self {
+        Self {
+            notes: [const { None::<Note> }; Self::SLOTS],
+            slide: false,
+            micro_timing: 0,
+        }
+    }
+
+    /// Sets the note in the provided slot of the step, returning the previous note.
+    ///
+    /// Fails with [`PatternError::OutOfRange`] if the slot is
+    /// outside of the 8 available note slots.
+    pub fn set_note(&mut self, slot: usize, note: Note) -> Result<Option<Note>, PatternError> {
+        let slot = self.notes.get_mut(slot).ok_or(PatternError::OutOfRange)?;
+        Ok(slot.replace(note))
+    }
+
+    /// Returns the note in the provided slot of the step, if any.
+    pub fn note(&self, slot: usize) -> Option<&Note> {
+        self.notes.get(slot)?.as_ref()
+    }
+
+    /// Clears the note in the provided slot of the step, returning it.
+    ///
+    /// Fails with [`PatternError::OutOfRange`] if the slot is
+    /// outside of the 8 available note slots.
+    pub fn clear_note(&mut self, slot: usize) -> Result<Option<Note>, PatternError> {
+        let slot = self.notes.get_mut(slot).ok_or(PatternError::OutOfRange)?;
+        Ok(slot.take())
+    }
+
+    /// Sets if the step slides from the previous step's notes instead of retriggering.
+    pub fn set_slide(&mut self, slide: bool) {
+        self.slide = slide;
+    }
+
+    /// Returns true if the step slides from the previous step's notes.
+    pub const fn slide(&self) -> bool {
+        self.slide
+    }
+
+    /// Sets how far the step is nudged off the grid, in [`MICRO_TIMING_TICKS`] of a step.
+    ///
+    /// Nudges are clamped to half a step either way when scheduled,
+    /// so a step can never be moved past it's neighbours.
+    pub fn set_micro_timing(&mut self, ticks: i8) {
+        self.micro_timing = ticks;
+    }
+
+    /// Returns how far the step is nudged off the grid, in [`MICRO_TIMING_TICKS`] of a step.
+    pub const fn micro_timing(&self) -> i8 {
+        self.micro_timing
+    }
+
+    /// Iterates over the notes that are set in the step.
+    pub fn notes(&self) -> impl Iterator<Item = &Note> {
+        self.notes.iter().flatten()
+    }
+}
+
+impl Default for Step {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Track<const STEPS: usize> {
     /// The steps in the pattern.
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_array"))]
     steps: [Option<Step>; STEPS],
 
     /// The total length of the pattern.
     length: u8,
 }
 
+impl<const STEPS: usize> Track<STEPS> {
+    /// The longest length a track can be set to, the length is
+    /// stored in a `u8` so tracks over 255 steps are capped.
+    const MAX_LENGTH: u8 = if STEPS > u8::MAX as usize {
+        u8::MAX
+    } else {
+        STEPS as u8
+    };
+
+    /// Constructs a new empty track spanning all the available steps.
+    ///
+    /// The length starts at `STEPS`, capped to 255 steps for
+    /// longer tracks as it's stored in a `u8`.
+    pub const fn new() -> Self {
+        Self {
+            steps: [const { None::<Step> }; STEPS],
+            length: Self::MAX_LENGTH,
+        }
+    }
+
+    /// Returns the length of the track in steps.
+    pub const fn length(&self) -> u8 {
+        self.length
+    }
+
+    /// Sets the length of the track in steps, clamped from 1 to the available steps.
+    pub fn set_length(&mut self, length: u8) {
+        self.length = length.min(Self::MAX_LENGTH).max(1);
+    }
+
+    /// Returns the step at the provided index, if it has content.
+    ///
+    /// Returns `None` both for empty steps and for indexes
+    /// outside of the track's steps, instead of panicking.
+    pub fn get_step(&self, index: usize) -> Option<&Step> {
+        self.steps.get(index)?.as_ref()
+    }
+
+    /// Returns the step at the provided index mutably, if it has content.
+    ///
+    /// Like [`Track::get_step`], returns `None` for empty or out of
+    /// range steps. Use [`Track::set_step`] to program an empty step.
+    pub fn get_step_mut(&mut self, index: usize) -> Option<&mut Step> {
+        self.steps.get_mut(index)?.as_mut()
+    }
+
+    /// Shorthand for [`Track::get_step`].
+    pub fn step(&self, index: usize) -> Option<&Step> {
+        self.get_step(index)
+    }
+
+    /// Shorthand for [`Track::get_step_mut`].
+    pub fn step_mut(&mut self, index: usize) -> Option<&mut Step> {
+        self.get_step_mut(index)
+    }
+
+    /// Sets the step at the provided index, returning the previous step.
+    ///
+    /// Fails with [`PatternError::OutOfRange`] if the index is
+    /// outside of the track's steps, leaving the track unchanged.
+    pub fn set_step(&mut self, index: usize, step: Step) -> Result<Option<Step>, PatternError> {
+        let slot = self.steps.get_mut(index).ok_or(PatternError::OutOfRange)?;
+        Ok(slot.replace(step))
+    }
+
+    /// Erases the step at the provided index, returning it.
+    ///
+    /// Fails with [`PatternError::OutOfRange`] if the index is
+    /// outside of the track's steps.
+    pub fn take_step(&mut self, index: usize) -> Result<Option<Step>, PatternError> {
+        let slot = self.steps.get_mut(index).ok_or(PatternError::OutOfRange)?;
+        Ok(slot.take())
+    }
+
+    /// Erases all the steps in the track and resets it's length.
+    pub fn clear(&mut self) {
+        self.steps = [const { None::<Step> }; STEPS];
+        self.length = Self::MAX_LENGTH;
+    }
+
+    /// Iterates over the steps in the track that have content, along with their index.
+    pub fn iter_active(&self) -> impl Iterator<Item = (usize, &Step)> {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter_map(|(index, step)| step.as_ref().map(|step| (index, step)))
+    }
+}
+
+impl<const STEPS: usize> Default for Track<STEPS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A pattern provides a list of [`Step`]s thats are
 /// sequenced to play an instrument or create MIDI data.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pattern<const TRACKS: usize, const STEPS: usize> {
     /// The steps in the pattern.
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_array"))]
     tracks: [Option<Track<STEPS>>; TRACKS],
+
+    /// How many semitones the pattern's notes are transposed by when played.
+    transpose: i16,
 }
 
 impl<const TRACKS: usize, const STEPS: usize> Pattern<TRACKS, STEPS> {
     pub fn new() -> Self {
         Self {
             tracks: [const { None::<Track<STEPS>> }; TRACKS],
+            transpose: 0,
+        }
+    }
+
+    /// Returns how many semitones the pattern's notes are transposed by when played.
+    pub const fn transpose(&self) -> i16 {
+        self.transpose
+    }
+
+    /// Transposes the pattern's notes by an amount of semitones when
+    /// played, without editing the notes, i.e. to move a bassline up
+    /// a fifth for a chorus. Adds to the project's transpose.
+    pub fn set_transpose(&mut self, semitones: i16) {
+        self.transpose = semitones;
+    }
+
+    /// Sets the track at the provided index, returning the previous track.
+    ///
+    /// Fails with [`PatternError::OutOfRange`] if the index is
+    /// outside of the pattern's tracks, leaving the pattern unchanged.
+    pub fn set_track(
+        &mut self,
+        index: usize,
+        track: Track<STEPS>,
+    ) -> Result<Option<Track<STEPS>>, PatternError> {
+        let slot = self.tracks.get_mut(index).ok_or(PatternError::OutOfRange)?;
+        Ok(slot.replace(track))
+    }
+
+    /// Returns the track at the provided index, if it's in use.
+    pub fn track(&self, index: usize) -> Option<&Track<STEPS>> {
+        self.tracks.get(index)?.as_ref()
+    }
+
+    /// Returns the track at the provided index mutably, if it's in use.
+    pub fn track_mut(&mut self, index: usize) -> Option<&mut Track<STEPS>> {
+        self.tracks.get_mut(index)?.as_mut()
+    }
+
+    /// Copies the track at `src` over the track at `dst`, returning the previous track.
+    ///
+    /// Copying an unused track clears the destination. Fails with
+    /// [`PatternError::OutOfRange`] if either index is outside of
+    /// the pattern's tracks, leaving the pattern unchanged.
+    pub fn copy_track(
+        &mut self,
+        src: usize,
+        dst: usize,
+    ) -> Result<Option<Track<STEPS>>, PatternError> {
+        let track = self
+            .tracks
+            .get(src)
+            .ok_or(PatternError::OutOfRange)?
+            .clone();
+        let slot = self.tracks.get_mut(dst).ok_or(PatternError::OutOfRange)?;
+        Ok(core::mem::replace(slot, track))
+    }
+
+    /// Erases all the tracks in the pattern.
+    pub fn clear(&mut self) {
+        self.tracks = [const { None::<Track<STEPS>> }; TRACKS];
+    }
+
+    /// Returns the length of the pattern in steps, which is the length of it's longest track.
+    pub fn length(&self) -> u64 {
+        self.iter_active()
+            .map(|(_, track)| track.length() as u64)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Randomly changes the steps and notes of the pattern, i.e. for generative music.
+    ///
+    /// See [`MutateParams`] for the kinds of change. The same seed of
+    /// `rng` and parameters always mutate a pattern the same way.
+    pub fn mutate(&mut self, rng: &mut Rng, params: &MutateParams) {
+        for track in self.tracks.iter_mut().flatten() {
+            let length = (track.length as usize).min(STEPS);
+
+            for index in 0..length {
+                if rng.next_f32() < params.toggle {
+                    if track.steps[index].is_some() {
+                        track.steps[index] = None;
+                    } else {
+                        // Fill the step from one of the other programmed steps.
+                        let programmed = track.iter_active().count();
+                        if programmed > 0 {
+                            let pick = rng.next_u32() as usize % programmed;
+                            let copy = track.iter_active().nth(pick).map(|(_, step)| step.clone());
+                            track.steps[index] = copy;
+                        }
+                    }
+                }
+
+                if let Some(step) = track.steps[index].as_mut() {
+                    for note in step.notes.iter_mut().flatten() {
+                        note.mutate(rng, params);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Iterates over the tracks in the pattern that are in use, along with their index.
+    pub fn iter_active(&self) -> impl Iterator<Item = (usize, &Track<STEPS>)> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, track)| track.as_ref().map(|track| (index, track)))
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn programmed_track() -> Track<16> {
+        let mut step = Step::new();
+        step.set_note(0, Note::new(note::CFour, 1, 100)).unwrap();
+
+        let mut track = Track::new();
+        track.set_step(0, step).unwrap();
+        track.set_step(4, Step::new()).unwrap();
+        track
+    }
+
+    #[test]
+    fn test_step_bounds() {
+        let mut track = programmed_track();
+        assert!(track.get_step(0).is_some());
+        self::assert_eq!(track.get_step(1), None);
+        self::assert_eq!(track.get_step(16), None);
+        assert!(track.get_step_mut(16).is_none());
+
+        // Out of range edits fail instead of panicking.
+        assert!(matches!(
+            track.set_step(16, Step::new()),
+            Err(PatternError::OutOfRange)
+        ));
+        assert!(matches!(
+            track.take_step(usize::MAX),
+            Err(PatternError::OutOfRange)
+        ));
+
+        let mut step = Step::new();
+        assert!(matches!(
+            step.set_note(Step::SLOTS, Note::new(note::CFour, 1, 100)),
+            Err(PatternError::OutOfRange)
+        ));
+        assert!(matches!(
+            step.clear_note(Step::SLOTS),
+            Err(PatternError::OutOfRange)
+        ));
+
+        track.get_step_mut(4).unwrap().set_slide(true);
+        assert!(track.step(4).unwrap().slide());
+    }
+
+    #[test]
+    fn test_track_clear() {
+        let mut track = programmed_track();
+        self::assert_eq!(track.iter_active().count(), 2);
+
+        track.clear();
+        self::assert_eq!(track.iter_active().count(), 0);
+        self::assert_eq!(track.length(), 16);
+    }
+
+    #[test]
+    fn test_long_track_length_is_capped() {
+        let mut track = Track::<300>::new();
+        self::assert_eq!(track.length(), 255);
+
+        track.set_length(16);
+        track.clear();
+        self::assert_eq!(track.length(), 255);
+    }
+
+    #[test]
+    fn test_copy_track() {
+        let mut pattern = Pattern::<4, 16>::new();
+        pattern.set_track(0, programmed_track()).unwrap();
+
+        self::assert_eq!(pattern.copy_track(0, 2).unwrap(), None);
+        self::assert_eq!(pattern.tracks[2], pattern.tracks[0]);
+
+        // Copying an unused track clears the destination.
+        self::assert_eq!(pattern.copy_track(1, 2).unwrap(), Some(programmed_track()));
+        self::assert_eq!(pattern.iter_active().count(), 1);
+
+        // Out of range tracks fail instead of panicking.
+        assert!(matches!(
+            pattern.copy_track(4, 0),
+            Err(PatternError::OutOfRange)
+        ));
+        assert!(matches!(
+            pattern.copy_track(0, 4),
+            Err(PatternError::OutOfRange)
+        ));
+        assert!(matches!(
+            pattern.set_track(4, programmed_track()),
+            Err(PatternError::OutOfRange)
+        ));
+        self::assert_eq!(pattern.iter_active().count(), 1);
+    }
+
+    #[test]
+    fn test_pattern_clear() {
+        let mut pattern = Pattern::<4, 16>::new();
+        pattern.set_track(1, programmed_track()).unwrap();
+        pattern.set_track(3, programmed_track()).unwrap();
+        self::assert_eq!(pattern.iter_active().count(), 2);
+
+        pattern.clear();
+        self::assert_eq!(pattern.iter_active().count(), 0);
+    }
+
+    fn melodic_pattern() -> Pattern<2, 16> {
+        let mut track = Track::new();
+        for (index, note) in [note::CFour, note::EFour, note::GFour, note::BFour]
+            .into_iter()
+            .enumerate()
+        {
+            let mut step = Step::new();
+            step.set_note(0, Note::new(note, 1, 100)).unwrap();
+            track.set_step(index * 4, step).unwrap();
+        }
+
+        let mut pattern = Pattern::new();
+        pattern.set_track(0, track).unwrap();
+        pattern
+    }
+
+    #[test]
+    fn test_mutate_without_probability_is_unchanged() {
+        let mut pattern = melodic_pattern();
+        pattern.mutate(&mut Rng::new(1), &MutateParams::default());
+        self::assert_eq!(pattern, melodic_pattern());
+    }
+
+    #[test]
+    fn test_mutate_is_reproducible() {
+        let params = MutateParams {
+            toggle: 0.3,
+            velocity: 0.5,
+            transpose: 0.5,
+            scale: Scale::Major,
+            ..MutateParams::default()
+        };
+
+        let mut a = melodic_pattern();
+        let mut b = melodic_pattern();
+        a.mutate(&mut Rng::new(7), &params);
+        b.mutate(&mut Rng::new(7), &params);
+        self::assert_eq!(a, b);
+        assert_ne!(a, melodic_pattern());
+
+        // Transposed notes stay in the scale, velocities stay playable.
+        for (_, track) in a.iter_active() {
+            for (_, step) in track.iter_active() {
+                for note in step.notes() {
+                    assert!(Scale::Major.contains(Pitch::C, note.note().pitch()));
+                    assert!((1..=127).contains(&note.velocity()));
+                }
+            }
         }
     }
 }
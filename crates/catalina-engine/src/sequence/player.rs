@@ -0,0 +1,201 @@
+//! Plays the steps of a track on an [`Instrument`].
+
+use heapless::Vec;
+
+use crate::{
+    instrument::{Instrument, NoteError},
+    music::note::Note,
+    sequence::{TransposePolicy, pattern::Step},
+};
+
+/// Tracks the notes a track is sounding on an instrument so that
+/// they can be released, or slid from, when the next step plays.
+pub struct TrackPlayer {
+    /// The notes that are currently held on the instrument.
+    sounding: Vec<Note, 8>,
+
+    /// How many semitones the step's notes are transposed by.
+    transpose: i16,
+    /// What happens to notes the transpose moves out of range.
+    transpose_policy: TransposePolicy,
+}
+
+impl TrackPlayer {
+    pub const fn new() -> Self {
+        Self {
+            sounding: Vec::new(),
+            transpose: 0,
+            transpose_policy: TransposePolicy::Clamp,
+        }
+    }
+
+    /// Transposes the notes of the steps played from now on by an amount
+    /// of semitones, i.e. from [`Project::pattern_transpose`](super::Project::pattern_transpose).
+    ///
+    /// Notes already sounding are released at the pitch they were pressed at.
+    pub fn set_transpose(&mut self, semitones: i16, policy: TransposePolicy) {
+        self.transpose = semitones;
+        self.transpose_policy = policy;
+    }
+
+    /// Returns the notes the player is currently holding.
+    pub fn sounding(&self) -> &[Note] {
+        &self.sounding
+    }
+
+    /// Plays a step on the instrument.
+    ///
+    /// Normally the notes from the previous step are released and the
+    /// step's notes are pressed. When the step has slide set, each
+    /// sounding note is glided to the note in the same position of the
+    /// step instead, any notes left over on either side are released
+    /// or pressed as usual.
+    ///
+    /// Notes are transposed by the player's transpose first, notes
+    /// skipped by the [`TransposePolicy`] aren't played at all.
+    ///
+    /// If the instrument runs out of voices the notes left over from
+    /// the previous step are released so none are left hanging, the
+    /// notes of the step that did start are still tracked.
+    pub fn play_step<I: Instrument>(
+        &mut self,
+        step: &Step,
+        instrument: &mut I,
+    ) -> Result<(), NoteError> {
+        let mut previous = core::mem::take(&mut self.sounding).into_iter();
+
+        for note in step.notes() {
+            let Some(pitch) = self.transpose_policy.apply(note.note(), self.transpose) else {
+                continue;
+            };
+
+            let held = if step.slide() { previous.next() } else { None };
+            let result = match held {
+                Some(from) => instrument.glide(from, pitch, note.velocity()),
+                None => instrument.note_on(pitch, note.velocity()),
+            };
+
+            if let Err(error) = result {
+                for note in previous {
+                    instrument.note_off(note);
+                }
+
+                return Err(error);
+            }
+
+            // The vector is the same size as the step's note slots.
+            let _ = self.sounding.push(pitch);
+        }
+
+        for note in previous {
+            instrument.note_off(note);
+        }
+
+        Ok(())
+    }
+
+    /// Releases all the notes the player is holding.
+    pub fn release<I: Instrument>(&mut self, instrument: &mut I) {
+        for note in self.sounding.drain(..) {
+            instrument.note_off(note);
+        }
+    }
+}
+
+impl Default for TrackPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instrument::test_support::{RecordedEvent, RecordingInstrument},
+        music::note,
+        sequence::pattern,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn step(note: Note, slide: bool) -> Step {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(note, 1, 100)).unwrap();
+        step.set_slide(slide);
+        step
+    }
+
+    #[test]
+    fn test_retrigger() {
+        let mut recorder = RecordingInstrument::new();
+        let mut player = TrackPlayer::new();
+
+        player
+            .play_step(&step(note::CFour, false), &mut recorder)
+            .unwrap();
+        player
+            .play_step(&step(note::GFour, false), &mut recorder)
+            .unwrap();
+
+        self::assert_eq!(
+            recorder.events.as_slice(),
+            &[
+                RecordedEvent::On(note::CFour),
+                RecordedEvent::On(note::GFour),
+                RecordedEvent::Off(note::CFour)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slide_glides_without_retrigger() {
+        let mut recorder = RecordingInstrument::new();
+        let mut player = TrackPlayer::new();
+
+        player
+            .play_step(&step(note::CFour, false), &mut recorder)
+            .unwrap();
+        player
+            .play_step(&step(note::GFour, true), &mut recorder)
+            .unwrap();
+        player.release(&mut recorder);
+
+        self::assert_eq!(
+            recorder.events.as_slice(),
+            &[
+                RecordedEvent::On(note::CFour),
+                RecordedEvent::Glide(note::CFour, note::GFour),
+                RecordedEvent::Off(note::GFour)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut recorder = RecordingInstrument::new();
+        let mut player = TrackPlayer::new();
+        player.set_transpose(7, TransposePolicy::Clamp);
+
+        player
+            .play_step(&step(note::CFour, false), &mut recorder)
+            .unwrap();
+        player.release(&mut recorder);
+
+        // Notes the transpose pushes out of range are skipped.
+        player.set_transpose(i16::MAX, TransposePolicy::Skip);
+        player
+            .play_step(&step(note::CFour, false), &mut recorder)
+            .unwrap();
+
+        self::assert_eq!(
+            recorder.events.as_slice(),
+            &[
+                RecordedEvent::On(note::GFour),
+                RecordedEvent::Off(note::GFour)
+            ]
+        );
+        assert!(player.sounding().is_empty());
+    }
+}
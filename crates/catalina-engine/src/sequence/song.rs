@@ -0,0 +1,265 @@
+//! Arranges patterns into a song by chaining them in an order.
+
+use heapless::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::sequence::PatternError;
+
+/// A pattern in a song and how many times it's played in a row.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SongEntry {
+    /// The index of the pattern in the project.
+    pub pattern: usize,
+    /// How many times the pattern is played before moving on.
+    pub repeats: u16,
+}
+
+/// Where a step falls in a [`Song`].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SongPosition {
+    /// The index of the entry in the song.
+    pub entry: usize,
+    /// The index of the pattern in the project.
+    pub pattern: usize,
+    /// How many times the pattern has already played in this entry.
+    pub repeat: u16,
+    /// The step within the pattern.
+    pub step: u64,
+}
+
+/// An ordered list of patterns to play, each repeated a number of times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Song<const ENTRIES: usize> {
+    entries: Vec<SongEntry, ENTRIES>,
+
+    /// Start over from the first entry after the last one instead of ending.
+    looping: bool,
+}
+
+impl<const ENTRIES: usize> Song<ENTRIES> {
+    /// Constructs a new empty song that doesn't loop.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            looping: false,
+        }
+    }
+
+    /// Adds a pattern to the end of the song, played `repeats` times.
+    pub fn push(&mut self, pattern: usize, repeats: u16) -> Result<(), PatternError> {
+        self.entries
+            .push(SongEntry { pattern, repeats })
+            .map_err(|_| PatternError::SongFull)
+    }
+
+    /// Removes all the entries from the song.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the entries of the song in the order they're played.
+    pub fn entries(&self) -> &[SongEntry] {
+        &self.entries
+    }
+
+    /// Sets if the song starts over from the first entry once it ends.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns true if the song starts over from the first entry once it ends.
+    pub const fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Returns the length of the song in steps.
+    ///
+    /// `pattern_length` should return the length in steps of the pattern at an index.
+    pub fn length(&self, pattern_length: impl Fn(usize) -> u64) -> u64 {
+        self.entries
+            .iter()
+            .map(|entry| pattern_length(entry.pattern) * entry.repeats as u64)
+            .sum()
+    }
+
+    /// Returns where a step counted from the start of the song
+    /// falls, or `None` once past the end of a song that doesn't loop.
+    ///
+    /// `pattern_length` should return the length in steps of the pattern at an index,
+    /// entries with empty patterns or no repeats are skipped.
+    pub fn position(
+        &self,
+        step: u64,
+        pattern_length: impl Fn(usize) -> u64,
+    ) -> Option<SongPosition> {
+        let mut step = step;
+        if self.looping {
+            let length = self.length(&pattern_length);
+            if length == 0 {
+                return None;
+            }
+
+            step %= length;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let length = pattern_length(entry.pattern);
+            let span = length * entry.repeats as u64;
+            if step < span {
+                return Some(SongPosition {
+                    entry: index,
+                    pattern: entry.pattern,
+                    repeat: (step / length) as u16,
+                    step: step % length,
+                });
+            }
+
+            step -= span;
+        }
+
+        None
+    }
+}
+
+impl<const ENTRIES: usize> Default for Song<ENTRIES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        music::note::{self, Note},
+        sequence::{
+            Project,
+            clock::ManualClock,
+            pattern::{self, Pattern, Step, Track},
+            transport::Transport,
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    /// A pattern with a single note on it's first step.
+    fn pattern(pitch: Note, steps: u8) -> Pattern<1, 4> {
+        let mut step = Step::new();
+        step.set_note(0, pattern::Note::new(pitch, 1, 100)).unwrap();
+        let mut track = Track::<4>::new();
+        track.set_step(0, step).unwrap();
+        for index in 1..steps as usize {
+            track.set_step(index, Step::new()).unwrap();
+        }
+
+        let mut pattern = Pattern::new();
+        pattern.set_track(0, track).unwrap();
+        pattern
+    }
+
+    #[test]
+    fn test_position() {
+        let mut song = Song::<4>::new();
+        song.push(1, 2).unwrap();
+        song.push(0, 1).unwrap();
+
+        let length = |pattern| if pattern == 0 { 3 } else { 4 };
+        self::assert_eq!(song.length(length), 11);
+
+        self::assert_eq!(
+            song.position(5, length),
+            Some(SongPosition {
+                entry: 0,
+                pattern: 1,
+                repeat: 1,
+                step: 1,
+            })
+        );
+        self::assert_eq!(
+            song.position(10, length),
+            Some(SongPosition {
+                entry: 1,
+                pattern: 0,
+                repeat: 0,
+                step: 2,
+            })
+        );
+        self::assert_eq!(song.position(11, length), None);
+
+        song.set_looping(true);
+        self::assert_eq!(song.position(11, length), song.position(0, length));
+    }
+
+    #[test]
+    fn test_transport_follows_song() {
+        let mut project = Project::<2, 1, 4>::new();
+        project.push_pattern(pattern(note::CFour, 4)).unwrap();
+        project.push_pattern(pattern(note::GFour, 4)).unwrap();
+
+        let mut song = Song::<2>::new();
+        song.push(0, 2).unwrap();
+        song.push(1, 1).unwrap();
+
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.clock_mut().set(6_000 * 20);
+
+        let mut notes = heapless::Vec::<(u64, Note), 8>::new();
+        let length = |index| project.pattern_length(index).unwrap_or(0);
+        while let Some((event, position)) = transport.poll_song(&song, length) {
+            let pattern = project.patterns[position.pattern].as_ref().unwrap();
+            for (_, track) in pattern.iter_active() {
+                for (index, step) in track.iter_active() {
+                    if index as u64 == position.step {
+                        for note in step.notes() {
+                            notes.push((event.step, note.note())).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+
+        self::assert_eq!(
+            notes.as_slice(),
+            &[(0, note::CFour), (4, note::CFour), (8, note::GFour)]
+        );
+
+        // The song ran out after 12 steps so the transport stopped.
+        self::assert_eq!(transport.is_playing(), false);
+    }
+
+    #[test]
+    fn test_last_step_plays_out() {
+        let mut project = Project::<1, 1, 4>::new();
+        project.push_pattern(pattern(note::CFour, 4)).unwrap();
+
+        let mut song = Song::<1>::new();
+        song.push(0, 1).unwrap();
+
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        let length = |index| project.pattern_length(index).unwrap_or(0);
+
+        // Just past the start of the last step, which still has to play out.
+        transport.clock_mut().set(6_000 * 3 + 1);
+        let mut steps = 0;
+        while transport.poll_song(&song, length).is_some() {
+            steps += 1;
+        }
+        self::assert_eq!(steps, 4);
+        self::assert_eq!(transport.is_playing(), true);
+        self::assert_eq!(transport.is_stopping(), false);
+
+        // The end of the last step is the end of the song.
+        transport.clock_mut().set(6_000 * 4);
+        self::assert_eq!(transport.poll_song(&song, length), None);
+        self::assert_eq!(transport.is_playing(), false);
+        self::assert_eq!(transport.is_stopping(), true);
+    }
+}
@@ -0,0 +1,592 @@
+//! The transport keeps track of the tempo and play state
+//! of the sequencer and turns clock time into steps.
+
+use crate::sequence::{
+    ProjectSettings,
+    clock::Clock,
+    pattern::MICRO_TIMING_TICKS,
+    song::{Song, SongPosition},
+};
+
+/// Emitted by the transport when the clock reaches the start of a step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StepEvent {
+    /// The index of the step since the transport was started.
+    pub step: u64,
+    /// The sample position of the start of the step.
+    pub sample: u64,
+}
+
+/// The default tick resolution of the transport in pulses per quarter note,
+/// the resolution commonly used by hardware sequencers.
+pub const DEFAULT_PPQN: u32 = 96;
+
+/// The default time in seconds the output fades out over when the transport stops.
+pub const DEFAULT_STOP_FADE_TIME: f32 = 0.005;
+
+/// Converts the time reported by a [`Clock`] into sequencer steps.
+pub struct Transport<C: Clock> {
+    /// The clock used to determine the current time.
+    clock: C,
+
+    /// The sample rate the audio engine is being ran at.
+    sample_rate: usize,
+    /// The tempo in beats per minute.
+    bpm: f32,
+    /// The number of sequencer steps in a beat.
+    ///
+    /// Defaults to 4, making each step a 16th note in 4/4.
+    steps_per_beat: u32,
+    /// How far every other step is pushed late, from 0.0 (straight) to 1.0.
+    swing: f32,
+    /// The tick resolution in pulses (ticks) per quarter note.
+    ///
+    /// Defaults to [`DEFAULT_PPQN`].
+    ppqn: u32,
+
+    playing: bool,
+    /// True while the output is fading out after being stopped.
+    stopping: bool,
+    /// The time in seconds the output fades out over when stopped.
+    stop_fade_time: f32,
+    /// The length of the current stop fade in samples.
+    fade_length: u32,
+    /// Samples left until the stop fade reaches silence.
+    fade_remaining: u32,
+    /// The clock position the step positions are measured from, where
+    /// the transport was started or the tempo last changed.
+    start: u64,
+    /// How many steps, including any fraction of a step, had
+    /// played by `start`, zero unless the tempo was changed.
+    start_step: f64,
+    /// The index of the next step to emit.
+    next_step: u64,
+}
+
+impl<C: Clock> Transport<C> {
+    /// Constructs a new stopped transport driven by the provided clock.
+    pub fn new(clock: C, sample_rate: usize, bpm: f32) -> Self {
+        Self {
+            clock,
+            sample_rate,
+            bpm,
+            steps_per_beat: 4,
+            swing: 0.0,
+            ppqn: DEFAULT_PPQN,
+            playing: false,
+            stopping: false,
+            stop_fade_time: DEFAULT_STOP_FADE_TIME,
+            fade_length: 0,
+            fade_remaining: 0,
+            start: 0,
+            start_step: 0.0,
+            next_step: 0,
+        }
+    }
+
+    /// Returns a reference to the clock driving the transport.
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns a mutable reference to the clock driving the transport.
+    #[inline]
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Returns the tempo in beats per minute.
+    #[inline]
+    pub const fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Sets the tempo in beats per minute.
+    ///
+    /// While playing, the steps are re-anchored to the current clock
+    /// position so the steps already played stay where they were, and
+    /// only the steps from now on move to the new tempo.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if self.playing {
+            let now = self.clock.now();
+            self.start_step += now.saturating_sub(self.start) as f64 / self.samples_per_step();
+            self.start = now;
+        }
+
+        self.bpm = bpm;
+    }
+
+    /// Sets how many sequencer steps make up a beat.
+    pub fn set_steps_per_beat(&mut self, steps: u32) {
+        self.steps_per_beat = steps;
+    }
+
+    /// Returns how far every other step is pushed late, from 0.0 to 1.0.
+    #[inline]
+    pub const fn swing(&self) -> f32 {
+        self.swing
+    }
+
+    /// Sets how far every other step is pushed late, clamped from 0.0 to 1.0.
+    ///
+    /// At full swing the odd steps are a third of a step late, giving
+    /// pairs of steps a long-short triplet feel.
+    pub fn set_swing(&mut self, swing: f32) {
+        self.swing = swing.clamp(0.0, 1.0);
+    }
+
+    /// Takes on the tempo and swing of a project.
+    pub fn apply_settings(&mut self, settings: &ProjectSettings) {
+        self.set_bpm(settings.bpm);
+        self.set_swing(settings.swing);
+    }
+
+    /// Returns the tick resolution in pulses per quarter note.
+    #[inline]
+    pub const fn ppqn(&self) -> u32 {
+        self.ppqn
+    }
+
+    /// Sets the tick resolution in pulses per quarter note, i.e. 24 to follow MIDI clock.
+    pub fn set_ppqn(&mut self, ppqn: u32) {
+        self.ppqn = ppqn;
+    }
+
+    /// Returns the length of a beat in samples.
+    pub fn samples_per_beat(&self) -> f64 {
+        self.sample_rate as f64 * 60.0 / self.bpm as f64
+    }
+
+    /// Returns the length of a step in samples.
+    pub fn samples_per_step(&self) -> f64 {
+        self.samples_per_beat() / self.steps_per_beat as f64
+    }
+
+    /// Returns the length of a tick in samples.
+    pub fn samples_per_tick(&self) -> f64 {
+        self.samples_per_beat() / self.ppqn as f64
+    }
+
+    /// Returns how many ticks make up a step, which can be
+    /// fractional when the steps don't divide a beat evenly.
+    pub fn ticks_per_step(&self) -> f64 {
+        self.ppqn as f64 / self.steps_per_beat as f64
+    }
+
+    /// Returns the clock position of the start of the provided tick.
+    pub fn tick_position(&self, tick: u64) -> u64 {
+        let ticks = tick as f64 - self.start_step * self.ticks_per_step();
+        self.offset_position(ticks * self.samples_per_tick())
+    }
+
+    /// Returns the tick that a clock position falls within, counted
+    /// from when the transport started. Positions before the start
+    /// are in the first tick.
+    pub fn tick_at(&self, position: u64) -> u64 {
+        let samples = position.saturating_sub(self.start) as f64;
+        let ticks = samples / self.samples_per_tick() + self.start_step * self.ticks_per_step();
+
+        // Nudge up slightly so a position rounded from the start of
+        // a tick by `tick_position` doesn't land in the tick before.
+        libm::floor(ticks + 1e-6) as u64
+    }
+
+    /// Returns the step that a tick falls within.
+    pub fn tick_to_step(&self, tick: u64) -> u64 {
+        libm::floor(tick as f64 / self.ticks_per_step() + 1e-9) as u64
+    }
+
+    /// Returns the tick at the start of the provided step, rounded
+    /// to the nearest tick when steps don't divide a beat evenly.
+    pub fn step_to_tick(&self, step: u64) -> u64 {
+        libm::round(step as f64 * self.ticks_per_step()) as u64
+    }
+
+    /// Returns the clock position of the start of the provided step.
+    pub fn step_position(&self, step: u64) -> u64 {
+        self.offset_position((step as f64 - self.start_step) * self.samples_per_step())
+    }
+
+    /// Returns the clock position an amount of samples from the anchor
+    /// the steps are measured from, never landing before the anchor.
+    fn offset_position(&self, samples: f64) -> u64 {
+        self.start + libm::round(samples.max(0.0)) as u64
+    }
+
+    /// Returns the clock position of a step nudged by an amount of micro-timing ticks.
+    ///
+    /// The nudge and swing together are clamped to half a step either way
+    /// so that nudged steps stay in order, and never land before the
+    /// transport started.
+    pub fn nudged_step_position(&self, step: u64, micro_timing: i8) -> u64 {
+        let half_step = self.samples_per_step() / 2.0;
+        let offset = micro_timing as f64 / MICRO_TIMING_TICKS as f64 * self.samples_per_step();
+
+        // Swing pushes the odd steps late, up to a third of a step.
+        let swing = if step % 2 == 1 {
+            self.swing as f64 / 3.0 * self.samples_per_step()
+        } else {
+            0.0
+        };
+
+        let position = (step as f64 - self.start_step) * self.samples_per_step()
+            + (offset + swing).clamp(-half_step, half_step);
+        self.offset_position(position)
+    }
+
+    /// Sets how long in seconds the output fades out over when
+    /// the transport stops. Zero stops without fading.
+    pub fn set_stop_fade_time(&mut self, seconds: f32) {
+        self.stop_fade_time = seconds.max(0.0);
+    }
+
+    /// Starts playback from the first step at the current clock position.
+    ///
+    /// Cancels the fade out of a previous stop that hadn't finished.
+    pub fn play(&mut self) {
+        self.playing = true;
+        self.stopping = false;
+        self.start = self.clock.now();
+        self.start_step = 0.0;
+        self.next_step = 0;
+    }
+
+    /// Stops playback.
+    ///
+    /// No more steps are emitted, and the output passed through
+    /// [`Transport::process_fade`] fades out to avoid a click.
+    pub fn stop(&mut self) {
+        let length = libm::roundf(self.stop_fade_time * self.sample_rate as f32) as u32;
+        if self.playing && length > 0 {
+            self.stopping = true;
+            self.fade_length = length;
+            self.fade_remaining = length;
+        }
+
+        self.playing = false;
+    }
+
+    /// Returns true if the transport is playing.
+    #[inline]
+    pub const fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns true while the output is fading out after a stop.
+    #[inline]
+    pub const fn is_stopping(&self) -> bool {
+        self.stopping
+    }
+
+    /// Applies the stop fade to a rendered block of the master output in place.
+    ///
+    /// Returns true once the fade has reached silence, at which point the
+    /// caller should call [`Instrument::all_notes_off`] on the instruments
+    /// so nothing is left sounding when the output is back at full gain.
+    ///
+    /// [`Instrument::all_notes_off`]: crate::instrument::Instrument::all_notes_off
+    pub fn process_fade(&mut self, buffer: &mut [f32]) -> bool {
+        if !self.stopping {
+            return false;
+        }
+
+        for sample in buffer.iter_mut() {
+            self.fade_remaining = self.fade_remaining.saturating_sub(1);
+            *sample *= self.fade_remaining as f32 / self.fade_length as f32;
+        }
+
+        if self.fade_remaining > 0 {
+            return false;
+        }
+
+        self.stopping = false;
+        true
+    }
+
+    /// Returns the next step the clock has reached since the last poll.
+    ///
+    /// Call this repeatedly until it returns `None`, a single clock
+    /// advance may cross several steps when rendering large blocks.
+    pub fn poll(&mut self) -> Option<StepEvent> {
+        self.poll_nudged(|_| 0)
+    }
+
+    /// Returns the next step the clock has reached since the last poll,
+    /// with each step nudged by the micro-timing returned for it.
+    ///
+    /// The callback is given the index of the upcoming step and should
+    /// return the micro-timing of the matching step in the pattern.
+    pub fn poll_nudged(&mut self, micro_timing: impl Fn(u64) -> i8) -> Option<StepEvent> {
+        if !self.playing {
+            return None;
+        }
+
+        let sample = self.nudged_step_position(self.next_step, micro_timing(self.next_step));
+        if sample > self.clock.now() {
+            return None;
+        }
+
+        let event = StepEvent {
+            step: self.next_step,
+            sample,
+        };
+        self.next_step += 1;
+
+        Some(event)
+    }
+
+    /// Returns the next step the clock has reached since the last poll,
+    /// along with where that step falls in the song.
+    ///
+    /// The transport moves on to the next entry of the song once the
+    /// current pattern has played through it's repeats, `pattern_length`
+    /// should return the length in steps of the pattern at an index,
+    /// see [`Project::pattern_length`](crate::sequence::Project::pattern_length).
+    ///
+    /// The transport stops when the clock reaches the end of a song that
+    /// doesn't loop, once the last step has played out rather than as soon
+    /// as it has been emitted.
+    pub fn poll_song<const ENTRIES: usize>(
+        &mut self,
+        song: &Song<ENTRIES>,
+        pattern_length: impl Fn(usize) -> u64,
+    ) -> Option<(StepEvent, SongPosition)> {
+        if !self.playing {
+            return None;
+        }
+
+        let Some(position) = song.position(self.next_step, &pattern_length) else {
+            // The song ends where the step after it's last would start.
+            if self.clock.now() >= self.step_position(self.next_step) {
+                self.stop();
+            }
+            return None;
+        };
+
+        self.poll().map(|event| (event, position))
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::clock::ManualClock;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_steps_at_boundaries() {
+        // 120 BPM at 48kHz with 16th note steps is 6000 samples per step.
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        self::assert_eq!(transport.samples_per_step(), 6_000.0);
+
+        self::assert_eq!(transport.poll(), None);
+        transport.play();
+
+        self::assert_eq!(transport.poll(), Some(StepEvent { step: 0, sample: 0 }));
+        self::assert_eq!(transport.poll(), None);
+
+        transport.clock_mut().set(5_999);
+        self::assert_eq!(transport.poll(), None);
+
+        transport.clock_mut().advance(1);
+        self::assert_eq!(
+            transport.poll(),
+            Some(StepEvent {
+                step: 1,
+                sample: 6_000
+            })
+        );
+        self::assert_eq!(transport.poll(), None);
+    }
+
+    #[test]
+    fn test_tempo_change_mid_play() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.clock_mut().set(9_000);
+        while transport.poll().is_some() {}
+
+        // Half way through step 1, doubling the tempo halves
+        // the rest of it rather than moving steps already due.
+        transport.set_bpm(240.0);
+        self::assert_eq!(transport.poll(), None);
+        self::assert_eq!(transport.step_position(2), 10_500);
+
+        transport.clock_mut().set(10_499);
+        self::assert_eq!(transport.poll(), None);
+        transport.clock_mut().set(10_500);
+        self::assert_eq!(
+            transport.poll(),
+            Some(StepEvent {
+                step: 2,
+                sample: 10_500
+            })
+        );
+
+        // And the steps after carry on at the new tempo.
+        self::assert_eq!(transport.step_position(3), 13_500);
+
+        // Restarting measures from the start again.
+        transport.play();
+        self::assert_eq!(transport.step_position(1), 10_500 + 3_000);
+    }
+
+    #[test]
+    fn test_crossing_several_steps() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.clock_mut().set(18_000);
+
+        let mut steps = heapless::Vec::<u64, 8>::new();
+        while let Some(event) = transport.poll() {
+            steps.push(event.step).unwrap();
+        }
+
+        self::assert_eq!(steps.as_slice(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_stopped_emits_nothing() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.stop();
+        self::assert_eq!(transport.poll(), None);
+    }
+
+    #[test]
+    fn test_stop_fades_out() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.stop();
+        self::assert_eq!(transport.is_stopping(), true);
+
+        // 5ms at 48kHz fades out over 240 samples.
+        let mut buffer = [1.0; 256];
+        self::assert_eq!(transport.process_fade(&mut buffer), true);
+        self::assert_eq!(transport.is_stopping(), false);
+
+        assert!(buffer[0] > 0.99);
+        assert!(buffer.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(buffer[119] > 0.45 && buffer[119] < 0.55);
+        assert!(buffer[239..].iter().all(|sample| *sample == 0.0));
+
+        // Once faded the output passes through again.
+        let mut buffer = [1.0; 4];
+        self::assert_eq!(transport.process_fade(&mut buffer), false);
+        self::assert_eq!(buffer, [1.0; 4]);
+    }
+
+    #[test]
+    fn test_fade_spans_blocks() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        transport.stop();
+
+        let mut buffer = [1.0; 128];
+        self::assert_eq!(transport.process_fade(&mut buffer), false);
+        assert!(buffer[127] > 0.0);
+        self::assert_eq!(transport.process_fade(&mut buffer), true);
+    }
+
+    #[test]
+    fn test_micro_timing_delays_step() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.play();
+        self::assert_eq!(transport.poll().map(|e| e.step), Some(0));
+
+        // A quarter of a step late is 1500 samples at 6000 samples per step.
+        let nudge = |step| if step == 1 { 32 } else { 0 };
+
+        transport.clock_mut().set(7_499);
+        self::assert_eq!(transport.poll_nudged(nudge), None);
+
+        transport.clock_mut().advance(1);
+        self::assert_eq!(
+            transport.poll_nudged(nudge),
+            Some(StepEvent {
+                step: 1,
+                sample: 7_500
+            })
+        );
+    }
+
+    #[test]
+    fn test_swing_delays_odd_steps() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.set_swing(1.0);
+
+        // Full swing makes pairs of 6000 sample steps 8000 then 4000 long.
+        self::assert_eq!(transport.nudged_step_position(0, 0), 0);
+        self::assert_eq!(transport.nudged_step_position(1, 0), 8_000);
+        self::assert_eq!(transport.nudged_step_position(2, 0), 12_000);
+        self::assert_eq!(transport.nudged_step_position(3, 0), 20_000);
+    }
+
+    #[test]
+    fn test_swing_and_nudge_stay_in_order() {
+        let mut transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+        transport.set_swing(1.0);
+
+        // The swing and nudge together are still limited to half a step,
+        // so a swung step pushed late can't pass the step after it.
+        self::assert_eq!(transport.nudged_step_position(1, 64), 9_000);
+        self::assert_eq!(transport.nudged_step_position(2, -64), 9_000);
+        self::assert_eq!(transport.nudged_step_position(3, -64), 17_000);
+
+        transport.play();
+        transport.clock_mut().set(12_000);
+        let nudge = |step| match step {
+            1 => 64,
+            2 => -64,
+            _ => 0,
+        };
+        let mut last = 0;
+        while let Some(event) = transport.poll_nudged(nudge) {
+            assert!(
+                event.sample >= last,
+                "step {} went back in time",
+                event.step
+            );
+            last = event.sample;
+        }
+    }
+
+    #[test]
+    fn test_tick_resolution() {
+        // 120 BPM at 44.1kHz is 22,050 samples a beat, over 96 ticks.
+        let transport = Transport::new(ManualClock::new(), 44_100, 120.0);
+        self::assert_eq!(transport.ppqn(), 96);
+        self::assert_eq!(transport.samples_per_tick(), 229.6875);
+        self::assert_eq!(transport.ticks_per_step(), 24.0);
+
+        // A bar of 4/4 later is exactly 88,200 samples.
+        self::assert_eq!(transport.tick_position(384), 88_200);
+        // 7 ticks is 1607.8125 samples, which rounds up.
+        self::assert_eq!(transport.tick_position(7), 1_608);
+        self::assert_eq!(transport.tick_at(1_608), 7);
+        self::assert_eq!(transport.tick_at(1_607), 6);
+
+        // Ticks and steps line up with the step timing.
+        self::assert_eq!(transport.step_to_tick(3), 72);
+        self::assert_eq!(transport.tick_to_step(71), 2);
+        self::assert_eq!(transport.tick_to_step(72), 3);
+        self::assert_eq!(
+            transport.tick_position(transport.step_to_tick(5)),
+            transport.step_position(5)
+        );
+    }
+
+    #[test]
+    fn test_micro_timing_is_clamped() {
+        let transport = Transport::new(ManualClock::new(), 48_000, 120.0);
+
+        // Nudges are limited to half a step so they can't pass their neighbours.
+        self::assert_eq!(transport.nudged_step_position(1, 127), 9_000);
+        self::assert_eq!(transport.nudged_step_position(2, -128), 9_000);
+        // And a nudge can't move the first step before the transport started.
+        self::assert_eq!(transport.nudged_step_position(0, -64), 0);
+    }
+}
@@ -0,0 +1,43 @@
+//! Measures how many samples per second the additive synth renders at
+//! with a chord held, across block sizes from a single sample up to 1024.
+//!
+//! Run with `cargo bench -p catalina-instruments --features std`. The
+//! throughput should stay roughly flat as the blocks get smaller.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use catalina_engine::{audio::AudioSource, instrument::Instrument, music::note};
+use catalina_instruments::synths::additive::AdditiveSynth;
+
+const BLOCK_SIZES: [usize; 5] = [1, 16, 64, 256, 1024];
+
+/// The amount of samples rendered per iteration, in blocks of the size being measured.
+const SAMPLES: usize = 4096;
+
+fn bench_additive(c: &mut Criterion) {
+    let mut group = c.benchmark_group("additive");
+    group.throughput(Throughput::Elements(SAMPLES as u64));
+
+    for block_size in BLOCK_SIZES {
+        let mut buffer = vec![0.0_f32; block_size];
+
+        let mut synth = AdditiveSynth::new(48_000);
+        synth
+            .chord(&[note::CFour, note::EFour, note::GFour, note::BFour], 100)
+            .unwrap();
+
+        group.bench_function(BenchmarkId::new("chord", block_size), |b| {
+            b.iter(|| {
+                for _ in 0..SAMPLES / block_size {
+                    synth.render(black_box(&mut buffer));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_additive);
+criterion_main!(benches);
@@ -1,8 +1,16 @@
 use heapless::index_map::FnvIndexMap;
 
 use catalina_engine::{
-    audio::{AudioSource, signal::Signal},
-    instrument::{Instrument, NoteError},
+    audio::{
+        AudioSource,
+        envelope::adsr::Envelope,
+        signal::{ColoredNoise, NoiseColor, Signal},
+    },
+    core::{
+        Hertz,
+        param::{ParamDescriptor, ParamError, ParamId, Smoothed},
+    },
+    instrument::{Instrument, MasterOutput, NoteError},
     music::note::{self, Note},
 };
 
@@ -12,8 +20,15 @@ pub(crate) use oscillator::AdditiveOscillator;
 pub mod voice;
 pub(crate) use voice::Voice;
 
+pub mod params;
+pub use params::AdditiveParam;
+
 /// A type of synthesizer that adds multiple oscillators together, typically sine
 /// waves, at different frequencies, amplitudes and phases to build harmonics.
+///
+/// Cloning the synth clones the sounding voices too, use
+/// [`AdditiveSynth::duplicate_patch`] to copy just the settings.
+#[derive(Clone)]
 pub struct AdditiveSynth {
     sample_rate: usize,
 
@@ -26,6 +41,47 @@ pub struct AdditiveSynth {
     ///
     /// Each voice pair tracks the phase data for that note.
     voices: FnvIndexMap<Note, Voice, 8>,
+    /// Set when a released voice finishes fading out, so the
+    /// voices are only scanned to free it once per block.
+    voices_finished: bool,
+
+    /// Set when a voice or oscillator changed in a way that changes the
+    /// voices' phase increments, so they're only worked out when needed.
+    increments_stale: bool,
+
+    /// The pitch bend requested by the host, in semitones.
+    pitch_bend: f32,
+
+    /// The pitch bend that was applied at the end of the last rendered block.
+    ///
+    /// Used as the starting point of the ramp towards `pitch_bend`
+    /// so that bend changes don't jump at block boundaries.
+    applied_pitch_bend: f32,
+
+    /// The time in seconds it takes a voice to glide to a new note.
+    glide_time: f32,
+
+    /// The envelope each new voice sweeps it's pitch with.
+    pitch_envelope: Envelope,
+    /// How far in semitones the pitch envelope raises the
+    /// pitch at it's peak, zero disables the pitch envelope.
+    pitch_envelope_amount: f32,
+
+    /// The time in seconds voices fade in and out over.
+    ///
+    /// Voices fade in and out over a few milliseconds when they start
+    /// and stop, independent of any envelope, so they never click.
+    declick_time: f32,
+    /// How much the declick gain of a voice changes each sample.
+    declick_step: f32,
+
+    /// How much each new voice's starting phase and tuning is randomized, from 0.0 to 1.0.
+    humanize: f32,
+    /// Seeded source of randomness for humanizing voices, so renders are reproducible.
+    humanize_noise: ColoredNoise,
+
+    /// Scales and meters the summed output of the voices.
+    output: MasterOutput,
 }
 
 impl AdditiveSynth {
@@ -43,23 +99,97 @@ impl AdditiveSynth {
             ],
 
             voices: FnvIndexMap::new(),
+            voices_finished: false,
+            increments_stale: false,
+
+            pitch_bend: 0.0,
+            applied_pitch_bend: 0.0,
+
+            glide_time: 0.06,
+
+            pitch_envelope: Envelope::new(sample_rate),
+            pitch_envelope_amount: 0.0,
+
+            declick_time: DEFAULT_DECLICK_TIME,
+            declick_step: declick_step(DEFAULT_DECLICK_TIME, sample_rate),
+
+            humanize: 0.0,
+            humanize_noise: ColoredNoise::new(DEFAULT_HUMANIZE_SEED, NoiseColor::White),
+
+            output: MasterOutput::new(),
         }
     }
-}
 
-/// The interfaces for controlling the instrument from the framework.
-impl Instrument for AdditiveSynth {
-    fn init(&mut self) {}
+    /// Sets how long in seconds voices take to fade in and out when they
+    /// start and stop. Zero starts and stops voices instantly.
+    pub fn set_declick_time(&mut self, seconds: f32) {
+        self.declick_time = seconds;
+        self.declick_step = declick_step(seconds, self.sample_rate);
+    }
+
+    /// Sets how much each new voice is randomized, from 0.0 to 1.0, to avoid
+    /// the phase-locked sound of every note starting identically.
+    ///
+    /// New voices start at a random phase and are detuned by up to
+    /// [`MAX_HUMANIZE_CENTS`] at full humanize. Zero disables it.
+    pub fn set_humanize(&mut self, amount: f32) {
+        self.humanize = amount.clamp(0.0, 1.0);
+    }
+
+    /// Reseeds the randomness used for humanizing voices,
+    /// the same seed always produces the same voices.
+    pub fn set_humanize_seed(&mut self, seed: u64) {
+        self.humanize_noise = ColoredNoise::new(seed, NoiseColor::White);
+    }
+
+    /// Constructs a new voice playing a frequency, randomizing it by the humanize amount.
+    fn new_voice(&mut self, frequency: Hertz) -> Voice {
+        let mut voice = Voice::new(self.sample_rate, frequency, self.pitch_envelope.clone());
+
+        if self.humanize > 0.0 {
+            // The noise is from -1.0 to 1.0, the phases need 0.0 to 1.0.
+            let mut phase = || (self.humanize_noise.next_sample() + 1.0) * 0.5 * self.humanize;
+            voice.phase_0 = phase();
+            voice.phase_1 = phase();
+            voice.phase_2 = phase();
+            voice.phase_3 = phase();
+
+            let cents = self.humanize_noise.next_sample() * self.humanize * MAX_HUMANIZE_CENTS;
+            voice.detune = bend_ratio(cents / 100.0);
+        }
+
+        voice
+    }
+
+    /// Starts a voice playing a frequency, keyed by a note so it can be released.
+    fn start_voice(&mut self, note: Note, frequency: Hertz) -> Result<(), NoteError> {
+        self.increments_stale = true;
+
+        // Pressing a note that's still sounding, i.e. fading out after a
+        // quick release, picks the voice back up from it's current level
+        // and phase, restarting it from silence would click.
+        if let Some(voice) = self.voices.get_mut(&note) {
+            voice.stopping = false;
+            voice.frequency = frequency;
+            voice.glide = Smoothed::new(self.sample_rate, 0.0, 1.0);
+            voice.pitch_envelope = self.pitch_envelope.clone();
+            return Ok(());
+        }
+
+        // Make room from any voices that finished since the last block.
+        if self.voices.len() == self.voices.capacity() {
+            self.free_finished_voices();
+        }
 
-    /// Called when a note is pressed.
-    fn note_on(&mut self, note: Note, _velocity: u8) -> Result<(), NoteError> {
         // Attempt to add a voice.
         //
         // .insert() will return an error if the voices map is full.
+        let voice = self.new_voice(frequency);
         self.voices
             .insert(
-                note,         // This is the note we're adding a voice for
-                Voice::new(), // This holds the data for the voice.
+                note, // This is the note we're adding a voice for
+                // This holds the data for the voice.
+                voice,
             )
             .map_err(|_| NoteError::NoVoices)?;
 
@@ -69,32 +199,130 @@ impl Instrument for AdditiveSynth {
         Ok(())
     }
 
-    /// Called when a note is released.
-    fn note_off(&mut self, note: Note) {
-        // Remove the voice for the note when the note is released.
-        self.voices.remove(&note);
+    /// Frees up the voices that have finished fading out since they were released.
+    fn free_finished_voices(&mut self) {
+        if !self.voices_finished {
+            return;
+        }
+        self.voices_finished = false;
+
+        self.voices
+            .retain(|_, voice| !(voice.stopping && voice.level <= 0.0));
     }
-}
 
-/// Allows the synth to be used in [`Signal`]` chains.
-impl Signal for AdditiveSynth {
-    type Frame = f32;
+    /// Configures a pitch sweep at the start of each note, i.e. for punchy kicks.
+    ///
+    /// Notes start `semitones` above (or below, when negative) their
+    /// pitch and fall back to it over `decay` seconds. An amount of
+    /// zero disables the pitch envelope.
+    pub fn set_pitch_envelope(&mut self, semitones: f32, decay: f32) {
+        self.pitch_envelope_amount = semitones;
 
-    /// Produces the next frame of audio from the synth.
-    fn next(&mut self) -> Self::Frame {
+        self.pitch_envelope.set_attack_time(0.0, 0.0);
+        self.pitch_envelope.set_decay_time(decay);
+        self.pitch_envelope.set_sustain_level(0.0);
+    }
+
+    /// Duplicates the synth's settings without any of it's sounding
+    /// voices or pitch bend, i.e. for A/B comparing patches.
+    pub fn duplicate_patch(&self) -> Self {
+        Self {
+            voices: FnvIndexMap::new(),
+            pitch_bend: 0.0,
+            applied_pitch_bend: 0.0,
+            ..self.clone()
+        }
+    }
+
+    /// Sets the time in seconds it takes a voice to glide to a new note.
+    pub fn set_glide_time(&mut self, seconds: f32) {
+        self.glide_time = seconds;
+    }
+
+    /// Sets the level of one of the optional oscillators,
+    /// enabling it when the level is above zero.
+    fn set_oscillator_level(&mut self, index: usize, level: f32) {
+        self.increments_stale = true;
+        self.oscillators[index].set_level(level);
+        self.oscillators[index].set_enabled(level > 0.0);
+    }
+
+    /// Works out how far each voice's oscillator phases advance per sample.
+    ///
+    /// The oscillator frequencies only change between blocks, so this
+    /// keeps the division by the sample rate out of the sample loop, and
+    /// does nothing unless a voice or oscillator changed since last time,
+    /// so tiny blocks don't pay for it over and over.
+    fn update_increments(&mut self) {
+        if !self.increments_stale {
+            return;
+        }
+        self.increments_stale = false;
+
+        let sample_rate = self.sample_rate as f32;
+        for voice in self.voices.values_mut() {
+            for (increment, osc) in voice.increments.iter_mut().zip(self.oscillators.iter()) {
+                *increment = osc.played_frequency(voice.frequency).hertz() / sample_rate;
+            }
+        }
+    }
+
+    /// Turns the oscillators into the odd harmonics of the played note, the
+    /// 1st, 3rd, 5th and 7th, for hollow square-like tones.
+    ///
+    /// See [`AdditiveSynth::set_even_harmonics`] for the arguments.
+    pub fn set_odd_harmonics(&mut self, count: usize, rolloff: f32) {
+        self.set_harmonics(count, rolloff, |index| index * 2 + 1);
+    }
+
+    /// Turns the oscillators into the even harmonics of the played note,
+    /// the 2nd, 4th, 6th and 8th, which sound an octave above the note.
+    ///
+    /// Up to `count` of the four oscillators are used, the rest are
+    /// disabled. Each harmonic `n` is played at a level of `1 / n^rolloff`,
+    /// so a rolloff of 1.0 matches the falloff of square and saw waves,
+    /// and 0.0 plays every harmonic at full level.
+    pub fn set_even_harmonics(&mut self, count: usize, rolloff: f32) {
+        self.set_harmonics(count, rolloff, |index| index * 2 + 2);
+    }
+
+    /// Sets the oscillators to the harmonics returned for each oscillator index.
+    fn set_harmonics(&mut self, count: usize, rolloff: f32, harmonic: impl Fn(usize) -> usize) {
+        self.increments_stale = true;
+
+        for (index, osc) in self.oscillators.iter_mut().enumerate() {
+            let harmonic = harmonic(index) as f32;
+            osc.set_harmonic(Some(harmonic));
+            osc.set_level(libm::powf(harmonic, -rolloff));
+            osc.set_enabled(index < count.max(1));
+        }
+    }
+
+    /// Produces the next frame of audio with the oscillator
+    /// frequencies multiplied by the provided bend ratio.
+    fn next_bent(&mut self, bend: f32) -> f32 {
         // The final sample for the frame.
         //
         // This is the result of all the voices (active notes) summed together.
         let mut sample = 0.0;
 
         // Loop through each active voice and sum them for the frame.
-        for (note, voice) in self.voices.iter_mut() {
+        for voice in self.voices.values_mut() {
             // The sample for this voice.
             //
             // This is the result of the oscillators summed
             // together (the add in **add**itive synthesis).
             let mut voice_sample = 0.0;
 
+            // Any glide between notes scales the frequency like the bend does.
+            let mut bend = bend * voice.glide.next() * voice.detune;
+
+            // As does the pitch envelope, while the note is held.
+            if self.pitch_envelope_amount != 0.0 {
+                let sweep = voice.pitch_envelope.process(true);
+                bend *= bend_ratio(self.pitch_envelope_amount * sweep);
+            }
+
             // Process the first oscillator for the voice, if enabled.
             if self.oscillators[0].is_enabled() {
                 let osc = &self.oscillators[0];
@@ -103,10 +331,9 @@ impl Signal for AdditiveSynth {
 
                 // Shift the base oscillator phase of the voice
                 // so that the voices oscillate independently.
-                voice.phase_0 =
-                    voice.phase_0 + (osc.note_frequency(note).hertz() / self.sample_rate as f32);
+                voice.phase_0 = voice.phase_0 + voice.increments[0] * bend;
                 if voice.phase_0 >= 1.0 {
-                    voice.phase_0 = 0.0;
+                    voice.phase_0 -= 1.0;
                 }
             }
 
@@ -118,10 +345,9 @@ impl Signal for AdditiveSynth {
 
                 // Shift the base oscillator phase of the voice
                 // so that the voices oscillate independently.
-                voice.phase_1 =
-                    voice.phase_1 + (osc.note_frequency(note).hertz() / self.sample_rate as f32);
+                voice.phase_1 = voice.phase_1 + voice.increments[1] * bend;
                 if voice.phase_1 >= 1.0 {
-                    voice.phase_1 = 0.0;
+                    voice.phase_1 -= 1.0;
                 }
             }
 
@@ -133,10 +359,9 @@ impl Signal for AdditiveSynth {
 
                 // Shift the base oscillator phase of the voice
                 // so that the voices oscillate independently.
-                voice.phase_2 =
-                    voice.phase_2 + (osc.note_frequency(note).hertz() / self.sample_rate as f32);
+                voice.phase_2 = voice.phase_2 + voice.increments[2] * bend;
                 if voice.phase_2 >= 1.0 {
-                    voice.phase_2 = 0.0;
+                    voice.phase_2 -= 1.0;
                 }
             }
 
@@ -148,14 +373,26 @@ impl Signal for AdditiveSynth {
 
                 // Shift the base oscillator phase of the voice
                 // so that the voices oscillate independently.
-                voice.phase_3 =
-                    voice.phase_3 + (osc.note_frequency(note).hertz() / self.sample_rate as f32);
+                voice.phase_3 = voice.phase_3 + voice.increments[3] * bend;
                 if voice.phase_3 >= 1.0 {
-                    voice.phase_3 = 0.0;
+                    voice.phase_3 -= 1.0;
                 }
             }
 
-            sample = sample + voice_sample;
+            // Fade the voice in or out to avoid clicks when it starts or stops.
+            voice.level = if voice.stopping {
+                (voice.level - self.declick_step).max(0.0)
+            } else {
+                (voice.level + self.declick_step).min(1.0)
+            };
+
+            // Voices that have finished fading out are freed up
+            // at the end of the block, see `free_finished_voices`.
+            if voice.stopping && voice.level <= 0.0 {
+                self.voices_finished = true;
+            }
+
+            sample = sample + voice_sample * voice.level;
         }
 
         // Note that the resulting buffer will be clipped on playback
@@ -168,18 +405,704 @@ impl Signal for AdditiveSynth {
     }
 }
 
+/// The most a voice is detuned by in cents at full humanize.
+pub const MAX_HUMANIZE_CENTS: f32 = 8.0;
+
+/// The seed used for humanizing voices until one is set.
+const DEFAULT_HUMANIZE_SEED: u64 = 0x5EED;
+
+/// The default time in seconds voices fade in and out over.
+const DEFAULT_DECLICK_TIME: f32 = 0.003;
+
+/// Converts a declick time in seconds to the change in gain per sample.
+#[inline]
+fn declick_step(seconds: f32, sample_rate: usize) -> f32 {
+    if seconds > 0.0 {
+        1.0 / (seconds * sample_rate as f32)
+    } else {
+        1.0 // instant change
+    }
+}
+
+/// Converts a pitch bend in semitones to a frequency ratio.
+#[inline]
+fn bend_ratio(semitones: f32) -> f32 {
+    libm::exp2f(semitones / 12.0)
+}
+
+/// The interfaces for controlling the instrument from the framework.
+impl Instrument for AdditiveSynth {
+    fn init(&mut self) {}
+
+    fn param_descriptors(&self) -> &'static [ParamDescriptor] {
+        &params::DESCRIPTORS
+    }
+
+    fn set_param(&mut self, id: ParamId, value: f32) -> Result<(), ParamError> {
+        let param = AdditiveParam::from_id(id).ok_or(ParamError::UnknownParam(id))?;
+        let value = param.descriptor().clamp(value);
+        self.increments_stale = true;
+
+        match param {
+            AdditiveParam::Osc1Level => self.oscillators[0].set_level(value),
+            AdditiveParam::Osc2Level => self.set_oscillator_level(1, value),
+            AdditiveParam::Osc3Level => self.set_oscillator_level(2, value),
+            AdditiveParam::Osc4Level => self.set_oscillator_level(3, value),
+            AdditiveParam::Osc1Detune => self.oscillators[0].set_detune(value),
+            AdditiveParam::Osc2Detune => self.oscillators[1].set_detune(value),
+            AdditiveParam::Osc3Detune => self.oscillators[2].set_detune(value),
+            AdditiveParam::Osc4Detune => self.oscillators[3].set_detune(value),
+            AdditiveParam::GlideTime => self.set_glide_time(value),
+            AdditiveParam::Humanize => self.set_humanize(value),
+        }
+
+        Ok(())
+    }
+
+    /// Called when a note is pressed.
+    fn note_on(&mut self, note: Note, _velocity: u8) -> Result<(), NoteError> {
+        self.start_voice(note, note.frequency())
+    }
+
+    /// Plays the frequency as is, keyed by the nearest note.
+    fn note_on_freq(&mut self, frequency: Hertz, _velocity: u8) -> Result<(), NoteError> {
+        let note = Note::nearest(frequency).ok_or(NoteError::OutOfRange)?;
+        self.start_voice(note, frequency)
+    }
+
+    /// Called when a note is released.
+    fn note_off(&mut self, note: Note) {
+        // Fade the voice for the note out, it's removed once it's silent.
+        if let Some(voice) = self.voices.get_mut(&note) {
+            voice.stopping = true;
+        }
+    }
+
+    /// Released voices that are still fading out don't count as held.
+    fn is_note_on(&self, note: Note) -> bool {
+        self.voices.get(&note).is_some_and(|voice| !voice.stopping)
+    }
+
+    /// Drops every voice without fading them out.
+    fn all_notes_off(&mut self) {
+        self.voices.clear();
+    }
+
+    /// Bends the pitch of all the voices.
+    ///
+    /// The bend is ramped across the next rendered block.
+    fn pitch_bend(&mut self, semitones: f32) {
+        self.pitch_bend = semitones;
+    }
+
+    /// Recalculates the sample rate dependant state of the synth and
+    /// it's voices, the voice phases carry on from where they were.
+    fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.sample_rate = sample_rate;
+        self.increments_stale = true;
+        self.declick_step = declick_step(self.declick_time, sample_rate);
+        self.pitch_envelope.set_sample_rate(sample_rate);
+
+        for (_, voice) in self.voices.iter_mut() {
+            voice.glide.set_sample_rate(sample_rate);
+            voice.pitch_envelope.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn master_gain(&self) -> f32 {
+        self.output.gain()
+    }
+
+    fn set_master_gain(&mut self, gain: f32) {
+        self.output.set_gain(gain);
+    }
+
+    fn peak(&self) -> f32 {
+        self.output.peak()
+    }
+
+    /// Moves the voice playing `from` over to `to` and glides
+    /// it's pitch, keeping the oscillator phases running.
+    fn glide(&mut self, from: Note, to: Note, velocity: u8) -> Result<(), NoteError> {
+        let Some(mut voice) = self.voices.remove(&from) else {
+            return self.note_on(to, velocity);
+        };
+
+        // Start from wherever the voice currently sounds, including a
+        // glide that's still in progress, relative to the new note.
+        //
+        // The ratio is derived from the first oscillator, which
+        // is always enabled and tracks the played note.
+        let from_frequency = self.oscillators[0]
+            .played_frequency(voice.frequency)
+            .hertz();
+        let to_frequency = self.oscillators[0].note_frequency(&to).hertz();
+        let ratio = if to_frequency > 0.0 {
+            voice.glide.value() * from_frequency / to_frequency
+        } else {
+            1.0
+        };
+        voice.glide = Smoothed::new(self.sample_rate, self.glide_time, ratio);
+        voice.glide.set(1.0);
+        voice.frequency = to.frequency();
+
+        self.increments_stale = true;
+        self.voices
+            .insert(to, voice)
+            .map_err(|_| NoteError::NoVoices)?;
+
+        Ok(())
+    }
+}
+
+/// Allows the synth to be used in [`Signal`]` chains.
+impl Signal for AdditiveSynth {
+    type Frame = f32;
+
+    /// Produces the next frame of audio from the synth.
+    ///
+    /// When sampling frame by frame there's no block to ramp
+    /// the pitch bend across, so the bend is applied directly.
+    fn next(&mut self) -> Self::Frame {
+        self.applied_pitch_bend = self.pitch_bend;
+        self.update_increments();
+        let sample = self.next_bent(bend_ratio(self.pitch_bend));
+        self.free_finished_voices();
+        self.output.process(sample)
+    }
+}
+
 impl AudioSource for AdditiveSynth {
     type Frame = f32;
 
     fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
-        for i in 0..buffer.len() {
+        let from = self.applied_pitch_bend;
+        let to = self.pitch_bend;
+
+        // Ramp the pitch bend linearly across the block so that a bend
+        // change doesn't cause a frequency jump at the block boundary.
+        let step = (to - from) / buffer.len() as f32;
+        let constant_ratio = bend_ratio(to);
+
+        self.update_increments();
+
+        for (i, frame) in buffer.iter_mut().enumerate() {
+            let bend = if from == to {
+                constant_ratio
+            } else {
+                bend_ratio(from + step * (i + 1) as f32)
+            };
+
             // Note that the resulting buffer will be clipped on playback
-            // depending on the voice count and frequencies.
+            // depending on the voice count, frequencies and master gain.
             //
             // It's on the receiving end of the rendered buffer to apply
             // amplitude scaling to bring the audio samples down to an
-            // acceptable level for playback.
-            buffer[i] = self.next();
+            // acceptable level for playback, or set the master gain.
+            *frame = self.next_bent(bend);
+        }
+
+        self.applied_pitch_bend = to;
+        self.free_finished_voices();
+        self.output.process_buffer(buffer);
+    }
+
+    /// Voices are freed once they've faded out, so the synth
+    /// is silent when there are no voices left.
+    fn is_silent(&self) -> bool {
+        self.voices.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the distance in samples between each rising zero crossing.
+    fn periods(buffer: &[f32]) -> heapless::Vec<f32, 1024> {
+        let mut periods = heapless::Vec::new();
+        let mut last_crossing = None;
+
+        for i in 1..buffer.len() {
+            if buffer[i - 1] < 0.0 && buffer[i] >= 0.0 {
+                if let Some(last) = last_crossing {
+                    periods.push((i - last) as f32).unwrap();
+                }
+                last_crossing = Some(i);
+            }
+        }
+
+        periods
+    }
+
+    #[test]
+    fn test_pitch_bend_is_ramped_across_block() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        let mut buffer = [0.0; 48_000];
+        synth.render(&mut buffer[..4_800]);
+
+        // Bend up an octave, which would halve the period instantly if unramped.
+        synth.pitch_bend(12.0);
+        synth.render(&mut buffer[4_800..]);
+
+        let periods = periods(&buffer);
+        for pair in periods.windows(2) {
+            assert!(
+                (pair[1] - pair[0]).abs() <= 3.0,
+                "period jumped from {} to {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        // The bend should still reach the full octave by the end of the block.
+        let first = periods[0];
+        let last = periods[periods.len() - 1];
+        assert!((first / last - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_glide_slides_without_retrigger() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_glide_time(0.2);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        let mut buffer = [0.0; 24_000];
+        synth.render(&mut buffer[..4_800]);
+
+        synth.glide(note::CFour, note::GThree, 127).unwrap();
+        synth.render(&mut buffer[4_800..]);
+
+        // The voice is moved over to the new note rather than a new one being started.
+        assert_eq!(synth.voices.len(), 1);
+        assert!(synth.voices.contains_key(&note::GThree));
+
+        // A retrigger would jump straight to the new period, ~37 samples shorter.
+        let periods = periods(&buffer);
+        for pair in periods.windows(2) {
+            assert!(
+                (pair[1] - pair[0]).abs() <= 4.0,
+                "period jumped from {} to {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        // The glide should have settled on the period of the new note.
+        let target = 48_000.0 / synth.oscillators[0].note_frequency(&note::GThree).hertz();
+        let last = periods[periods.len() - 1];
+        assert!((last - target).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_duplicate_patch() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_glide_time(0.2);
+        synth.note_on(note::CFour, 127).unwrap();
+        synth.pitch_bend(2.0);
+
+        let patch = synth.duplicate_patch();
+        assert_eq!(patch.voices.len(), 0);
+        assert_eq!(patch.pitch_bend, 0.0);
+        assert_eq!(patch.glide_time, 0.2);
+
+        // A plain clone carries the sounding voices along with it.
+        let mut clone = synth.clone();
+        let mut a = [0.0; 256];
+        let mut b = [0.0; 256];
+        synth.render(&mut a);
+        clone.render(&mut b);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_pitch_envelope_sweeps_down_to_note() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_pitch_envelope(12.0, 0.05);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        let mut buffer = [0.0; 24_000];
+        synth.render(&mut buffer);
+
+        let periods = periods(&buffer);
+        let note_period = 48_000.0 / synth.oscillators[0].note_frequency(&note::CFour).hertz();
+
+        // The note starts close to an octave up, and settles on the note.
+        assert!(periods[0] < note_period * 0.6, "started at {}", periods[0]);
+        let last = periods[periods.len() - 1];
+        assert!((last - note_period).abs() <= 1.0, "settled at {last}");
+    }
+
+    #[test]
+    fn test_stopped_voice_fades_out() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_declick_time(0.005);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        let mut buffer = [0.0; 4_800];
+        synth.render(&mut buffer);
+
+        // 5ms at 48kHz is 240 samples to fade out over.
+        synth.note_off(note::CFour);
+        synth.render(&mut buffer);
+
+        let peak = |samples: &[f32]| samples.iter().fold(0.0_f32, |p, s| p.max(s.abs()));
+        assert!(peak(&buffer[..60]) > 0.5, "cut off instantly");
+        assert!(peak(&buffer[180..240]) < peak(&buffer[..60]));
+        assert_eq!(peak(&buffer[240..]), 0.0);
+
+        // The voice is freed once it's silent.
+        assert_eq!(synth.voices.len(), 0);
+    }
+
+    #[test]
+    fn test_sample_rate_change_keeps_pitch() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.note_on(note::GThree, 127).unwrap();
+
+        let mut before = [0.0; 4_800];
+        synth.render(&mut before);
+        let before = periods(&before);
+
+        synth.set_sample_rate(96_000);
+        let mut after = [0.0; 9_600];
+        synth.render(&mut after);
+        let after = periods(&after);
+
+        // Twice the samples per period at twice the sample rate is the same pitch.
+        let average = |periods: &[f32]| periods.iter().sum::<f32>() / periods.len() as f32;
+        let ratio = average(&after[1..]) / average(&before[1..]);
+        assert!((ratio - 2.0).abs() < 0.01, "ratio {ratio}");
+    }
+
+    #[test]
+    fn test_master_gain_scales_output() {
+        let mut unity = AdditiveSynth::new(48_000);
+        unity.note_on(note::AFour, 127).unwrap();
+        let mut halved = unity.clone();
+        halved.set_master_gain(0.5);
+
+        let mut full = [0.0; 480];
+        let mut half = [0.0; 480];
+        unity.render(&mut full);
+        halved.render(&mut half);
+
+        for (full, half) in full.iter().zip(half.iter()) {
+            assert!((full * 0.5 - half).abs() < 1e-6);
+        }
+
+        // The meter reads the level after the gain.
+        let peak = full.iter().fold(0.0_f32, |p, s| p.max(s.abs()));
+        assert!(peak > 0.5);
+        assert_eq!(unity.peak(), peak);
+        assert_eq!(halved.peak(), peak * 0.5);
+    }
+
+    #[test]
+    fn test_set_param_changes_output() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.note_on(note::AFour, 127).unwrap();
+        let mut layered = synth.clone();
+
+        let osc2 = ParamId::from(AdditiveParam::Osc2Level);
+        let descriptor = &layered.param_descriptors()[osc2.0 as usize];
+        assert_eq!(descriptor.name, "Osc 2 Level");
+        assert_eq!(descriptor.default, 0.0);
+
+        layered.set_param(osc2, 0.5).unwrap();
+        layered
+            .set_param(AdditiveParam::Osc2Detune.id(), 700.0)
+            .unwrap();
+
+        let mut single = [0.0; 480];
+        let mut both = [0.0; 480];
+        synth.render(&mut single);
+        layered.render(&mut both);
+        assert!(
+            single
+                .iter()
+                .zip(both.iter())
+                .any(|(a, b)| (a - b).abs() > 0.1)
+        );
+
+        assert_eq!(
+            synth.set_param(ParamId(1_000), 1.0),
+            Err(ParamError::UnknownParam(ParamId(1_000)))
+        );
+    }
+
+    /// Plays the same note twice, one after the other, returning the start of each.
+    fn play_twice(synth: &mut AdditiveSynth) -> ([f32; 960], [f32; 960]) {
+        let mut first = [0.0; 960];
+        let mut second = [0.0; 960];
+
+        synth.note_on(note::AFour, 127).unwrap();
+        synth.render(&mut first);
+        synth.note_off(note::AFour);
+
+        // Let the first voice fade out and be freed.
+        let mut tail = [0.0; 480];
+        synth.render(&mut tail);
+        assert_eq!(synth.voices.len(), 0);
+
+        synth.note_on(note::AFour, 127).unwrap();
+        synth.render(&mut second);
+
+        (first, second)
+    }
+
+    #[test]
+    fn test_humanize_decorrelates_voices() {
+        let mut synth = AdditiveSynth::new(48_000);
+        let (first, second) = play_twice(&mut synth);
+        assert_eq!(first, second);
+
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_humanize(1.0);
+        let (first, second) = play_twice(&mut synth);
+
+        // Normalized correlation, 1.0 would be identical waveforms.
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+        let correlation =
+            dot(&first, &second) / libm::sqrtf(dot(&first, &first) * dot(&second, &second));
+        assert!(correlation < 0.9, "correlation {correlation}");
+
+        // The same seed humanizes the same way.
+        let mut a = AdditiveSynth::new(48_000);
+        let mut b = AdditiveSynth::new(48_000);
+        a.set_humanize(1.0);
+        b.set_humanize(1.0);
+        assert_eq!(play_twice(&mut a), play_twice(&mut b));
+    }
+
+    #[test]
+    fn test_full_synth_rejects_notes() {
+        let mut synth = AdditiveSynth::new(48_000);
+        let notes = [
+            note::CFour,
+            note::DFour,
+            note::EFour,
+            note::FFour,
+            note::GFour,
+            note::AFour,
+        ];
+        synth.chord(&notes, 127).unwrap();
+
+        // Only two of the three notes fit, so none of them are played.
+        let result = synth.chord(&[note::CFive, note::DFive, note::EFive], 127);
+        assert!(matches!(result, Err(NoteError::NoVoices)));
+
+        // The two notes that did start are faded out and freed.
+        let mut buffer = [0.0; 480];
+        synth.render(&mut buffer);
+        assert_eq!(synth.voices.len(), notes.len());
+
+        synth.note_on(note::CFive, 127).unwrap();
+        synth.note_on(note::DFive, 127).unwrap();
+        assert!(matches!(
+            synth.note_on(note::EFive, 127),
+            Err(NoteError::NoVoices)
+        ));
+    }
+
+    #[test]
+    fn test_retrigger_picks_up_fading_voice() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_declick_time(0.005);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        let mut buffer = [0.0; 480];
+        synth.render(&mut buffer);
+
+        // Release and press again part way through the fade out.
+        synth.note_off(note::CFour);
+        synth.render(&mut buffer[..120]);
+        let level = synth.voices[&note::CFour].level;
+        let phase = synth.voices[&note::CFour].phase_0;
+        assert!(level > 0.0 && level < 1.0, "level of {level}");
+
+        synth.note_on(note::CFour, 127).unwrap();
+        let voice = &synth.voices[&note::CFour];
+        assert!(!voice.stopping);
+        assert_eq!(voice.level, level);
+        assert_eq!(voice.phase_0, phase);
+        assert_eq!(synth.voices.len(), 1);
+    }
+
+    #[test]
+    fn test_finished_voices_free_up_for_new_notes() {
+        let mut synth = AdditiveSynth::new(48_000);
+        let notes = [
+            note::CFour,
+            note::DFour,
+            note::EFour,
+            note::FFour,
+            note::GFour,
+            note::AFour,
+            note::BFour,
+            note::CFive,
+        ];
+        synth.chord(&notes, 127).unwrap();
+        for note in notes {
+            synth.note_off(note);
+        }
+
+        // Fade the voices out without ending a block, so they aren't freed yet.
+        for _ in 0..480 {
+            synth.next_bent(1.0);
+        }
+        assert_eq!(synth.voices.len(), notes.len());
+
+        // A new note makes room from the finished voices instead of failing.
+        synth.note_on(note::DFive, 127).unwrap();
+        assert_eq!(synth.voices.len(), 1);
+    }
+
+    #[test]
+    fn test_is_note_on() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.note_on(note::CFour, 127).unwrap();
+
+        assert!(synth.is_note_on(note::CFour));
+        assert!(!synth.is_note_on(note::DFour));
+
+        // Still sounding while it fades out, but no longer held.
+        synth.note_off(note::CFour);
+        assert_eq!(synth.voices.len(), 1);
+        assert!(!synth.is_note_on(note::CFour));
+    }
+
+    #[test]
+    fn test_hoisted_increments_match_per_sample_frequency() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_oscillator_level(1, 0.5);
+        synth.note_on(note::AFour, 127).unwrap();
+
+        let mut buffer = [0.0; 4_800];
+        for block in buffer.chunks_mut(64) {
+            synth.render(block);
+        }
+
+        // Works the phases out from the frequency every sample, like the loop used to.
+        let mut phases = [0.0_f32; 4];
+        let mut level = 0.0_f32;
+        for (i, rendered) in buffer.iter().enumerate() {
+            let mut expected = 0.0;
+            for (phase, osc) in phases.iter_mut().zip(synth.oscillators.iter()) {
+                if osc.is_enabled() {
+                    expected += osc.sample::<f32>(*phase);
+                    *phase += osc.note_frequency(&note::AFour).hertz() / 48_000.0;
+                    if *phase >= 1.0 {
+                        *phase -= 1.0;
+                    }
+                }
+            }
+
+            level = (level + synth.declick_step).min(1.0);
+            expected *= level;
+
+            assert!(
+                (rendered - expected).abs() < 1e-3,
+                "sample {i}: {rendered} != {expected}"
+            );
         }
     }
+
+    /// Returns the level of a frequency in a buffer rendered at 48kHz.
+    fn level_at(buffer: &[f32], frequency: f32) -> f32 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, sample) in buffer.iter().enumerate() {
+            let phase = core::f32::consts::TAU * frequency * i as f32 / 48_000.0;
+            re += sample * libm::cosf(phase);
+            im += sample * libm::sinf(phase);
+        }
+        libm::sqrtf(re * re + im * im) * 2.0 / buffer.len() as f32
+    }
+
+    #[test]
+    fn test_odd_harmonics() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_odd_harmonics(4, 1.0);
+        synth.note_on(note::AFour, 127).unwrap();
+
+        // Skip the declick fade in, then take a whole number of periods.
+        let mut buffer = [0.0; 4_800];
+        synth.render(&mut buffer);
+        synth.render(&mut buffer);
+
+        let fundamental = note::AFour.frequency().hertz();
+        for harmonic in [1.0, 3.0, 5.0, 7.0] {
+            let level = level_at(&buffer, fundamental * harmonic);
+            assert!(
+                (level - 1.0 / harmonic).abs() < 0.02,
+                "harmonic {harmonic} at {level}"
+            );
+        }
+        for harmonic in [2.0, 4.0, 6.0, 8.0] {
+            let level = level_at(&buffer, fundamental * harmonic);
+            assert!(level < 0.01, "even harmonic {harmonic} at {level}");
+        }
+    }
+
+    #[test]
+    fn test_even_harmonics() {
+        let mut synth = AdditiveSynth::new(48_000);
+        synth.set_even_harmonics(2, 0.0);
+        synth.note_on(note::AFour, 127).unwrap();
+
+        let mut buffer = [0.0; 4_800];
+        synth.render(&mut buffer);
+        synth.render(&mut buffer);
+
+        let fundamental = note::AFour.frequency().hertz();
+        assert!(level_at(&buffer, fundamental) < 0.01);
+        assert!(level_at(&buffer, fundamental * 2.0) > 0.95);
+        assert!(level_at(&buffer, fundamental * 4.0) > 0.95);
+        assert!(level_at(&buffer, fundamental * 6.0) < 0.01);
+    }
+
+    #[test]
+    fn test_note_on_freq_plays_exact_frequency() {
+        let mut synth = AdditiveSynth::new(48_000);
+        // A single harmonic plays the fundamental as is.
+        synth.set_odd_harmonics(1, 0.0);
+        synth.note_on_freq(Hertz(445.0), 127).unwrap();
+        assert!(synth.is_note_on(note::AFour));
+
+        // 0.2s holds a whole number of periods of 445Hz, and one less of 440Hz.
+        let mut buffer = [0.0; 9_600];
+        synth.render(&mut buffer);
+        synth.render(&mut buffer);
+
+        let level = level_at(&buffer, 445.0);
+        assert!(level > 0.95, "445Hz at {level}");
+        let quantized = level_at(&buffer, 440.0);
+        assert!(quantized < 0.05, "440Hz at {quantized}");
+
+        synth.note_off(note::AFour);
+        assert!(!synth.is_note_on(note::AFour));
+    }
+
+    #[test]
+    fn test_silent_after_release() {
+        let mut synth = AdditiveSynth::new(48_000);
+        assert!(synth.is_silent());
+
+        synth.chord(&[note::CFour, note::EFour], 127).unwrap();
+        let mut buffer = [0.0; 480];
+        synth.render(&mut buffer);
+        assert!(!synth.is_silent());
+
+        // Still fading out straight after the release.
+        synth.note_off(note::CFour);
+        synth.note_off(note::EFour);
+        assert!(!synth.is_silent());
+
+        synth.render(&mut buffer);
+        assert!(synth.is_silent());
+        synth.render(&mut buffer);
+        assert!(buffer.iter().all(|sample| *sample == 0.0));
+    }
 }
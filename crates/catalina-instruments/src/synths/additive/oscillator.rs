@@ -6,6 +6,7 @@ use catalina_engine::{
 
 /// Implements the oscillators for the additive synth, including parameters
 /// for per-oscillator phase shifting and frequency adjustments.
+#[derive(Clone)]
 pub(crate) struct AdditiveOscillator {
     enabled: bool,
     /// Base frequency of the oscillator.
@@ -20,6 +21,14 @@ pub(crate) struct AdditiveOscillator {
 
     /// The amplitude level in the range 0..1 for the oscillator.
     level: f32,
+
+    /// The frequency ratio of the detune in cents, cached
+    /// to avoid recalculating it for every sample.
+    detune_ratio: f32,
+
+    /// Plays the oscillator at a multiple of the played note's
+    /// frequency instead of offsetting from the base frequency.
+    harmonic: Option<f32>,
 }
 
 impl AdditiveOscillator {
@@ -29,9 +38,34 @@ impl AdditiveOscillator {
             base_frequency,
             fixed_frequency: false,
             level: 1.0,
+            detune_ratio: 1.0,
+            harmonic: None,
         }
     }
 
+    /// Enables or disables the oscillator.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the amplitude level in the range 0..1 for the oscillator.
+    #[inline]
+    pub fn set_level(&mut self, level: f32) {
+        self.level = level.clamp(0.0, 1.0);
+    }
+
+    /// Detunes the oscillator from the played note by an amount of cents.
+    pub fn set_detune(&mut self, cents: f32) {
+        self.detune_ratio = libm::exp2f(cents / 1200.0);
+    }
+
+    /// Tracks the played note at a multiple of it's frequency, i.e. 3.0 for the
+    /// third harmonic, or offsets from the base frequency again when `None`.
+    pub fn set_harmonic(&mut self, ratio: Option<f32>) {
+        self.harmonic = ratio;
+    }
+
     /// Returns if the oscillator is enabled.
     #[inline]
     pub const fn is_enabled(&self) -> bool {
@@ -47,22 +81,29 @@ impl AdditiveOscillator {
     /// for the oscillator given the specified note.
     #[inline]
     pub fn note_frequency(&self, note: &'_ Note) -> Hertz {
+        self.played_frequency(note.frequency())
+    }
+
+    /// Calculates the frequency that should be used for the oscillator
+    /// given the frequency being played, which may not be a note.
+    #[inline]
+    pub fn played_frequency(&self, played: Hertz) -> Hertz {
         // If we're using a fixed frequency, then we don't
-        // apply an offset based on the played note.
+        // apply an offset based on the played frequency.
         if self.fixed_frequency {
-            return self.base_frequency;
+            return self.base_frequency * self.detune_ratio;
         }
 
-        // Get the frequency of the note in hertz.
-        //
-        // We use this as the base frequency of our oscillators so
-        // that the oscillator plays in-key with the triggered note.
-        let note_freq = note.frequency();
+        // We use the played frequency as the base frequency of our
+        // oscillators so that the oscillator plays in-key with it.
+        if let Some(ratio) = self.harmonic {
+            return played * ratio * self.detune_ratio;
+        }
 
-        // Relatively offset the base frequency based on the played note.
-        let offset_freq = self.base_frequency - note_freq;
+        // Relatively offset the base frequency based on the played frequency.
+        let offset_freq = self.base_frequency - played;
 
-        self.base_frequency + offset_freq
+        (self.base_frequency + offset_freq) * self.detune_ratio
     }
 
     /// Sample the oscillator with the provided phase.
@@ -0,0 +1,113 @@
+//! The parameters of the additive synth exposed to hosts.
+
+use catalina_engine::core::param::{ParamDescriptor, ParamId, ParamUnit};
+
+/// The parameters of the [`AdditiveSynth`](super::AdditiveSynth).
+///
+/// Each parameter's [`ParamId`] is it's discriminant, and it's
+/// range and default are described by [`AdditiveParam::descriptor`].
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[repr(u16)]
+pub enum AdditiveParam {
+    Osc1Level,
+    Osc1Detune,
+    Osc2Level,
+    Osc2Detune,
+    Osc3Level,
+    Osc3Detune,
+    Osc4Level,
+    Osc4Detune,
+    GlideTime,
+    Humanize,
+}
+
+impl AdditiveParam {
+    /// Every parameter, in the order of their IDs.
+    pub const ALL: [AdditiveParam; 10] = [
+        AdditiveParam::Osc1Level,
+        AdditiveParam::Osc1Detune,
+        AdditiveParam::Osc2Level,
+        AdditiveParam::Osc2Detune,
+        AdditiveParam::Osc3Level,
+        AdditiveParam::Osc3Detune,
+        AdditiveParam::Osc4Level,
+        AdditiveParam::Osc4Detune,
+        AdditiveParam::GlideTime,
+        AdditiveParam::Humanize,
+    ];
+
+    pub const fn id(self) -> ParamId {
+        ParamId(self as u16)
+    }
+
+    /// Looks up the parameter with the provided ID.
+    pub fn from_id(id: ParamId) -> Option<Self> {
+        Self::ALL.get(id.0 as usize).copied()
+    }
+
+    /// Describes the name, range and default of the parameter.
+    pub fn descriptor(self) -> &'static ParamDescriptor {
+        &DESCRIPTORS[self as usize]
+    }
+}
+
+impl From<AdditiveParam> for ParamId {
+    fn from(param: AdditiveParam) -> Self {
+        param.id()
+    }
+}
+
+/// Builds the descriptor for an oscillator's level.
+///
+/// Only the first oscillator is enabled by default, the
+/// others are enabled by raising their level above zero.
+const fn level(param: AdditiveParam, name: &'static str, default: f32) -> ParamDescriptor {
+    ParamDescriptor {
+        id: param.id(),
+        name,
+        min: 0.0,
+        max: 1.0,
+        default,
+        unit: ParamUnit::None,
+    }
+}
+
+/// Builds the descriptor for an oscillator's detune.
+const fn detune(param: AdditiveParam, name: &'static str) -> ParamDescriptor {
+    ParamDescriptor {
+        id: param.id(),
+        name,
+        min: -100.0,
+        max: 100.0,
+        default: 0.0,
+        unit: ParamUnit::Cents,
+    }
+}
+
+/// The descriptors of every parameter, indexed by their IDs.
+pub static DESCRIPTORS: [ParamDescriptor; 10] = [
+    level(AdditiveParam::Osc1Level, "Osc 1 Level", 1.0),
+    detune(AdditiveParam::Osc1Detune, "Osc 1 Detune"),
+    level(AdditiveParam::Osc2Level, "Osc 2 Level", 0.0),
+    detune(AdditiveParam::Osc2Detune, "Osc 2 Detune"),
+    level(AdditiveParam::Osc3Level, "Osc 3 Level", 0.0),
+    detune(AdditiveParam::Osc3Detune, "Osc 3 Detune"),
+    level(AdditiveParam::Osc4Level, "Osc 4 Level", 0.0),
+    detune(AdditiveParam::Osc4Detune, "Osc 4 Detune"),
+    ParamDescriptor {
+        id: AdditiveParam::GlideTime.id(),
+        name: "Glide Time",
+        min: 0.0,
+        max: 2.0,
+        default: 0.06,
+        unit: ParamUnit::Seconds,
+    },
+    ParamDescriptor {
+        id: AdditiveParam::Humanize.id(),
+        name: "Humanize",
+        min: 0.0,
+        max: 1.0,
+        default: 0.0,
+        unit: ParamUnit::None,
+    },
+];
@@ -1,3 +1,8 @@
+use catalina_engine::{
+    audio::envelope::adsr::Envelope,
+    core::{Hertz, param::Smoothed},
+};
+
 /// A voice renders the output sound from the synth.
 ///
 /// In a monophonic synth there is a single voice that
@@ -5,7 +10,12 @@
 ///
 /// In polyphonic synths there are several voices that
 /// can play sounds from multiple keys at once.
+#[derive(Clone)]
 pub(crate) struct Voice {
+    /// The frequency the voice is playing, usually the frequency of
+    /// it's note but it can be any frequency when played directly.
+    pub(crate) frequency: Hertz,
+
     /// Phase of the voice to be fed to the oscillators.
     ///
     /// Note that because the speed of the phase change is
@@ -20,16 +30,51 @@ pub(crate) struct Voice {
     pub(crate) phase_1: f32,
     pub(crate) phase_2: f32,
     pub(crate) phase_3: f32,
+
+    /// How far each oscillator's phase advances per sample before any
+    /// bend, worked out once per block rather than every sample.
+    pub(crate) increments: [f32; 4],
+
+    /// Frequency ratio applied on top of the note's frequency.
+    ///
+    /// Gliding a voice to a new note starts the ratio at
+    /// the old pitch and smooths it towards unison (1.0).
+    pub(crate) glide: Smoothed,
+
+    /// Fixed frequency ratio the voice is detuned by when humanized.
+    pub(crate) detune: f32,
+
+    /// Sweeps the pitch of the voice from the start of the note.
+    pub(crate) pitch_envelope: Envelope,
+
+    /// Declick gain that fades the voice in when it starts
+    /// and out when it's stopped, from 0.0 to 1.0.
+    pub(crate) level: f32,
+    /// Set when the voice is fading out, it's removed once silent.
+    pub(crate) stopping: bool,
 }
 
 impl Voice {
     /// Constructs a new voice for the additive synth.
-    pub fn new() -> Self {
+    pub fn new(sample_rate: usize, frequency: Hertz, pitch_envelope: Envelope) -> Self {
         Self {
+            frequency,
+
             phase_0: 0.0,
             phase_1: 0.0,
             phase_2: 0.0,
             phase_3: 0.0,
+
+            increments: [0.0; 4],
+
+            glide: Smoothed::new(sample_rate, 0.0, 1.0),
+
+            detune: 1.0,
+
+            pitch_envelope,
+
+            level: 0.0,
+            stopping: false,
         }
     }
 }
@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use catalina::engine::{
+    audio::{AudioSource, Frame},
+    instrument::Instrument,
+    music::note::{self, Note},
+};
+use cpal::{
+    FromSample, Sample, SizedSample,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+};
+
+use crate::voice::FilteredVoice;
+
+// See this crate for how the voice is put together.
+mod voice;
+
+/// The notes played by the example, as the note and
+/// the times in seconds it's pressed and released at.
+const PHRASE: [(Note, f32, f32); 4] = [
+    (note::CThree, 0.0, 0.8),
+    (note::EFlatThree, 1.0, 1.8),
+    (note::GThree, 2.0, 2.4),
+    (note::CFour, 2.5, 3.5),
+];
+
+/// How long the phrase is played for, leaving time for the last release.
+const DURATION_SECS: f32 = 4.0;
+
+/// The sample rate the WAV file is rendered at.
+const WAV_SAMPLE_RATE: u32 = 44100;
+
+/// Presses and releases the notes of the phrase that fall on the given sample.
+fn play_phrase(voice: &mut FilteredVoice, sample: usize, sample_rate: usize) {
+    let at = |secs: f32| (secs * sample_rate as f32) as usize;
+
+    for (note, on, off) in PHRASE {
+        if at(on) == sample {
+            voice.note_on(note, 127).unwrap();
+        } else if at(off) == sample {
+            voice.note_off(note);
+        }
+    }
+}
+
+/// Renders the phrase to a mono WAV file.
+fn render_wav(path: &Path) -> Result<(), hound::Error> {
+    // Set the specification for the wave file we're going to create.
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: WAV_SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+
+    let mut writer = hound::WavWriter::create(path, spec)?;
+
+    let sample_rate = WAV_SAMPLE_RATE as usize;
+    let mut voice = FilteredVoice::new(sample_rate);
+
+    let total_samples = (DURATION_SECS * sample_rate as f32) as usize;
+    for index in 0..total_samples {
+        play_phrase(&mut voice, index, sample_rate);
+
+        let mut sample = [0.0];
+        AudioSource::render(&mut voice, &mut sample);
+        writer.write_sample(sample[0] * 0.5)?;
+    }
+
+    writer.finalize()
+}
+
+fn main() -> anyhow::Result<()> {
+    render_wav(Path::new("filtered-synth.wav"))?;
+    println!("Phrase written to 'filtered-synth.wav'");
+
+    // Retrieve the default audio output device for the current device.
+    let default_out = cpal::default_host()
+        .default_output_device()
+        .expect("failed to find output device");
+
+    // Retrieve the audio config for the device output.
+    let out_config = default_out.default_output_config()?;
+
+    // Play the phrase using the appropriate encoding given
+    // the sample format expected by the output device.
+    match out_config.sample_format() {
+        cpal::SampleFormat::I8 => run::<i8>(&default_out, &out_config.into()),
+        cpal::SampleFormat::I16 => run::<i16>(&default_out, &out_config.into()),
+        cpal::SampleFormat::I32 => run::<i32>(&default_out, &out_config.into()),
+        cpal::SampleFormat::I64 => run::<i64>(&default_out, &out_config.into()),
+        cpal::SampleFormat::U8 => run::<u8>(&default_out, &out_config.into()),
+        cpal::SampleFormat::U16 => run::<u16>(&default_out, &out_config.into()),
+        cpal::SampleFormat::U32 => run::<u32>(&default_out, &out_config.into()),
+        cpal::SampleFormat::U64 => run::<u64>(&default_out, &out_config.into()),
+        cpal::SampleFormat::F32 => run::<f32>(&default_out, &out_config.into()),
+        cpal::SampleFormat::F64 => run::<f64>(&default_out, &out_config.into()),
+        sample_format => panic!("Unsupported sample format '{sample_format}'"),
+    }
+}
+
+pub fn run<T>(device: &cpal::Device, config: &cpal::StreamConfig) -> Result<(), anyhow::Error>
+where
+    T: SizedSample + FromSample<f32> + Frame,
+    <T as Frame>::Sample: FromSample<f32>,
+{
+    let sample_rate = config.sample_rate.0 as usize;
+    let channels = config.channels as usize;
+
+    let mut voice = FilteredVoice::new(sample_rate);
+    let mut index = 0;
+
+    let err_fn = |err| eprintln!("an error occurred on stream: {err}");
+
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            for frame in data.chunks_mut(channels) {
+                play_phrase(&mut voice, index, sample_rate);
+                index += 1;
+
+                let mut f = [0.0];
+                AudioSource::render(&mut voice, &mut f);
+
+                // Write the sample to the left, and if present, the right channel.
+                for sample in frame.iter_mut() {
+                    *sample = f[0].scale_amp(0.5).to_sample();
+                }
+            }
+        },
+        err_fn,
+        None,
+    )?;
+
+    stream.play()?;
+
+    std::thread::sleep(std::time::Duration::from_secs_f32(DURATION_SECS));
+
+    Ok(())
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_phrase_to_wav() {
+        let path = std::env::temp_dir().join("filtered-synth-test.wav");
+        render_wav(&path).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.sample_rate, WAV_SAMPLE_RATE);
+        assert_eq!(spec.channels, 1);
+
+        // The file runs for the full length of the phrase.
+        let expected = (DURATION_SECS * WAV_SAMPLE_RATE as f32) as u32;
+        assert_eq!(reader.duration(), expected);
+
+        // And actually has the notes in it.
+        let peak = reader
+            .samples::<f32>()
+            .map(|sample| sample.unwrap().abs())
+            .fold(0.0, f32::max);
+        assert!(peak > 0.1, "rendered phrase peaked at {peak}");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use catalina::engine::{
+    audio::{
+        AudioSource,
+        envelope::adsr::Envelope,
+        filter::Lowpass,
+        lfo::Lfo,
+        oscillator::{OscillatorType, RuntimeOscillator, ToneSource},
+        signal::Signal,
+    },
+    core::Hertz,
+    instrument::{Instrument, NoteError},
+    music::note::Note,
+};
+
+/// The cutoff of the filter while it's envelope is closed.
+const BASE_CUTOFF: f32 = 200.0;
+
+/// How far the filter envelope opens the cutoff above the base cutoff.
+const CUTOFF_AMOUNT: f32 = 4_000.0;
+
+/// How far the vibrato bends the pitch either side of the note, in semitones.
+const VIBRATO_DEPTH: f32 = 0.15;
+
+/// A monophonic subtractive voice.
+///
+/// The signal runs through the stack as follows:
+///
+/// 1. A saw oscillator, with it's pitch wobbled by an LFO for vibrato.
+/// 2. A resonant lowpass filter, with it's cutoff swept by an envelope.
+/// 3. An amplitude envelope, shaping the volume of the note.
+pub struct FilteredVoice {
+    oscillator: RuntimeOscillator,
+    vibrato: Lfo,
+
+    filter: Lowpass,
+    filter_envelope: Envelope,
+
+    amp_envelope: Envelope,
+
+    /// The note that's currently held, if any.
+    note: Option<Note>,
+    /// The frequency of the last pressed note, before vibrato.
+    frequency: Hertz,
+    /// The gain from the velocity of the last pressed note.
+    velocity: f32,
+}
+
+impl FilteredVoice {
+    pub fn new(sample_rate: usize) -> Self {
+        let mut filter = Lowpass::new(sample_rate);
+        filter.set_resonance(0.6);
+
+        // A quick pluck that settles to a darker sustain.
+        let mut filter_envelope = Envelope::new(sample_rate);
+        filter_envelope.set_attack_time(0.01, 0.0);
+        filter_envelope.set_decay_time(0.4);
+        filter_envelope.set_sustain_level(0.3);
+        filter_envelope.set_release_time(0.5);
+
+        let mut amp_envelope = Envelope::new(sample_rate);
+        amp_envelope.set_attack_time(0.005, 0.0);
+        amp_envelope.set_decay_time(0.2);
+        amp_envelope.set_sustain_level(0.8);
+        amp_envelope.set_release_time(0.3);
+
+        Self {
+            oscillator: RuntimeOscillator::new(OscillatorType::Saw, sample_rate, Hertz(440.0)),
+            vibrato: Lfo::new(OscillatorType::Sine, sample_rate, Hertz(5.0)),
+            filter,
+            filter_envelope,
+            amp_envelope,
+            note: None,
+            frequency: Hertz(440.0),
+            velocity: 0.0,
+        }
+    }
+}
+
+impl AudioSource for FilteredVoice {
+    type Frame = f32;
+
+    fn render(&mut self, buffer: &'_ mut [Self::Frame]) {
+        let gate = self.note.is_some();
+
+        for sample in buffer.iter_mut() {
+            // Bend the oscillator around the note for vibrato.
+            let bend = Signal::next(&mut self.vibrato) * VIBRATO_DEPTH / 12.0;
+            ToneSource::set_frequency(
+                &mut self.oscillator,
+                Hertz(self.frequency.0 * 2f32.powf(bend)),
+            );
+            let tone = ToneSource::next(&mut self.oscillator);
+
+            // Open the filter with it's envelope.
+            let sweep = self.filter_envelope.process(gate);
+            self.filter
+                .set_cutoff(Hertz(BASE_CUTOFF + sweep * CUTOFF_AMOUNT));
+            let filtered = self.filter.process(tone);
+
+            *sample = filtered * self.amp_envelope.process(gate) * self.velocity;
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        self.note.is_none() && self.amp_envelope.is_idle()
+    }
+}
+
+impl Signal for FilteredVoice {
+    type Frame = f32;
+
+    fn next(&mut self) -> Self::Frame {
+        let mut sample = [0.0];
+        AudioSource::render(self, &mut sample);
+        sample[0]
+    }
+}
+
+impl Instrument for FilteredVoice {
+    fn init(&mut self) {}
+
+    fn note_on(&mut self, note: Note, velocity: u8) -> Result<(), NoteError> {
+        // Only restart the vibrato and filter from a silent voice,
+        // so that playing legato doesn't click.
+        if self.is_silent() {
+            self.vibrato.reset();
+            self.filter.reset();
+        }
+
+        self.note = Some(note);
+        self.frequency = note.frequency();
+        self.velocity = velocity as f32 / 127.0;
+
+        Ok(())
+    }
+
+    fn note_off(&mut self, note: Note) {
+        if self.note == Some(note) {
+            self.note = None;
+        }
+    }
+
+    fn is_note_on(&self, note: Note) -> bool {
+        self.note == Some(note)
+    }
+
+    fn all_notes_off(&mut self) {
+        self.note = None;
+        self.filter_envelope.reset();
+        self.amp_envelope.reset();
+    }
+}
@@ -0,0 +1,51 @@
+use catalina::engine::{
+    audio::{
+        AudioSource,
+        oscillator::{ToneSource, variable::VariableShapeOscillator},
+    },
+    core::Hertz,
+};
+
+fn main() {
+    // Set the specification for the wave file we're going to create.
+    let spec = hound::WavSpec {
+        channels: 1,         // mono
+        sample_rate: 44100,  // samples per second
+        bits_per_sample: 32, // bit depth
+        sample_format: hound::SampleFormat::Float,
+    };
+
+    // Create a WAV writer using the specification
+    let mut writer =
+        hound::WavWriter::create("waveshape.wav", spec).expect("Failed to create WAV file");
+
+    // Create a variable shape oscillator playing middle C.
+    let mut osc = VariableShapeOscillator::new(spec.sample_rate as usize);
+    ToneSource::set_frequency(&mut osc, Hertz::from_hertz(261.63));
+    osc.set_amplitude(0.8);
+    osc.set_pulse_width(0.5);
+    osc.set_waveshape(0.0);
+    osc.snap_parameters();
+
+    let duration_secs = 4.0; // 4 seconds
+    let sample_rate = spec.sample_rate as f32;
+    let total_samples = (sample_rate * duration_secs) as usize;
+
+    // Render in blocks, moving the waveshape from a triangle
+    // (0.0) to a square (1.0) a little further each block.
+    let mut buffer = [0.0; 256];
+    let mut rendered = 0;
+    while rendered < total_samples {
+        osc.set_waveshape(rendered as f32 / total_samples as f32);
+        osc.render(&mut buffer);
+
+        for sample in buffer.iter().take(total_samples - rendered) {
+            writer.write_sample(*sample).unwrap();
+        }
+
+        rendered += buffer.len();
+    }
+
+    writer.finalize().unwrap();
+    println!("Waveshape sweep written to 'waveshape.wav'");
+}
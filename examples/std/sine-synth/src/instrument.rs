@@ -79,6 +79,11 @@ impl AudioSource for SineInstrument {
             buffer[i] = sample;
         }
     }
+
+    /// Voices are removed as soon as their note is released.
+    fn is_silent(&self) -> bool {
+        self.voices.is_empty()
+    }
 }
 
 impl Signal for SineInstrument {
@@ -142,4 +147,51 @@ impl Instrument for SineInstrument {
         // Remove the voice for the note when the note is released.
         self.voices.remove(&note);
     }
+
+    fn is_note_on(&self, note: Note) -> bool {
+        // Voices only exist while their note is pressed.
+        self.voices.contains_key(&note)
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use catalina::engine::{
+        audio::oscillator::sample_sine,
+        instrument::test_support::{SequenceEvent, assert_close, render_sequence},
+        music::note,
+    };
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    const EVENTS: [SequenceEvent; 2] = [
+        SequenceEvent::NoteOn {
+            frame: 0,
+            note: note::AFour,
+            velocity: 127,
+        },
+        SequenceEvent::NoteOff {
+            frame: 4_800,
+            note: note::AFour,
+        },
+    ];
+
+    #[test]
+    fn test_render_is_stable() {
+        let first = render_sequence(&mut SineInstrument::new(SAMPLE_RATE), &EVENTS, 9_600).unwrap();
+        let second =
+            render_sequence(&mut SineInstrument::new(SAMPLE_RATE), &EVENTS, 9_600).unwrap();
+        assert_close(&first, &second, 0.0);
+
+        // A single held A4 starts out as a plain 440Hz sine, only the
+        // start is compared as the oscillator's phase drifts a little.
+        let expected: Vec<f32> = (0..480)
+            .map(|i| sample_sine(i, SAMPLE_RATE, note::AFour.frequency()))
+            .collect();
+        assert_close(&first[..480], &expected, 1e-3);
+        assert!(first[4_800..].iter().all(|sample| *sample == 0.0));
+    }
 }
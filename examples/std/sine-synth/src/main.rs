@@ -1,6 +1,9 @@
 use catalina::engine::{
-    audio::{AudioSource, Frame},
-    instrument::Instrument,
+    audio::Frame,
+    instrument::{
+        EventQueue,
+        InstrumentEvent::{NoteOff, NoteOn},
+    },
     music::note,
 };
 use cpal::{
@@ -71,46 +74,56 @@ where
 
     let err_fn = |err| eprintln!("an error occurred on stream: {err}");
 
-    let time_at_start = std::time::Instant::now();
-    let mut step = 0;
+    // Queue up some note on and off events, timed in samples from the start.
+    let mut events = EventQueue::<8>::new();
+    let at = |secs: usize| secs * sample_rate;
+    for (offset, event) in [
+        (
+            at(0),
+            NoteOn {
+                note: note::CFour,
+                velocity: 127,
+            },
+        ),
+        (
+            at(1),
+            NoteOn {
+                note: note::DFour,
+                velocity: 127,
+            },
+        ),
+        (
+            at(2),
+            NoteOn {
+                note: note::GFour,
+                velocity: 127,
+            },
+        ),
+        (at(3), NoteOff { note: note::CFour }),
+        (at(4), NoteOff { note: note::DFour }),
+        (at(5), NoteOff { note: note::GFour }),
+    ] {
+        events.push(offset, event).unwrap();
+    }
 
     let stream = device.build_output_stream(
         config,
         move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
-            // Use the time since start to automatically change notes.
-            let time_since_start = std::time::Instant::now()
-                .duration_since(time_at_start)
-                .as_secs_f32();
-
-            // Simulate some note on and off events for now.
-            if step < 1 {
-                inst.note_on(note::CFour, 255).unwrap();
-                step = 1;
-            } else if time_since_start > 1.0 && step < 2 {
-                inst.note_on(note::DFour, 255).unwrap();
-                step = 2;
-            } else if time_since_start > 2.0 && step < 3 {
-                inst.note_on(note::GFour, 255).unwrap();
-                step = 3;
-            } else if time_since_start > 3.0 && step < 4 {
-                inst.note_off(note::CFour);
-                step = 4;
-            } else if time_since_start > 4.0 && step < 5 {
-                inst.note_off(note::DFour);
-                step = 5;
-            } else if time_since_start > 5.0 && step < 6 {
-                inst.note_off(note::GFour);
-                step = 6;
-            }
-
             for frame in data.chunks_mut(channels) {
                 // Render a single sample from the instrument.
                 //
                 // Note: This isn't terribly efficiant, should
                 //  look at options for passing an entire slice
                 //  in with dasp_slice with slice::to_frame_slice(
+                //
+                // Any events due on this sample are applied first.
                 let mut f: [f32; 1] = [0_f32; 1];
-                inst.render(&mut f);
+
+                // Events the instrument can't play, i.e. with every voice
+                // in use, are logged rather than panicking the audio thread.
+                if let Err(err) = events.render(&mut inst, &mut f) {
+                    eprintln!("failed to apply event: {err:?}");
+                }
 
                 // Write the sample to the left, and if present, the right channel.
                 for sample in frame.iter_mut() {